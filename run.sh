@@ -3,4 +3,26 @@ set -euo pipefail
 
 make
 
-cd build && ./veriload "$@" main libfoo.so libbar.so libbaz.so libunused.so libc.so
+out=$(mktemp)
+trap 'rm -f "$out"' EXIT
+
+(cd build && ./veriload "$@" "${MAIN:-main}") | tee "$out"
+
+# Every destructor runs exactly once, in reverse init order.
+grep -E '^\[[a-z]+\] (dtor|DT_FINI)$' "$out" | diff -u tests/expected-dtors.txt -
+
+# With default options the whole run matches the README's expected output,
+# kept in tests/expected-output.txt with thread-local addresses masked.
+if [[ $# -eq 0 ]]; then
+    sed -E 's/&tls=0x[0-9a-f]+/\&tls=ADDR/' "$out" \
+        | diff -u <(sed "s/^\[main\] argv\[0\]=main\$/[main] argv[0]=${MAIN:-main}/" tests/expected-output.txt) -
+fi
+
+# Static executables run their own startup code and need no libraries.
+for exe in static static-pie; do
+    (cd build && ./veriload "$exe") | diff -u tests/expected-static.txt -
+done
+
+# A program without libc start code calls the %rdx finalizer itself (twice);
+# the destructor runs once.
+(cd build && ./veriload rtldfini) | diff -u tests/expected-rtldfini.txt -
@@ -17,8 +17,8 @@ fi
 
 exec ${PODMAN_RUN_PREFIX} sh -lc '
     cd build
-    ./veriload main libfoo.so libbar.so libbaz.so libunused.so libc.so
-    ./veriload /bin/busybox libc.musl-x86_64.so.1
-    ./veriload /usr/bin/file /usr/lib/libmagic.so.1 libc.musl-x86_64.so.1
-    ./veriload /usr/bin/readelf /usr/lib/libctf-nobfd.so.0 /usr/lib/libz.so.1 /usr/lib/libzstd.so.1 /usr/lib/libsframe.so.2 libc.musl-x86_64.so.1
+    ./veriload main
+    ./veriload /bin/busybox
+    ./veriload /usr/bin/file
+    ./veriload /usr/bin/readelf
   '
@@ -0,0 +1,83 @@
+use crate::consts::*;
+use crate::search_path::DynamicPeek;
+use crate::types::{LoaderError, LoaderErrorReason, LoaderStage};
+
+// Randomized bases land in [ASLR_BASE_LO, ASLR_BASE_HI). The window sits above
+// the usual ET_EXEC addresses and ends where the packed layout starts, so an
+// object that falls back to packing never collides with a randomized one.
+const ASLR_BASE_LO: u64 = 0x1000_0000_0000;
+const ASLR_BASE_HI: u64 = DYN_BASE_START;
+const ASLR_ATTEMPTS: usize = 64;
+
+unsafe extern "C" {
+    fn getrandom(buf: *mut u8, len: usize, flags: u32) -> isize;
+}
+
+// SplitMix64: small, seedable, and good enough to spread load bases. The
+// randomness only has to be unpredictable when the seed itself is.
+pub struct BaseRng {
+    state: u64,
+}
+
+impl BaseRng {
+    pub fn from_seed(seed: u64) -> BaseRng {
+        BaseRng { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+pub fn random_seed() -> Result<u64, LoaderError> {
+    let mut buf = [0u8; 8];
+    let got = unsafe { getrandom(buf.as_mut_ptr(), buf.len(), 0) };
+    if got != buf.len() as isize {
+        return Err(LoaderError {
+            stage: LoaderStage::Input,
+            object_name: Vec::new(),
+            reason: LoaderErrorReason::Io { errno: std::io::Error::last_os_error().raw_os_error().unwrap_or(0) },
+        });
+    }
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn overlaps(taken: &[(u64, u64)], lo: u64, hi: u64) -> bool {
+    taken.iter().any(|&(t_lo, t_hi)| lo < t_hi.saturating_add(PAGE_SIZE) && t_lo < hi.saturating_add(PAGE_SIZE))
+}
+
+// Draws one base per `ET_DYN` object, aligned to the object's largest segment
+// alignment and keeping a guard page between objects. Objects that do not fit
+// after a few attempts get `None` and fall back to the packed layout; the
+// verified mmap stage still rejects any base that is misaligned or overlaps.
+pub fn pick_load_bases(peeks: &[&DynamicPeek], rng: &mut BaseRng) -> Vec<Option<u64>> {
+    let mut taken: Vec<(u64, u64)> = Vec::new();
+    let mut bases = Vec::new();
+    for peek in peeks {
+        if peek.elf_type != ET_DYN || peek.load_lo >= peek.load_hi {
+            bases.push(None);
+            continue;
+        }
+        let align = peek.load_align.max(PAGE_SIZE);
+        let window = ASLR_BASE_HI - ASLR_BASE_LO;
+        let mut picked = None;
+        if align.is_power_of_two() && peek.load_hi < window {
+            let slots = (window - peek.load_hi) / align + 1;
+            for _ in 0..ASLR_ATTEMPTS {
+                let base = ASLR_BASE_LO + (rng.next_u64() % slots) * align;
+                let (start, end) = (base + peek.load_lo, base + peek.load_hi);
+                if !overlaps(&taken, start, end) {
+                    taken.push((start, end));
+                    picked = Some(base);
+                    break;
+                }
+            }
+        }
+        bases.push(picked);
+    }
+    bases
+}
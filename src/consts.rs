@@ -26,6 +26,9 @@ pub const EM_X86_64: u16 = 62;
 
 pub const PT_LOAD: u32 = 1;
 pub const PT_DYNAMIC: u32 = 2;
+pub const PT_INTERP: u32 = 3;
+pub const PT_TLS: u32 = 7;
+pub const PT_GNU_RELRO: u32 = 0x6474_e552;
 
 pub const PF_X: u32 = 0x1;
 pub const PF_W: u32 = 0x2;
@@ -35,6 +38,7 @@ pub const DT_NULL: i64 = 0;
 pub const DT_NEEDED: i64 = 1;
 pub const DT_PLTRELSZ: i64 = 2;
 pub const DT_PLTGOT: i64 = 3;
+pub const DT_HASH: i64 = 4;
 pub const DT_STRTAB: i64 = 5;
 pub const DT_SYMTAB: i64 = 6;
 pub const DT_RELA: i64 = 7;
@@ -42,35 +46,77 @@ pub const DT_RELASZ: i64 = 8;
 pub const DT_RELAENT: i64 = 9;
 pub const DT_STRSZ: i64 = 10;
 pub const DT_SYMENT: i64 = 11;
+pub const DT_INIT: i64 = 12;
 pub const DT_FINI: i64 = 13;
 pub const DT_SONAME: i64 = 14;
+pub const DT_RPATH: i64 = 15;
+pub const DT_REL: i64 = 17;
+pub const DT_RELSZ: i64 = 18;
+pub const DT_RELENT: i64 = 19;
 pub const DT_PLTREL: i64 = 20;
 pub const DT_JMPREL: i64 = 23;
 pub const DT_INIT_ARRAY: i64 = 25;
 pub const DT_FINI_ARRAY: i64 = 26;
 pub const DT_INIT_ARRAYSZ: i64 = 27;
 pub const DT_FINI_ARRAYSZ: i64 = 28;
+pub const DT_RUNPATH: i64 = 29;
+pub const DT_PREINIT_ARRAY: i64 = 32;
+pub const DT_PREINIT_ARRAYSZ: i64 = 33;
 pub const DT_RELRSZ: i64 = 35;
 pub const DT_RELR: i64 = 36;
 pub const DT_RELRENT: i64 = 37;
 
 pub const DT_RELA_TAG: u64 = 7;
+pub const DT_REL_TAG: u64 = 17;
 pub const DT_RELACOUNT: i64 = 0x6fff_fff9;
+pub const DT_GNU_HASH: i64 = 0x6fff_fef5;
+pub const DT_VERSYM: i64 = 0x6fff_fff0;
+pub const DT_VERDEF: i64 = 0x6fff_fffc;
+pub const DT_VERDEFNUM: i64 = 0x6fff_fffd;
+pub const DT_VERNEED: i64 = 0x6fff_fffe;
+pub const DT_VERNEEDNUM: i64 = 0x6fff_ffff;
+pub const DT_FLAGS_1: i64 = 0x6fff_fffb;
+pub const DF_1_PIE: u64 = 0x0800_0000;
+
+pub const VER_FLG_WEAK: u16 = 0x2;
+pub const VERSYM_HIDDEN: u16 = 0x8000;
+pub const VERSYM_VERSION: u16 = 0x7fff;
 
 pub const R_X86_64_GLOB_DAT: u32 = 6;
 pub const R_X86_64_JUMP_SLOT: u32 = 7;
 pub const R_X86_64_RELATIVE: u32 = 8;
 pub const R_X86_64_COPY: u32 = 5;
 pub const R_X86_64_64: u32 = 1;
+pub const R_X86_64_DTPMOD64: u32 = 16;
+pub const R_X86_64_DTPOFF64: u32 = 17;
+pub const R_X86_64_TPOFF64: u32 = 18;
+pub const R_X86_64_IRELATIVE: u32 = 37;
+
+pub const STT_TLS: u8 = 6;
+pub const STT_GNU_IFUNC: u8 = 10;
+
+pub const SHN_UNDEF: u16 = 0;
+pub const SHN_LORESERVE: u16 = 0xff00;
+pub const SHN_ABS: u16 = 0xfff1;
+pub const SHN_COMMON: u16 = 0xfff2;
+
+pub const STB_LOCAL: u8 = 0;
+pub const STB_GLOBAL: u8 = 1;
+pub const STB_WEAK: u8 = 2;
+
+pub const STV_DEFAULT: u8 = 0;
+pub const STV_INTERNAL: u8 = 1;
+pub const STV_HIDDEN: u8 = 2;
+pub const STV_PROTECTED: u8 = 3;
 
 pub const ELF64_EHDR_SIZE: usize = 64;
 pub const ELF64_PHDR_SIZE: usize = 56;
 pub const ELF64_DYN_SIZE: usize = 16;
 pub const ELF64_SYM_SIZE: usize = 24;
 pub const ELF64_RELA_SIZE: usize = 24;
+pub const ELF64_REL_SIZE: usize = 16;
 
 pub const PAGE_SIZE: u64 = 4096;
 pub const DYN_BASE_START: u64 = 0x7000_0000_0000;
-pub const DYN_BASE_STRIDE: u64 = 0x20_0000;
 
 } // verus!
@@ -1,4 +1,4 @@
-use crate::types::LoaderOutput;
+use crate::types::{LoaderError, LoaderErrorReason, LoaderOutput, LoaderStage};
 
 fn render_name(bytes: &[u8]) -> String {
     String::from_utf8_lossy(bytes).into_owned()
@@ -20,10 +20,38 @@ pub fn print_loader_plan(plan: &LoaderOutput) {
             "  map {} start=0x{:016x} len={} prot={}",
             render_name(&p.object_name),
             p.start,
-            p.bytes.len(),
+            p.len,
             p.prot.render(),
         );
     }
+    println!(
+        "tls_modules={} static_size=0x{:x} align={}",
+        plan.tls.modules.len(),
+        plan.tls.static_size,
+        plan.tls.align,
+    );
+    for m in &plan.tls.modules {
+        println!(
+            "  tls {} module={} tp_offset=0x{:x} filesz={} memsz={} align={}",
+            render_name(&plan.parsed[m.object_index].input_name),
+            m.module_id,
+            m.tp_offset,
+            m.image.len(),
+            m.memsz,
+            m.align,
+        );
+    }
+    println!("resolver_writes={}", plan.resolver_writes.len());
+    for w in &plan.resolver_writes {
+        println!(
+            "  resolve {} addr=0x{:016x} resolver=0x{:016x} addend={} type={}",
+            render_name(&w.object_name),
+            w.write_addr,
+            w.resolver_pc,
+            w.addend,
+            w.reloc_type,
+        );
+    }
     println!("debug.reloc_writes={}", plan.reloc_writes.len());
     for w in &plan.reloc_writes {
         println!(
@@ -37,18 +65,20 @@ pub fn print_loader_plan(plan: &LoaderOutput) {
     println!("debug.parsed={}", plan.parsed.len());
     for (i, obj) in plan.parsed.iter().enumerate() {
         println!(
-            "  parsed[{}] name={} elf_type={} phdrs={} needed={} dynsyms={} relas={} jmprels={}",
+            "  parsed[{}] name={} elf_type={} phdrs={} needed={} dynsyms={} ({:?}) relas={} jmprels={}",
             i,
             render_name(&obj.input_name),
             obj.elf_type,
             obj.phdrs.len(),
             obj.needed_offsets.len(),
             obj.dynsyms.len(),
+            obj.dynsym_count_source,
             obj.relas.len(),
             obj.jmprels.len(),
         );
     }
     println!("debug.discovered.order={:?}", plan.discovered.order);
+    println!("debug.discovered.init_order={:?}", plan.discovered.init_order);
     println!("debug.resolved.planned={}", plan.resolved.planned.len());
     for (i, po) in plan.resolved.planned.iter().enumerate() {
         println!("  planned[{}] index={} base=0x{:016x}", i, po.index, po.base);
@@ -67,3 +97,99 @@ pub fn print_loader_plan(plan: &LoaderOutput) {
         );
     }
 }
+
+fn render_stage(stage: LoaderStage) -> &'static str {
+    match stage {
+        LoaderStage::Input => "input",
+        LoaderStage::Parse => "parse",
+        LoaderStage::Discover => "discover",
+        LoaderStage::Resolve => "resolve",
+        LoaderStage::MmapPlan => "mmap_plan",
+        LoaderStage::TlsLayout => "tls_layout",
+        LoaderStage::RelocatePlan => "relocate_plan",
+        LoaderStage::RelocateApply => "relocate_apply",
+        LoaderStage::Final => "final",
+        LoaderStage::Runtime => "runtime",
+    }
+}
+
+fn render_errno(errno: i32) -> String {
+    std::io::Error::from_raw_os_error(errno).to_string()
+}
+
+fn render_reason(reason: &LoaderErrorReason) -> String {
+    match reason {
+        LoaderErrorReason::Io { errno } => format!("cannot read file: {}", render_errno(*errno)),
+        LoaderErrorReason::Truncated => "truncated or out-of-bounds data".to_string(),
+        LoaderErrorReason::BadElfHeader => "unsupported or malformed ELF header".to_string(),
+        LoaderErrorReason::BadProgramHeaders => "malformed program headers".to_string(),
+        LoaderErrorReason::BadDynamicSection => "malformed dynamic section".to_string(),
+        LoaderErrorReason::BadSymbolTable => "malformed dynamic symbol table".to_string(),
+        LoaderErrorReason::BadRelocationTable => "malformed relocation table".to_string(),
+        LoaderErrorReason::UnmappedAddress { vaddr } => {
+            format!("address 0x{:x} is not covered by a PT_LOAD segment", vaddr)
+        }
+        LoaderErrorReason::UnsupportedRelocType { reloc_type, offset } => {
+            format!("unsupported relocation type {} at offset 0x{:x}", reloc_type, offset)
+        }
+        LoaderErrorReason::MissingNeeded { name } => {
+            format!("missing DT_NEEDED \"{}\"", render_name(name))
+        }
+        LoaderErrorReason::MissingVersion { version, file } => {
+            format!("version \"{}\" not found in \"{}\"", render_name(version), render_name(file))
+        }
+        LoaderErrorReason::BadSymbolIndex { sym_index } => {
+            format!("relocation refers to invalid symbol index {}", sym_index)
+        }
+        LoaderErrorReason::UndefinedSymbol { name } => {
+            format!("undefined symbol \"{}\"", render_name(name))
+        }
+        LoaderErrorReason::MissingCopySource { name } => {
+            format!("no provider for copy relocation of \"{}\"", render_name(name))
+        }
+        LoaderErrorReason::UnsupportedSymbol { name, shndx, sym_type } => format!(
+            "unsupported definition of \"{}\" (section index 0x{:x}, type {})",
+            render_name(name),
+            shndx,
+            sym_type
+        ),
+        LoaderErrorReason::InvalidObjectIndex { index } => format!("invalid object index {}", index),
+        LoaderErrorReason::NoInitOrder => "no dependency-respecting constructor order".to_string(),
+        LoaderErrorReason::BadLoadBase { base } => {
+            format!("load base 0x{:x} is misaligned or does not fit the address space", base)
+        }
+        LoaderErrorReason::BadTlsLayout => "PT_TLS segments do not fit a static TLS layout".to_string(),
+        LoaderErrorReason::SegmentOverlap { start, len } => {
+            format!("segment 0x{:x}+0x{:x} overlaps a previously planned segment", start, len)
+        }
+        LoaderErrorReason::WritableExecutable { start, len } => {
+            format!("0x{:x}+0x{:x} would be writable and executable", start, len)
+        }
+        LoaderErrorReason::AddressInUse { start, len } => {
+            format!("0x{:x}+0x{:x} is already mapped in the loader process", start, len)
+        }
+        LoaderErrorReason::Mmap { start, errno } => {
+            format!("mmap at 0x{:x} failed: {}", start, render_errno(*errno))
+        }
+        LoaderErrorReason::Mprotect { start, errno } => {
+            format!("mprotect at 0x{:x} failed: {}", start, render_errno(*errno))
+        }
+        LoaderErrorReason::StackSetup => "initial stack does not fit".to_string(),
+        LoaderErrorReason::SetThreadPointer { errno } => {
+            format!("cannot set the thread pointer: {}", render_errno(*errno))
+        }
+    }
+}
+
+pub fn render_loader_error(err: &LoaderError) -> String {
+    if err.object_name.is_empty() {
+        format!("{} failed: {}", render_stage(err.stage), render_reason(&err.reason))
+    } else {
+        format!(
+            "{} failed for {}: {}",
+            render_stage(err.stage),
+            render_name(&err.object_name),
+            render_reason(&err.reason),
+        )
+    }
+}
@@ -1,3 +1,4 @@
+use crate::consts::*;
 use crate::discover_spec::*;
 use crate::types::*;
 use vstd::prelude::*;
@@ -178,9 +179,26 @@ fn depends_on(parsed: &Vec<ParsedObject>, from: usize, to: usize) -> (r: bool)
     false
 }
 
-fn has_needed_match(parsed: &Vec<ParsedObject>, from: usize, need_off: u32) -> (r: bool) {
+proof fn lemma_first_needed_match(parsed: Seq<ParsedObject>, from: int, need_off: nat, to: nat)
+    ensures
+        match first_needed_match(parsed, from, need_off, to) {
+            Some(t) => to <= t < parsed.len() && dep_target_matches(parsed, from, t as int, need_off),
+            None => true,
+        },
+    decreases parsed.len() - to,
+{
+    if to < parsed.len() && !dep_target_matches(parsed, from, to as int, need_off) {
+        lemma_first_needed_match(parsed, from, need_off, (to + 1) as nat);
+    }
+}
+
+fn find_needed_object(parsed: &Vec<ParsedObject>, from: usize, need_off: u32) -> (r: Option<usize>)
+    ensures
+        from < parsed@.len() ==> r == first_needed_match(parsed@, from as int, need_off as nat, 0),
+        from >= parsed@.len() ==> r.is_none(),
+{
     if from >= parsed.len() {
-        return false;
+        return None;
     }
 
     let mut to: usize = 0;
@@ -188,6 +206,12 @@ fn has_needed_match(parsed: &Vec<ParsedObject>, from: usize, need_off: u32) -> (
         invariant
             to <= parsed.len(),
             from < parsed.len(),
+            first_needed_match(parsed@, from as int, need_off as nat, 0) == first_needed_match(
+                parsed@,
+                from as int,
+                need_off as nat,
+                to as nat,
+            ),
         decreases parsed.len() - to,
     {
         let soname_opt = parsed[to].soname_offset;
@@ -199,8 +223,11 @@ fn has_needed_match(parsed: &Vec<ParsedObject>, from: usize, need_off: u32) -> (
                 &parsed[to].dynstr,
                 soname_off as usize,
             );
+            proof {
+                assert(eq == dep_target_matches(parsed@, from as int, to as int, need_off as nat));
+            }
             if eq {
-                return true;
+                return Some(to);
             }
         } else {
             let mut input_name_cstr = clone_u8_vec(&parsed[to].input_name);
@@ -211,12 +238,52 @@ fn has_needed_match(parsed: &Vec<ParsedObject>, from: usize, need_off: u32) -> (
                 &input_name_cstr,
                 0,
             );
+            proof {
+                assert(input_name_cstr@ == parsed@[to as int].input_name@.push(0u8));
+                assert(eq == dep_target_matches(parsed@, from as int, to as int, need_off as nat));
+            }
             if eq {
-                return true;
+                return Some(to);
             }
         }
         to = to + 1;
     }
+    None
+}
+
+fn defines_version_exec(parsed: &Vec<ParsedObject>, from: usize, name_off: u32, to: usize) -> (r: bool)
+    requires
+        from < parsed@.len(),
+        to < parsed@.len(),
+    ensures
+        r == defines_version(parsed@, from as int, name_off as nat, to as int),
+{
+    let mut i: usize = 0;
+    while i < parsed[to].verdefs.len()
+        invariant
+            from < parsed.len(),
+            to < parsed.len(),
+            i <= parsed@[to as int].verdefs@.len(),
+            forall|j: int|
+                0 <= j < i ==> !cstr_eq_from(
+                    parsed@[from as int].dynstr@,
+                    name_off as nat,
+                    parsed@[to as int].dynstr@,
+                    parsed@[to as int].verdefs@[j].name_offset as nat,
+                ),
+        decreases parsed@[to as int].verdefs@.len() - i,
+    {
+        let eq = cstr_eq_from_exec(
+            &parsed[from].dynstr,
+            name_off as usize,
+            &parsed[to].dynstr,
+            parsed[to].verdefs[i].name_offset as usize,
+        );
+        if eq {
+            return true;
+        }
+        i = i + 1;
+    }
     false
 }
 
@@ -330,6 +397,372 @@ proof fn lemma_non_root_parent_push(
     };
 }
 
+proof fn lemma_dep_path_refl(parsed: Seq<ParsedObject>, order: Seq<usize>, a: int)
+    ensures
+        pos_reaches(parsed, order, a, a),
+{
+    assert(dep_path(parsed, order, a, a, 0));
+}
+
+proof fn lemma_dep_path_edge(parsed: Seq<ParsedObject>, order: Seq<usize>, a: int, b: int)
+    requires
+        pos_dep_edge(parsed, order, a, b),
+    ensures
+        pos_reaches(parsed, order, a, b),
+{
+    assert(dep_path(parsed, order, b, b, 0));
+    assert(dep_path(parsed, order, a, b, 1));
+}
+
+proof fn lemma_dep_path_concat(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    a: int,
+    m: int,
+    b: int,
+    s1: nat,
+    s2: nat,
+)
+    requires
+        dep_path(parsed, order, a, m, s1),
+        dep_path(parsed, order, m, b, s2),
+    ensures
+        dep_path(parsed, order, a, b, s1 + s2),
+    decreases s1,
+{
+    if s1 > 0 {
+        let mid = choose|mid: int|
+            pos_dep_edge(parsed, order, a, mid) && dep_path(parsed, order, mid, m, (s1 - 1) as nat);
+        lemma_dep_path_concat(parsed, order, mid, m, b, (s1 - 1) as nat, s2);
+        assert((s1 + s2 - 1) as nat == ((s1 - 1) as nat + s2) as nat);
+        assert(dep_path(parsed, order, a, b, s1 + s2));
+    }
+}
+
+proof fn lemma_reaches_trans(parsed: Seq<ParsedObject>, order: Seq<usize>, a: int, m: int, b: int)
+    requires
+        pos_reaches(parsed, order, a, m),
+        pos_reaches(parsed, order, m, b),
+    ensures
+        pos_reaches(parsed, order, a, b),
+{
+    let s1 = choose|s: nat| dep_path(parsed, order, a, m, s);
+    let s2 = choose|s: nat| dep_path(parsed, order, m, b, s);
+    lemma_dep_path_concat(parsed, order, a, m, b, s1, s2);
+}
+
+fn reach_row(parsed: &Vec<ParsedObject>, order: &Vec<usize>, a: usize) -> (row: Vec<bool>)
+    requires
+        a < order@.len(),
+        valid_object_indices(order@, parsed@.len()),
+    ensures
+        row@.len() == order@.len(),
+        forall|b: int| 0 <= b < order@.len() && row@[b] ==> pos_reaches(parsed@, order@, a as int, b),
+{
+    let mut row: Vec<bool> = Vec::new();
+    let mut b: usize = 0;
+    while b < order.len()
+        invariant
+            b <= order@.len(),
+            a < order@.len(),
+            valid_object_indices(order@, parsed@.len()),
+            row@.len() == b,
+            forall|k: int| 0 <= k < b && row@[k] ==> pos_reaches(parsed@, order@, a as int, k),
+        decreases order.len() - b,
+    {
+        let v = a == b || depends_on(parsed, order[a], order[b]);
+        proof {
+            if a == b {
+                lemma_dep_path_refl(parsed@, order@, a as int);
+            } else if v {
+                assert(pos_dep_edge(parsed@, order@, a as int, b as int));
+                lemma_dep_path_edge(parsed@, order@, a as int, b as int);
+            }
+        }
+        row.push(v);
+        b = b + 1;
+    }
+    row
+}
+
+// Transitive closure of the dependency edges between load positions
+// (Floyd-Warshall, one intermediate position per round).
+fn reach_table(parsed: &Vec<ParsedObject>, order: &Vec<usize>) -> (reach: Vec<Vec<bool>>)
+    requires
+        valid_object_indices(order@, parsed@.len()),
+    ensures
+        reach_table_sound(parsed@, order@, reach@),
+{
+    let n = order.len();
+    let mut reach: Vec<Vec<bool>> = Vec::new();
+    let mut a: usize = 0;
+    while a < n
+        invariant
+            n == order@.len(),
+            a <= n,
+            valid_object_indices(order@, parsed@.len()),
+            reach@.len() == a,
+            forall|i: int| 0 <= i < a ==> reach@[i]@.len() == n,
+            forall|i: int, j: int|
+                0 <= i < a && 0 <= j < n && reach@[i]@[j] ==> pos_reaches(parsed@, order@, i, j),
+        decreases n - a,
+    {
+        reach.push(reach_row(parsed, order, a));
+        a = a + 1;
+    }
+
+    let mut k: usize = 0;
+    while k < n
+        invariant
+            n == order@.len(),
+            k <= n,
+            reach_table_sound(parsed@, order@, reach@),
+        decreases n - k,
+    {
+        let mut next: Vec<Vec<bool>> = Vec::new();
+        let mut i: usize = 0;
+        while i < n
+            invariant
+                n == order@.len(),
+                k < n,
+                i <= n,
+                reach_table_sound(parsed@, order@, reach@),
+                next@.len() == i,
+                forall|x: int| 0 <= x < i ==> next@[x]@.len() == n,
+                forall|x: int, y: int|
+                    0 <= x < i && 0 <= y < n && next@[x]@[y] ==> pos_reaches(parsed@, order@, x, y),
+            decreases n - i,
+        {
+            let mut row: Vec<bool> = Vec::new();
+            let mut j: usize = 0;
+            while j < n
+                invariant
+                    n == order@.len(),
+                    k < n,
+                    i < n,
+                    j <= n,
+                    reach_table_sound(parsed@, order@, reach@),
+                    row@.len() == j,
+                    forall|y: int| 0 <= y < j && row@[y] ==> pos_reaches(parsed@, order@, i as int, y),
+                decreases n - j,
+            {
+                let v = reach[i][j] || (reach[i][k] && reach[k][j]);
+                proof {
+                    if !reach@[i as int]@[j as int] && v {
+                        lemma_reaches_trans(parsed@, order@, i as int, k as int, j as int);
+                    }
+                }
+                row.push(v);
+                j = j + 1;
+            }
+            next.push(row);
+            i = i + 1;
+        }
+        reach = next;
+        k = k + 1;
+    }
+    reach
+}
+
+fn init_ready(
+    parsed: &Vec<ParsedObject>,
+    order: &Vec<usize>,
+    reach: &Vec<Vec<bool>>,
+    placed: &Vec<bool>,
+    p: usize,
+) -> (r: bool)
+    requires
+        p < order@.len(),
+        valid_object_indices(order@, parsed@.len()),
+        reach_table_sound(parsed@, order@, reach@),
+        placed@.len() == order@.len(),
+    ensures
+        r ==> forall|q: int|
+            0 <= q < order@.len() && pos_dep_edge(parsed@, order@, p as int, q) && !pos_reaches(
+                parsed@,
+                order@,
+                q,
+                p as int,
+            ) ==> placed@[q],
+{
+    let mut q: usize = 0;
+    while q < order.len()
+        invariant
+            q <= order@.len(),
+            p < order@.len(),
+            valid_object_indices(order@, parsed@.len()),
+            reach_table_sound(parsed@, order@, reach@),
+            placed@.len() == order@.len(),
+            forall|x: int|
+                0 <= x < q && pos_dep_edge(parsed@, order@, p as int, x) && !pos_reaches(
+                    parsed@,
+                    order@,
+                    x,
+                    p as int,
+                ) ==> placed@[x],
+        decreases order.len() - q,
+    {
+        if !placed[q] && !reach[q][p] && depends_on(parsed, order[p], order[q]) {
+            return false;
+        }
+        q = q + 1;
+    }
+    true
+}
+
+// Repeatedly places the last-discovered object whose dependencies are all
+// placed, ignoring dependencies that reach back to it (cycles). Without
+// cycles this is reverse discovery order whenever that is already valid.
+fn init_order_exec(parsed: &Vec<ParsedObject>, order: &Vec<usize>) -> (out: Option<Vec<usize>>)
+    requires
+        valid_object_indices(order@, parsed@.len()),
+    ensures
+        match out {
+            Some(init_order) => init_order_spec(parsed@, order@, init_order@),
+            None => true,
+        },
+{
+    let n = order.len();
+    let reach = reach_table(parsed, order);
+    let mut placed: Vec<bool> = Vec::new();
+    while placed.len() < n
+        invariant
+            placed@.len() <= n,
+            forall|x: int| 0 <= x < placed@.len() ==> !placed@[x],
+        decreases n - placed.len(),
+    {
+        placed.push(false);
+    }
+
+    let mut init_order: Vec<usize> = Vec::new();
+    while init_order.len() < n
+        invariant
+            n == order@.len(),
+            valid_object_indices(order@, parsed@.len()),
+            reach_table_sound(parsed@, order@, reach@),
+            placed@.len() == n,
+            init_order@.len() <= n,
+            valid_object_indices(init_order@, n as nat),
+            unique_indices(init_order@),
+            forall|r: int| 0 <= r < init_order@.len() ==> placed@[init_order@[r] as int],
+            forall|x: int| 0 <= x < n && placed@[x] ==> in_order_int(init_order@, x),
+            forall|r: int, q: int|
+                0 <= r < init_order@.len() && 0 <= q < n && pos_dep_edge(
+                    parsed@,
+                    order@,
+                    init_order@[r] as int,
+                    q,
+                ) && !pos_reaches(parsed@, order@, q, init_order@[r] as int) ==> placed@[q],
+            forall|ra: int, rb: int|
+                0 <= ra < init_order@.len() && 0 <= rb < init_order@.len() && pos_dep_edge(
+                    parsed@,
+                    order@,
+                    init_order@[ra] as int,
+                    init_order@[rb] as int,
+                ) && !pos_reaches(parsed@, order@, init_order@[rb] as int, init_order@[ra] as int)
+                    ==> rb < ra,
+        decreases n - init_order.len(),
+    {
+        let mut pick: Option<usize> = None;
+        let mut c: usize = n;
+        while c > 0 && pick.is_none()
+            invariant
+                c <= n,
+                n == order@.len(),
+                valid_object_indices(order@, parsed@.len()),
+                reach_table_sound(parsed@, order@, reach@),
+                placed@.len() == n,
+                match pick {
+                    Some(p) => p < n && !placed@[p as int] && forall|q: int|
+                        0 <= q < n && pos_dep_edge(parsed@, order@, p as int, q) && !pos_reaches(
+                            parsed@,
+                            order@,
+                            q,
+                            p as int,
+                        ) ==> placed@[q],
+                    None => true,
+                },
+            decreases c,
+        {
+            let cand = c - 1;
+            if !placed[cand] && init_ready(parsed, order, &reach, &placed, cand) {
+                pick = Some(cand);
+            }
+            c = cand;
+        }
+        let p = match pick {
+            Some(v) => v,
+            None => {
+                return None;
+            },
+        };
+
+        let ghost old_init = init_order@;
+        let ghost old_placed = placed@;
+        proof {
+            assert(!in_order_int(old_init, p as int)) by {
+                if in_order_int(old_init, p as int) {
+                    let w = choose|w: int| 0 <= w < old_init.len() && old_init[w] as int == p as int;
+                    assert(old_placed[old_init[w] as int]);
+                }
+            };
+            lemma_unique_indices_push(old_init, p);
+            lemma_valid_indices_push(old_init, n as nat, p);
+            lemma_in_order_push_new(old_init, p);
+            lemma_dep_path_refl(parsed@, order@, p as int);
+        }
+        init_order.push(p);
+        placed.set(p, true);
+        proof {
+            let len = old_init.len() as int;
+            assert(init_order@ == old_init.push(p));
+            assert forall|r: int| 0 <= r < init_order@.len() implies placed@[init_order@[r] as int] by {
+                if r < len {
+                    assert(old_placed[old_init[r] as int]);
+                }
+            };
+            assert forall|x: int| 0 <= x < n && placed@[x] implies in_order_int(init_order@, x) by {
+                if x != p as int {
+                    assert(old_placed[x]);
+                    lemma_in_order_push_preserve(old_init, x, p);
+                }
+            };
+            assert forall|r: int, q: int|
+                0 <= r < init_order@.len() && 0 <= q < n && pos_dep_edge(
+                    parsed@,
+                    order@,
+                    init_order@[r] as int,
+                    q,
+                ) && !pos_reaches(parsed@, order@, q, init_order@[r] as int) implies placed@[q] by {
+                if r < len {
+                    assert(old_placed[q]);
+                } else {
+                    assert(init_order@[r] == p);
+                    assert(old_placed[q]);
+                }
+            };
+            assert forall|ra: int, rb: int|
+                0 <= ra < init_order@.len() && 0 <= rb < init_order@.len() && pos_dep_edge(
+                    parsed@,
+                    order@,
+                    init_order@[ra] as int,
+                    init_order@[rb] as int,
+                ) && !pos_reaches(parsed@, order@, init_order@[rb] as int, init_order@[ra] as int)
+                    implies rb < ra by {
+                if ra < len && rb == len {
+                    assert(init_order@[rb] == p);
+                    assert(old_placed[p as int]);
+                    assert(false);
+                } else if ra == len && rb == len {
+                    assert(pos_reaches(parsed@, order@, p as int, p as int));
+                    assert(false);
+                }
+            };
+        }
+    }
+    Some(init_order)
+}
+
 pub fn discover_stage(parsed: &Vec<ParsedObject>) -> (out: Result<DiscoveryResult, LoaderError>)
     ensures
         out.is_ok() ==> discover_stage_spec(parsed@, out.unwrap()),
@@ -337,10 +770,14 @@ pub fn discover_stage(parsed: &Vec<ParsedObject>) -> (out: Result<DiscoveryResul
     let mut order: Vec<usize> = Vec::new();
 
     if parsed.len() == 0 {
-        return Ok(DiscoveryResult { order: order });
+        return Ok(DiscoveryResult { order: order, init_order: Vec::new() });
     }
 
     order.push(0);
+    proof {
+        assert(order@ =~= seq![0usize]);
+        assert(non_root_has_parent_edge(parsed@, order@));
+    }
 
     let mut q: usize = 0;
     while q < parsed.len()
@@ -351,179 +788,315 @@ pub fn discover_stage(parsed: &Vec<ParsedObject>) -> (out: Result<DiscoveryResul
             valid_object_indices(order@, parsed@.len()),
             unique_indices(order@),
             non_root_has_parent_edge(parsed@, order@),
-            forall|p: int, v: int|
-                0 <= p < q && p < order@.len() && 0 <= v < parsed.len() && dep_edge(
+            bfs_from(parsed@, order@, q as nat) == bfs_order(parsed@),
+            forall|p: int, k: int|
+                0 <= p < q && p < order@.len() && (order@[p] as int) < parsed.len() && 0 <= k
+                    < parsed@[order@[p] as int].needed_offsets@.len() ==> match needed_target(
                     parsed@,
                     order@[p] as int,
-                    v,
-                ) ==> in_order_int(order@, v),
+                    k,
+                ) {
+                    Some(t) => in_order_int(order@, t as int),
+                    None => true,
+                },
         decreases parsed.len() - q,
     {
+        let ghost order_q = order@;
         if q < order.len() {
             let cur = order[q];
-            let mut cand: usize = 0;
-            while cand < parsed.len()
+            let mut k: usize = 0;
+            while k < parsed[cur].needed_offsets.len()
                 invariant
-                    cand <= parsed.len(),
                     q < order@.len(),
                     cur == order@[q as int],
+                    (cur as int) < parsed@.len(),
+                    k <= parsed@[cur as int].needed_offsets@.len(),
                     order@.len() > 0,
                     order@[0] == 0,
+                    order_q.len() <= order@.len(),
+                    forall|i: int| 0 <= i < order_q.len() ==> order@[i] == order_q[i],
                     valid_object_indices(order@, parsed@.len()),
                     unique_indices(order@),
                     non_root_has_parent_edge(parsed@, order@),
-                    forall|p: int, v: int|
-                        0 <= p < q && p < order@.len() && 0 <= v < parsed.len() && dep_edge(
+                    bfs_visit_needed(parsed@, order@, cur as int, k as nat) == bfs_visit_needed(
+                        parsed@,
+                        order_q,
+                        cur as int,
+                        0,
+                    ),
+                    forall|p: int, j: int|
+                        0 <= p < q && p < order@.len() && (order@[p] as int) < parsed.len() && 0 <= j
+                            < parsed@[order@[p] as int].needed_offsets@.len() ==> match needed_target(
                             parsed@,
                             order@[p] as int,
-                            v,
-                        ) ==> in_order_int(order@, v),
-                    forall|v: int|
-                        0 <= v < cand as int && dep_edge(parsed@, cur as int, v) ==> in_order_int(
-                            order@,
-                            v,
-                        ),
-                decreases parsed.len() - cand,
+                            j,
+                        ) {
+                            Some(t) => in_order_int(order@, t as int),
+                            None => true,
+                        },
+                    forall|j: int|
+                        0 <= j < k ==> match needed_target(parsed@, cur as int, j) {
+                            Some(t) => in_order_int(order@, t as int),
+                            None => true,
+                        },
+                decreases parsed@[cur as int].needed_offsets@.len() - k,
             {
                 let ghost old_order = order@;
-                let dep = depends_on(&parsed, cur, cand);
-                let seen = contains_index(&order, cand);
+                let need_off = parsed[cur].needed_offsets[k];
+                let target = find_needed_object(parsed, cur, need_off);
                 proof {
-                    assert(dep == dep_edge(parsed@, cur as int, cand as int));
-                    assert(seen == in_order_int(old_order, cand as int));
+                    assert(target == needed_target(parsed@, cur as int, k as int));
+                    lemma_first_needed_match(parsed@, cur as int, need_off as nat, 0);
                 }
-                if dep && !seen {
-                    order.push(cand);
-                    proof {
-                        assert(dep_edge(parsed@, cur as int, cand as int));
-                        assert(order@ == old_order.push(cand));
-                        assert((cand as nat) < parsed@.len());
-                        lemma_valid_indices_push(old_order, parsed@.len(), cand);
-                        assert(valid_object_indices(order@, parsed@.len()));
-                        assert(!in_order_int(old_order, cand as int));
-                        lemma_unique_indices_push(old_order, cand);
-                        assert(unique_indices(order@));
-                        assert(cur == old_order[q as int]);
-                        assert(dep_edge(parsed@, old_order[q as int] as int, cand as int));
-                        lemma_non_root_parent_push(parsed@, old_order, cand, q as int);
-                        assert(non_root_has_parent_edge(parsed@, order@));
-                        assert(cur == order@[q as int]);
-                        assert forall|p: int, v: int|
-                            0 <= p < q && p < order@.len() && 0 <= v < parsed.len()
-                                && dep_edge(parsed@, order@[p] as int, v) implies in_order_int(
-                                order@,
-                                v,
-                            ) by {
-                            assert(p < old_order.len());
-                            assert(in_order_int(old_order, v));
-                            lemma_in_order_push_preserve(old_order, v, cand);
-                        };
-                    }
-                } else {
-                    proof {
-                        assert(order@ == old_order);
-                        assert(non_root_has_parent_edge(parsed@, order@));
-                    }
-                }
-                proof {
-                    assert forall|v: int|
-                        0 <= v < cand as int + 1 && dep_edge(parsed@, cur as int, v) implies in_order_int(
-                            order@,
-                            v,
-                        ) by {
-                        if v < cand as int {
-                            assert(in_order_int(old_order, v));
-                            if dep && !seen {
-                                lemma_in_order_push_preserve(old_order, v, cand);
+                match target {
+                    Some(t) => {
+                        let seen = contains_index(&order, t);
+                        proof {
+                            assert(seen == old_order.contains(t)) by {
+                                if old_order.contains(t) {
+                                    let w = choose|w: int| 0 <= w < old_order.len() && old_order[w] == t;
+                                    assert(old_order[w] as int == t as int);
+                                }
+                            };
+                        }
+                        if !seen {
+                            order.push(t);
+                            proof {
+                                assert(order@ == old_order.push(t));
+                                assert(dep_target_matches(parsed@, cur as int, t as int, need_off as nat));
+                                assert(dep_edge(parsed@, cur as int, t as int));
+                                lemma_valid_indices_push(old_order, parsed@.len(), t);
+                                lemma_unique_indices_push(old_order, t);
+                                lemma_non_root_parent_push(parsed@, old_order, t, q as int);
+                                lemma_in_order_push_new(old_order, t);
+                                assert forall|p: int, j: int|
+                                    0 <= p < q && p < order@.len() && (order@[p] as int) < parsed.len() && 0 <= j
+                                        < parsed@[order@[p] as int].needed_offsets@.len() implies match needed_target(
+                                        parsed@,
+                                        order@[p] as int,
+                                        j,
+                                    ) {
+                                        Some(u) => in_order_int(order@, u as int),
+                                        None => true,
+                                    } by {
+                                    assert(order@[p] == old_order[p]);
+                                    match needed_target(parsed@, old_order[p] as int, j) {
+                                        Some(u) => lemma_in_order_push_preserve(old_order, u as int, t),
+                                        None => {},
+                                    }
+                                };
+                                assert forall|j: int| 0 <= j < k implies match needed_target(parsed@, cur as int, j) {
+                                    Some(u) => in_order_int(order@, u as int),
+                                    None => true,
+                                } by {
+                                    match needed_target(parsed@, cur as int, j) {
+                                        Some(u) => lemma_in_order_push_preserve(old_order, u as int, t),
+                                        None => {},
+                                    }
+                                };
                             }
                         } else {
-                            assert(v == cand as int);
-                            if dep {
-                                if seen {
-                                    assert(in_order_int(old_order, v));
-                                    assert(in_order_int(order@, v));
-                                } else {
-                                    lemma_in_order_push_new(old_order, cand);
-                                }
+                            proof {
+                                let w = choose|w: int| 0 <= w < old_order.len() && old_order[w] == t;
+                                assert(old_order[w] as int == t as int);
+                                assert(in_order_int(order@, t as int));
                             }
                         }
-                    };
+                    },
+                    None => {},
+                }
+                proof {
+                    assert(bfs_visit_needed(parsed@, old_order, cur as int, k as nat) == bfs_visit_needed(
+                        parsed@,
+                        order@,
+                        cur as int,
+                        (k + 1) as nat,
+                    ));
                 }
-                cand = cand + 1;
+                k = k + 1;
             }
             proof {
-                assert(cand == parsed.len());
-                assert(non_root_has_parent_edge(parsed@, order@));
-                assert forall|v: int|
-                    0 <= v < parsed.len() && dep_edge(parsed@, cur as int, v) implies in_order_int(
-                        order@,
-                        v,
-                    ) by {
-                    assert(v < cand as int);
-                };
-                assert forall|p: int, v: int|
-                    0 <= p < q + 1 && p < order@.len() && 0 <= v < parsed.len() && dep_edge(
+                assert(bfs_visit_needed(parsed@, order@, cur as int, k as nat) == order@);
+                assert(bfs_from(parsed@, order_q, q as nat) == bfs_from(parsed@, order@, (q + 1) as nat));
+                assert forall|p: int, j: int|
+                    0 <= p < q + 1 && p < order@.len() && (order@[p] as int) < parsed.len() && 0 <= j
+                        < parsed@[order@[p] as int].needed_offsets@.len() implies match needed_target(
                         parsed@,
                         order@[p] as int,
-                        v,
-                    ) implies in_order_int(order@, v) by {
-                    if p < q {
-                    } else {
-                        assert(p == q);
-                        assert(order@[p] as int == cur as int);
+                        j,
+                    ) {
+                        Some(t) => in_order_int(order@, t as int),
+                        None => true,
+                    } by {
+                    if p == q {
+                        assert(order@[p] == cur);
                     }
                 };
             }
+        } else {
+            proof {
+                assert(bfs_from(parsed@, order@, q as nat) == bfs_from(parsed@, order@, (q + 1) as nat));
+            }
         }
         q = q + 1;
     }
+    proof {
+        assert(bfs_from(parsed@, order@, q as nat) == order@);
+        assert(order@ == bfs_order(parsed@));
+    }
 
     let mut oi: usize = 0;
     while oi < order.len()
         invariant
             oi <= order.len(),
+            forall|p: int, k: int|
+                0 <= p < oi && (order@[p] as int) < parsed@.len() && 0 <= k
+                    < parsed@[order@[p] as int].needed_offsets@.len() ==> needed_target(
+                    parsed@,
+                    order@[p] as int,
+                    k,
+                ).is_some(),
+            forall|p: int, k: int|
+                0 <= p < oi && (order@[p] as int) < parsed@.len() && 0 <= k
+                    < parsed@[order@[p] as int].verneeds@.len() ==> version_need_met(
+                    parsed@,
+                    order@[p] as int,
+                    parsed@[order@[p] as int].verneeds@[k],
+                ),
         decreases order.len() - oi,
     {
         let obj_idx = order[oi];
         if obj_idx >= parsed.len() {
-            return Err(LoaderError {});
+            return Err(loader_error(
+                LoaderStage::Discover,
+                &Vec::new(),
+                LoaderErrorReason::InvalidObjectIndex { index: obj_idx },
+            ));
         }
-        let needed_offsets = parsed[obj_idx].needed_offsets.clone();
-        let need_len = needed_offsets.len();
+        let need_len = parsed[obj_idx].needed_offsets.len();
         let mut ni: usize = 0;
         while ni < need_len
             invariant
                 ni <= need_len,
-                need_len == needed_offsets.len(),
+                oi < order@.len(),
+                obj_idx == order@[oi as int],
+                obj_idx < parsed@.len(),
+                need_len == parsed@[obj_idx as int].needed_offsets@.len(),
+                forall|p: int, k: int|
+                    0 <= p < oi && (order@[p] as int) < parsed@.len() && 0 <= k
+                        < parsed@[order@[p] as int].needed_offsets@.len() ==> needed_target(
+                        parsed@,
+                        order@[p] as int,
+                        k,
+                    ).is_some(),
+                forall|k: int| 0 <= k < ni ==> needed_target(parsed@, obj_idx as int, k).is_some(),
             decreases need_len - ni,
         {
-            assert(ni < needed_offsets.len());
-            let need_off = needed_offsets[ni];
-            if !has_needed_match(parsed, obj_idx, need_off) {
-                return Err(LoaderError {});
+            let need_off = parsed[obj_idx].needed_offsets[ni];
+            if find_needed_object(parsed, obj_idx, need_off).is_none() {
+                return Err(loader_error(
+                    LoaderStage::Discover,
+                    &parsed[obj_idx].input_name,
+                    LoaderErrorReason::MissingNeeded { name: cstr_at(parsed[obj_idx].dynstr.as_slice(), need_off) },
+                ));
             }
+            assert(needed_target(parsed@, obj_idx as int, ni as int).is_some());
             ni = ni + 1;
         }
+
+        let mut vi: usize = 0;
+        while vi < parsed[obj_idx].verneeds.len()
+            invariant
+                obj_idx < parsed.len(),
+                vi <= parsed@[obj_idx as int].verneeds@.len(),
+                oi < order@.len(),
+                obj_idx == order@[oi as int],
+                forall|p: int, k: int|
+                    0 <= p < oi && (order@[p] as int) < parsed@.len() && 0 <= k
+                        < parsed@[order@[p] as int].needed_offsets@.len() ==> needed_target(
+                        parsed@,
+                        order@[p] as int,
+                        k,
+                    ).is_some(),
+                forall|k: int|
+                    0 <= k < parsed@[obj_idx as int].needed_offsets@.len() ==> needed_target(
+                        parsed@,
+                        obj_idx as int,
+                        k,
+                    ).is_some(),
+                forall|p: int, k: int|
+                    0 <= p < oi && (order@[p] as int) < parsed@.len() && 0 <= k
+                        < parsed@[order@[p] as int].verneeds@.len() ==> version_need_met(
+                        parsed@,
+                        order@[p] as int,
+                        parsed@[order@[p] as int].verneeds@[k],
+                    ),
+                forall|k: int|
+                    0 <= k < vi ==> version_need_met(
+                        parsed@,
+                        obj_idx as int,
+                        parsed@[obj_idx as int].verneeds@[k],
+                    ),
+            decreases parsed@[obj_idx as int].verneeds@.len() - vi,
+        {
+            let need = parsed[obj_idx].verneeds[vi];
+            if need.flags & VER_FLG_WEAK == 0 {
+                match find_needed_object(parsed, obj_idx, need.file_offset) {
+                    Some(to) => {
+                        proof {
+                            lemma_first_needed_match(parsed@, obj_idx as int, need.file_offset as nat, 0);
+                        }
+                        if !defines_version_exec(parsed, obj_idx, need.name_offset, to) {
+                            return Err(loader_error(
+                                LoaderStage::Discover,
+                                &parsed[obj_idx].input_name,
+                                LoaderErrorReason::MissingVersion {
+                                    version: cstr_at(parsed[obj_idx].dynstr.as_slice(), need.name_offset),
+                                    file: cstr_at(parsed[obj_idx].dynstr.as_slice(), need.file_offset),
+                                },
+                            ));
+                        }
+                    },
+                    None => {},
+                }
+            }
+            assert(version_need_met(parsed@, obj_idx as int, parsed@[obj_idx as int].verneeds@[vi as int]));
+            vi = vi + 1;
+        }
         oi = oi + 1;
     }
 
+    let init_order = match init_order_exec(parsed, &order) {
+        Some(v) => v,
+        None => {
+            return Err(loader_error(LoaderStage::Discover, &Vec::new(), LoaderErrorReason::NoInitOrder));
+        },
+    };
+
     proof {
         assert(valid_object_indices(order@, parsed@.len()));
         assert(unique_indices(order@));
         assert(cycle_handling_policy(order@));
         assert(parsed@.len() == 0 ==> order@.len() == 0);
         assert(parsed@.len() > 0 ==> order@.len() > 0 && order@[0] == 0);
-        assert(forall|p: int, v: int|
-            0 <= p < parsed.len() && p < order@.len() && 0 <= v < parsed.len() && dep_edge(
-                parsed@,
-                order@[p] as int,
-                v,
-            ) ==> in_order_int(order@, v));
+        assert forall|p: int, k: int|
+            0 <= p < parsed@.len() && p < order@.len() && (order@[p] as int) < parsed@.len() && 0 <= k
+                < parsed@[order@[p] as int].needed_offsets@.len() implies match needed_target(
+            parsed@,
+            order@[p] as int,
+            k,
+        ) {
+            Some(t) => in_order_int(order@, t as int),
+            None => false,
+        } by {
+            assert(needed_target(parsed@, order@[p] as int, k).is_some());
+        };
         assert(direct_dep_closure(parsed@, order@));
+        assert(version_needs_met(parsed@, order@));
         assert(non_root_has_parent_edge(parsed@, order@));
     }
 
-    Ok(DiscoveryResult { order: order })
+    Ok(DiscoveryResult { order: order, init_order: init_order })
 }
 
 } // verus!
@@ -1,3 +1,4 @@
+use crate::consts::*;
 use crate::types::*;
 use vstd::prelude::*;
 
@@ -56,12 +57,79 @@ pub open spec fn unique_indices(order: Seq<usize>) -> bool {
         != order[j]
 }
 
+// The object a `DT_NEEDED` entry loads: the first one, in input order, whose
+// SONAME (or input name) matches.
+pub open spec fn first_needed_match(parsed: Seq<ParsedObject>, from: int, need_off: nat, to: nat) -> Option<usize>
+    decreases parsed.len() - to,
+{
+    if to >= parsed.len() {
+        None
+    } else if dep_target_matches(parsed, from, to as int, need_off) {
+        Some(to as usize)
+    } else {
+        first_needed_match(parsed, from, need_off, (to + 1) as nat)
+    }
+}
+
+pub open spec fn needed_target(parsed: Seq<ParsedObject>, from: int, k: int) -> Option<usize> {
+    first_needed_match(parsed, from, parsed[from].needed_offsets@[k] as nat, 0)
+}
+
+// Every `DT_NEEDED` entry of a loaded object names an input that is loaded too.
 pub open spec fn direct_dep_closure(parsed: Seq<ParsedObject>, order: Seq<usize>) -> bool {
-    forall|p: int, v: int|
-        0 <= p < parsed.len() && p < order.len() && 0 <= v < parsed.len() && dep_edge(parsed, order[p] as int, v) ==> in_order_int(
-            order,
-            v,
-        )
+    forall|p: int, k: int|
+        0 <= p < parsed.len() && p < order.len() && (order[p] as int) < parsed.len() && 0 <= k
+            < parsed[order[p] as int].needed_offsets@.len() ==> match needed_target(parsed, order[p] as int, k) {
+            Some(t) => in_order_int(order, t as int),
+            None => false,
+        }
+}
+
+// Appends the targets of `DT_NEEDED` entries `k..` of `from` that are not
+// loaded yet, in entry order.
+pub open spec fn bfs_visit_needed(parsed: Seq<ParsedObject>, order: Seq<usize>, from: int, k: nat) -> Seq<usize>
+    decreases parsed[from].needed_offsets@.len() - k,
+{
+    if !(0 <= from < parsed.len()) || k >= parsed[from].needed_offsets@.len() {
+        order
+    } else {
+        let next = match needed_target(parsed, from, k as int) {
+            Some(t) => if order.contains(t) {
+                order
+            } else {
+                order.push(t)
+            },
+            None => order,
+        };
+        bfs_visit_needed(parsed, next, from, (k + 1) as nat)
+    }
+}
+
+// Breadth-first traversal: the objects at positions `q..` are visited in
+// turn, each appending its unseen dependencies.
+pub open spec fn bfs_from(parsed: Seq<ParsedObject>, order: Seq<usize>, q: nat) -> Seq<usize>
+    decreases parsed.len() - q,
+{
+    if q >= parsed.len() {
+        order
+    } else {
+        let next = if q < order.len() {
+            bfs_visit_needed(parsed, order, order[q as int] as int, 0)
+        } else {
+            order
+        };
+        bfs_from(parsed, next, (q + 1) as nat)
+    }
+}
+
+// The `ld.so` global scope: the main program, then its dependencies
+// breadth-first in `DT_NEEDED` order.
+pub open spec fn bfs_order(parsed: Seq<ParsedObject>) -> Seq<usize> {
+    if parsed.len() == 0 {
+        Seq::empty()
+    } else {
+        bfs_from(parsed, seq![0usize], 0)
+    }
 }
 
 pub open spec fn has_parent_edge(parsed: Seq<ParsedObject>, order: Seq<usize>, p: int) -> bool {
@@ -81,13 +149,88 @@ pub open spec fn reverse_of(a: Seq<usize>, b: Seq<usize>) -> bool {
     &&& forall|i: int| 0 <= i < a.len() ==> b[i] == a[a.len() - 1 - i]
 }
 
+pub open spec fn pos_dep_edge(parsed: Seq<ParsedObject>, order: Seq<usize>, a: int, b: int) -> bool {
+    &&& 0 <= a < order.len()
+    &&& 0 <= b < order.len()
+    &&& dep_edge(parsed, order[a] as int, order[b] as int)
+}
+
+pub open spec fn dep_path(parsed: Seq<ParsedObject>, order: Seq<usize>, a: int, b: int, steps: nat) -> bool
+    decreases steps,
+{
+    if steps == 0 {
+        a == b
+    } else {
+        exists|m: int| pos_dep_edge(parsed, order, a, m) && dep_path(parsed, order, m, b, (steps - 1) as nat)
+    }
+}
+
+pub open spec fn pos_reaches(parsed: Seq<ParsedObject>, order: Seq<usize>, a: int, b: int) -> bool {
+    exists|steps: nat| dep_path(parsed, order, a, b, steps)
+}
+
+// `reach[a][b]` may only be set when position `a` reaches position `b`.
+pub open spec fn reach_table_sound(parsed: Seq<ParsedObject>, order: Seq<usize>, reach: Seq<Vec<bool>>) -> bool {
+    &&& reach.len() == order.len()
+    &&& forall|a: int| 0 <= a < reach.len() ==> reach[a]@.len() == order.len()
+    &&& forall|a: int, b: int|
+        0 <= a < reach.len() && 0 <= b < order.len() && reach[a]@[b] ==> pos_reaches(parsed, order, a, b)
+}
+
+// `init_order` is a permutation of load positions in which every dependency
+// that does not lie on a cycle with its dependent comes first.
+pub open spec fn init_order_spec(parsed: Seq<ParsedObject>, order: Seq<usize>, init_order: Seq<usize>) -> bool {
+    &&& init_order.len() == order.len()
+    &&& valid_object_indices(init_order, order.len())
+    &&& unique_indices(init_order)
+    &&& forall|ra: int, rb: int|
+        0 <= ra < init_order.len() && 0 <= rb < init_order.len() && pos_dep_edge(
+            parsed,
+            order,
+            init_order[ra] as int,
+            init_order[rb] as int,
+        ) && !pos_reaches(parsed, order, init_order[rb] as int, init_order[ra] as int) ==> rb < ra
+}
+
+pub open spec fn defines_version(parsed: Seq<ParsedObject>, from: int, name_off: nat, to: int) -> bool {
+    exists|i: int|
+        0 <= i < parsed[to].verdefs@.len() && cstr_eq_from(
+            parsed[from].dynstr@,
+            name_off,
+            parsed[to].dynstr@,
+            parsed[to].verdefs@[i].name_offset as nat,
+        )
+}
+
+// A non-weak `.gnu.version_r` entry names a version its file defines; a file
+// without version definitions satisfies none.
+pub open spec fn version_need_met(parsed: Seq<ParsedObject>, from: int, need: VersionNeed) -> bool {
+    need.flags & VER_FLG_WEAK != 0 || match first_needed_match(parsed, from, need.file_offset as nat, 0) {
+        Some(to) => defines_version(parsed, from, need.name_offset as nat, to as int),
+        None => true,
+    }
+}
+
+pub open spec fn version_needs_met(parsed: Seq<ParsedObject>, order: Seq<usize>) -> bool {
+    forall|p: int, k: int|
+        0 <= p < order.len() && (order[p] as int) < parsed.len() && 0 <= k
+            < parsed[order[p] as int].verneeds@.len() ==> version_need_met(
+            parsed,
+            order[p] as int,
+            parsed[order[p] as int].verneeds@[k],
+        )
+}
+
 pub open spec fn discover_stage_spec(parsed: Seq<ParsedObject>, out: DiscoveryResult) -> bool {
     &&& valid_object_indices(out.order@, parsed.len())
     &&& cycle_handling_policy(out.order@)
     &&& (parsed.len() == 0 ==> out.order@.len() == 0)
     &&& (parsed.len() > 0 ==> out.order@.len() > 0 && out.order@[0] == 0)
+    &&& out.order@ == bfs_order(parsed)
     &&& direct_dep_closure(parsed, out.order@)
+    &&& version_needs_met(parsed, out.order@)
     &&& non_root_has_parent_edge(parsed, out.order@)
+    &&& init_order_spec(parsed, out.order@, out.init_order@)
 }
 
 } // verus!
@@ -1,8 +1,10 @@
 use crate::consts::*;
 use crate::final_stage_spec::*;
+use crate::mmap_plan_impl::object_base_exec;
 use crate::mmap_plan_spec::*;
 use crate::relocate_apply_spec::*;
 use crate::relocate_plan_spec::*;
+use crate::tls_layout_spec::*;
 use crate::types::*;
 use vstd::prelude::*;
 
@@ -19,54 +21,664 @@ fn add_u64_or_zero_exec(a: u64, b: u64) -> (r: u64)
     }
 }
 
-fn dyn_base_for_pos_exec(pos: usize) -> (r: u64)
+fn plan_covers_u64_exec(plan: &MmapPlan, addr: u64) -> (r: bool)
     ensures
-        r == dyn_base_for_pos(pos as int),
+        r == plan_covers_u64(*plan, addr),
 {
-    let mul = (pos as i128).checked_mul(DYN_BASE_STRIDE as i128);
-    if mul.is_none() {
-        0
+    addr >= plan.start && (addr - plan.start) as u128 + 8 <= plan.len as u128
+}
+
+fn plan_file_byte_exec(plan: &MmapPlan, file: &Vec<u8>, k: usize) -> (r: u8)
+    ensures
+        r == plan_file_byte(*plan, file@, k as int),
+{
+    let off = plan.file_offset as u128 + k as u128;
+    if k < plan.file_len && off < file.len() as u128 {
+        file[off as usize]
     } else {
-        let raw_opt = (DYN_BASE_START as i128).checked_add(mul.unwrap());
-        if raw_opt.is_none() {
-            0
-        } else {
-            let raw = raw_opt.unwrap();
-            if raw >= 0 && raw <= u64::MAX as i128 {
-                raw as u64
+        0
+    }
+}
+
+fn plan_initial_byte_exec(plan: &MmapPlan, file: &Vec<u8>, k: usize) -> (r: u8)
+    ensures
+        r == plan_initial_byte(*plan, file@, k as int),
+{
+    match plan.overlay {
+        Some(fill) => {
+            if fill.offset <= k && k - fill.offset < fill.len {
+                let j = k - fill.offset;
+                let off = fill.file_offset as u128 + j as u128;
+                if j < fill.file_len && off < file.len() as u128 {
+                    file[off as usize]
+                } else {
+                    0
+                }
             } else {
-                0
+                plan_file_byte_exec(plan, file, k)
             }
+        },
+        None => plan_file_byte_exec(plan, file, k),
+    }
+}
+
+fn plan_byte_exec(plan: &MmapPlan, file: &Vec<u8>, k: usize) -> (r: u8)
+    ensures
+        r == plan_byte(*plan, file@, k as int),
+{
+    let mut n: usize = plan.patches.len();
+    while n > 0
+        invariant
+            n <= plan.patches@.len(),
+            patched_byte(*plan, file@, plan.patches@.len() as nat, k as int) == patched_byte(
+                *plan,
+                file@,
+                n as nat,
+                k as int,
+            ),
+        decreases n,
+    {
+        let p = plan.patches[n - 1];
+        if p.offset <= k && k - p.offset < 8 {
+            let shift = 8 * (k - p.offset);
+            let b = ((p.value >> shift) & 0xff) as u8;
+            proof {
+                assert(b == u64_le_byte(p.value, k as int - p.offset as int));
+            }
+            return b;
         }
+        n = n - 1;
     }
+    plan_initial_byte_exec(plan, file, k)
 }
 
-fn object_base_from_exec(parsed: &Vec<ParsedObject>, order: &Vec<usize>, obj_idx: usize, scan: usize) -> (r: u64)
+fn plan_u64_exec(parsed: &Vec<ParsedObject>, plans: &Vec<MmapPlan>, addr: u64) -> (r: Option<u64>)
     ensures
-        r == object_base_from(parsed@, order@, obj_idx as int, scan as nat),
-    decreases if scan < order.len() { order.len() - scan } else { 0 },
+        match r {
+            Some(v) => plan_u64_at(parsed@, plans@, addr, v),
+            None => true,
+        },
 {
-    if scan >= order.len() {
-        0
-    } else {
-        let cur = order[scan];
-        if cur == obj_idx && cur < parsed.len() {
-            if parsed[cur].elf_type == ET_EXEC {
-                0
+    let empty: Vec<u8> = Vec::new();
+    let mut j: usize = 0;
+    while j < plans.len()
+        invariant
+            j <= plans.len(),
+        decreases plans.len() - j,
+    {
+        if plan_covers_u64_exec(&plans[j], addr) {
+            let plan = &plans[j];
+            let file = if plan.object_index < parsed.len() {
+                &parsed[plan.object_index].file_bytes
             } else {
-                dyn_base_for_pos_exec(scan)
+                &empty
+            };
+            let off = (addr - plan.start) as usize;
+            let b0 = plan_byte_exec(plan, file, off);
+            let b1 = plan_byte_exec(plan, file, off + 1);
+            let b2 = plan_byte_exec(plan, file, off + 2);
+            let b3 = plan_byte_exec(plan, file, off + 3);
+            let b4 = plan_byte_exec(plan, file, off + 4);
+            let b5 = plan_byte_exec(plan, file, off + 5);
+            let b6 = plan_byte_exec(plan, file, off + 6);
+            let b7 = plan_byte_exec(plan, file, off + 7);
+            let v = (b0 as u64) | ((b1 as u64) << 8u64) | ((b2 as u64) << 16u64) | ((b3 as u64) << 24u64)
+                | ((b4 as u64) << 32u64) | ((b5 as u64) << 40u64) | ((b6 as u64) << 48u64)
+                | ((b7 as u64) << 56u64);
+            proof {
+                let img = plan_image(*plan, plan_file(parsed@, *plan));
+                assert(file@ == plan_file(parsed@, *plan));
+                assert(plan_covers_u64(plans@[j as int], addr));
+                assert(img[off as int] == b0);
+                assert(img[off as int + 1] == b1);
+                assert(img[off as int + 2] == b2);
+                assert(img[off as int + 3] == b3);
+                assert(img[off as int + 4] == b4);
+                assert(img[off as int + 5] == b5);
+                assert(img[off as int + 6] == b6);
+                assert(img[off as int + 7] == b7);
+                assert(v == le_u64_at(img, (addr - plans@[j as int].start) as int));
             }
-        } else {
-            object_base_from_exec(parsed, order, obj_idx, scan + 1)
+            return Some(v);
+        }
+        j = j + 1;
+    }
+    None
+}
+
+fn array_slot_addr_exec(base: u64, vaddr: u64, i: usize) -> (r: Option<u64>)
+    ensures
+        match r {
+            Some(a) => a == array_slot_addr(base, vaddr, i as int),
+            None => true,
+        },
+{
+    if i as u64 > u64::MAX / 8 {
+        return None;
+    }
+    Some(add_u64_or_zero_exec(add_u64_or_zero_exec(base, vaddr), i as u64 * 8))
+}
+
+fn plan_executes_exec(plan: &MmapPlan, pc: u64) -> (r: bool)
+    ensures
+        r == plan_executes(*plan, pc),
+{
+    plan.prot.execute && pc >= plan.start && ((pc - plan.start) as u128) < plan.len as u128
+}
+
+fn resolver_target_mapped_exec(plans: &Vec<MmapPlan>, w: &ResolverWrite) -> (r: bool)
+    ensures
+        r ==> exists|j: int| 0 <= j < plans@.len() && plan_covers_u64(plans@[j], w.write_addr),
+{
+    let mut j: usize = 0;
+    while j < plans.len()
+        invariant
+            j <= plans.len(),
+        decreases plans.len() - j,
+    {
+        if plan_covers_u64_exec(&plans[j], w.write_addr) {
+            proof {
+                assert(plan_covers_u64(plans@[j as int], w.write_addr));
+            }
+            return true;
+        }
+        j = j + 1;
+    }
+    false
+}
+
+fn resolver_pc_executable_exec(plans: &Vec<MmapPlan>, w: &ResolverWrite) -> (r: bool)
+    ensures
+        r ==> exists|j: int| 0 <= j < plans@.len() && plan_executes(plans@[j], w.resolver_pc),
+{
+    let mut j: usize = 0;
+    while j < plans.len()
+        invariant
+            j <= plans.len(),
+        decreases plans.len() - j,
+    {
+        if plan_executes_exec(&plans[j], w.resolver_pc) {
+            proof {
+                assert(plan_executes(plans@[j as int], w.resolver_pc));
+            }
+            return true;
+        }
+        j = j + 1;
+    }
+    false
+}
+
+fn array_slot_values(
+    parsed: &Vec<ParsedObject>,
+    plans: &Vec<MmapPlan>,
+    name: &Vec<u8>,
+    base: u64,
+    vaddr: u64,
+    len: usize,
+) -> (r: Result<Vec<u64>, LoaderError>)
+    ensures
+        match r {
+            Ok(pcs) => {
+                &&& pcs@.len() == len
+                &&& forall|k: int|
+                    0 <= k < pcs@.len() ==> plan_u64_at(parsed@, plans@, array_slot_addr(base, vaddr, k), pcs@[k])
+                &&& forall|k: int|
+                    0 <= k < pcs@.len() ==> array_call_sound(parsed@, plans@, base, vaddr, len as nat, pcs@[k])
+            },
+            Err(_) => true,
+        },
+{
+    let mut pcs: Vec<u64> = Vec::new();
+    let mut j: usize = 0;
+    while j < len
+        invariant
+            j <= len,
+            pcs@.len() == j,
+            forall|k: int|
+                0 <= k < pcs@.len() ==> plan_u64_at(parsed@, plans@, array_slot_addr(base, vaddr, k), pcs@[k]),
+            forall|k: int| 0 <= k < pcs@.len() ==> array_call_sound(parsed@, plans@, base, vaddr, len as nat, pcs@[k]),
+        decreases len - j,
+    {
+        let slot = match array_slot_addr_exec(base, vaddr, j) {
+            Some(v) => v,
+            None => {
+                return Err(loader_error(LoaderStage::Final, name, LoaderErrorReason::BadDynamicSection));
+            },
+        };
+        let pc = match plan_u64_exec(parsed, plans, slot) {
+            Some(v) => v,
+            None => {
+                return Err(loader_error(
+                    LoaderStage::Final,
+                    name,
+                    LoaderErrorReason::UnmappedAddress { vaddr: slot },
+                ));
+            },
+        };
+        proof {
+            assert(plan_u64_at(parsed@, plans@, array_slot_addr(base, vaddr, j as int), pc));
+            assert(array_call_sound(parsed@, plans@, base, vaddr, len as nat, pc));
         }
+        pcs.push(pc);
+        j = j + 1;
+    }
+    Ok(pcs)
+}
+
+fn append_preinit_calls(
+    calls: &mut Vec<InitCall>,
+    parsed: &Vec<ParsedObject>,
+    order: &Vec<usize>,
+    plans: &Vec<MmapPlan>,
+    pcs: &Vec<u64>,
+)
+    requires
+        order@.len() > 0,
+        (order@[0] as int) < parsed@.len(),
+        forall|k: int| 0 <= k < pcs@.len() ==> preinit_pc(parsed@, order@, plans@, pcs@[k]),
+        forall|k: int|
+            0 <= k < old(calls)@.len() ==> preinit_pc(parsed@, order@, plans@, old(calls)@[k].pc)
+                && init_call_sound(parsed@, order@, plans@, old(calls)@[k]),
+    ensures
+        forall|k: int|
+            0 <= k < calls@.len() ==> preinit_pc(parsed@, order@, plans@, calls@[k].pc) && init_call_sound(
+                parsed@,
+                order@,
+                plans@,
+                calls@[k],
+            ),
+{
+    let mut i: usize = 0;
+    while i < pcs.len()
+        invariant
+            i <= pcs@.len(),
+            order@.len() > 0,
+            (order@[0] as int) < parsed@.len(),
+            forall|k: int| 0 <= k < pcs@.len() ==> preinit_pc(parsed@, order@, plans@, pcs@[k]),
+            forall|k: int|
+                0 <= k < calls@.len() ==> preinit_pc(parsed@, order@, plans@, calls@[k].pc)
+                    && init_call_sound(parsed@, order@, plans@, calls@[k]),
+        decreases pcs.len() - i,
+    {
+        let call = InitCall { object_name: clone_u8_vec(&parsed[order[0]].input_name), pc: pcs[i] };
+        proof {
+            assert(init_pc_at(parsed@, order@, plans@, 0, call.pc));
+            assert(init_call_sound(parsed@, order@, plans@, call));
+        }
+        calls.push(call);
+        i = i + 1;
+    }
+}
+
+proof fn lemma_init_key_before_mono(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    init_order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    pc: u64,
+    r: int,
+    k: int,
+    r2: int,
+    k2: int,
+)
+    requires
+        init_key_before(parsed, order, init_order, plans, pc, r, k),
+        r < r2 || (r == r2 && k <= k2),
+    ensures
+        init_key_before(parsed, order, init_order, plans, pc, r2, k2),
+{
+    if !preinit_pc(parsed, order, plans, pc) {
+        let (ri, ki) = choose|ri: int, ki: int|
+            (ri < r || (ri == r && ki < k)) && init_rank_pc(parsed, order, init_order, plans, ri, ki, pc);
+        assert(ri < r2 || (ri == r2 && ki < k2));
     }
 }
 
-fn object_base_exec(parsed: &Vec<ParsedObject>, order: &Vec<usize>, obj_idx: usize) -> (r: u64)
+proof fn lemma_term_key_before_mono(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    init_order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    pc: u64,
+    r: int,
+    k: int,
+    r2: int,
+    k2: int,
+)
+    requires
+        term_key_before(parsed, order, init_order, plans, pc, r, k),
+        r2 < r || (r2 == r && k <= k2),
+    ensures
+        term_key_before(parsed, order, init_order, plans, pc, r2, k2),
+{
+    let (ri, ki) = choose|ri: int, ki: int|
+        (ri > r || (ri == r && ki < k)) && term_rank_pc(parsed, order, init_order, plans, ri, ki, pc);
+    assert(ri > r2 || (ri == r2 && ki < k2));
+}
+
+// Pushes `pcs` as steps `first..` of the initializers at rank `r`.
+fn append_init_calls(
+    calls: &mut Vec<InitCall>,
+    parsed: &Vec<ParsedObject>,
+    order: &Vec<usize>,
+    init_order: &Vec<usize>,
+    plans: &Vec<MmapPlan>,
+    r: usize,
+    first: usize,
+    pcs: &Vec<u64>,
+)
+    requires
+        r < init_order@.len(),
+        (init_order@[r as int] as int) < order@.len(),
+        (order@[init_order@[r as int] as int] as int) < parsed@.len(),
+        forall|k: int|
+            0 <= k < pcs@.len() ==> init_rank_pc(parsed@, order@, init_order@, plans@, r as int, first + k, pcs@[k]),
+        forall|k: int|
+            0 <= k < old(calls)@.len() ==> init_call_sound(parsed@, order@, plans@, old(calls)@[k]),
+        constructors_ordered(parsed@, order@, init_order@, plans@, old(calls)@),
+        forall|k: int|
+            0 <= k < old(calls)@.len() ==> init_key_before(
+                parsed@,
+                order@,
+                init_order@,
+                plans@,
+                old(calls)@[k].pc,
+                r as int,
+                first as int,
+            ),
     ensures
-        r == object_base(parsed@, order@, obj_idx as int),
+        forall|k: int| 0 <= k < calls@.len() ==> init_call_sound(parsed@, order@, plans@, calls@[k]),
+        constructors_ordered(parsed@, order@, init_order@, plans@, calls@),
+        forall|k: int|
+            0 <= k < calls@.len() ==> init_key_before(
+                parsed@,
+                order@,
+                init_order@,
+                plans@,
+                calls@[k].pc,
+                r as int,
+                first + pcs@.len(),
+            ),
 {
-    object_base_from_exec(parsed, order, obj_idx, 0)
+    let p = init_order[r];
+    let mut i: usize = 0;
+    while i < pcs.len()
+        invariant
+            i <= pcs@.len(),
+            r < init_order@.len(),
+            p == init_order@[r as int],
+            (p as int) < order@.len(),
+            (order@[p as int] as int) < parsed@.len(),
+            forall|k: int|
+                0 <= k < pcs@.len() ==> init_rank_pc(parsed@, order@, init_order@, plans@, r as int, first + k, pcs@[k]),
+            forall|k: int| 0 <= k < calls@.len() ==> init_call_sound(parsed@, order@, plans@, calls@[k]),
+            constructors_ordered(parsed@, order@, init_order@, plans@, calls@),
+            forall|k: int|
+                0 <= k < calls@.len() ==> init_key_before(
+                    parsed@,
+                    order@,
+                    init_order@,
+                    plans@,
+                    calls@[k].pc,
+                    r as int,
+                    first + i,
+                ),
+        decreases pcs.len() - i,
+    {
+        let call = InitCall { object_name: clone_u8_vec(&parsed[order[p]].input_name), pc: pcs[i] };
+        let ghost old_calls = calls@;
+        let ghost key = first + i;
+        proof {
+            let obj = parsed@[order@[p as int] as int];
+            let base = object_base(parsed@, order@, order@[p as int] as int);
+            assert(init_rank_pc(parsed@, order@, init_order@, plans@, r as int, key, call.pc));
+            if key != 0 {
+                assert(plan_u64_at(
+                    parsed@,
+                    plans@,
+                    array_slot_addr(base, obj.dynamic.init_array_vaddr, key - 1),
+                    call.pc,
+                ));
+            }
+            assert(object_init_pc(parsed@, order@, plans@, p as int, call.pc));
+            assert(init_pc_at(parsed@, order@, plans@, p as int, call.pc));
+            assert(init_call_sound(parsed@, order@, plans@, call));
+        }
+        calls.push(call);
+        proof {
+            assert(calls@ == old_calls.push(call));
+            assert forall|x: int, y: int| 0 <= x < y < calls@.len() implies preinit_pc(
+                parsed@,
+                order@,
+                plans@,
+                calls@[x].pc,
+            ) || exists|ri: int, ki: int, rj: int, kj: int|
+                (ri < rj || (ri == rj && ki < kj)) && init_rank_pc(
+                    parsed@,
+                    order@,
+                    init_order@,
+                    plans@,
+                    ri,
+                    ki,
+                    calls@[x].pc,
+                ) && init_rank_pc(parsed@, order@, init_order@, plans@, rj, kj, calls@[y].pc) by {
+                if y == old_calls.len() && !preinit_pc(parsed@, order@, plans@, calls@[x].pc) {
+                    assert(init_key_before(parsed@, order@, init_order@, plans@, old_calls[x].pc, r as int, key));
+                    let (ri, ki) = choose|ri: int, ki: int|
+                        (ri < r || (ri == r && ki < key)) && init_rank_pc(
+                            parsed@,
+                            order@,
+                            init_order@,
+                            plans@,
+                            ri,
+                            ki,
+                            old_calls[x].pc,
+                        );
+                    assert(init_rank_pc(parsed@, order@, init_order@, plans@, ri, ki, calls@[x].pc));
+                    assert(init_rank_pc(parsed@, order@, init_order@, plans@, r as int, key, calls@[y].pc));
+                }
+            };
+            assert forall|x: int| 0 <= x < calls@.len() implies init_key_before(
+                parsed@,
+                order@,
+                init_order@,
+                plans@,
+                calls@[x].pc,
+                r as int,
+                key + 1,
+            ) by {
+                if x < old_calls.len() {
+                    lemma_init_key_before_mono(
+                        parsed@,
+                        order@,
+                        init_order@,
+                        plans@,
+                        calls@[x].pc,
+                        r as int,
+                        key,
+                        r as int,
+                        key + 1,
+                    );
+                } else {
+                    assert(init_rank_pc(parsed@, order@, init_order@, plans@, r as int, key, calls@[x].pc));
+                }
+            };
+        }
+        i = i + 1;
+    }
+}
+
+// Pushes `pcs` back to front as steps `first..` of the finalizers at rank
+// `r`, the order in which `DT_FINI_ARRAY` runs.
+fn append_term_calls(
+    calls: &mut Vec<TermCall>,
+    parsed: &Vec<ParsedObject>,
+    order: &Vec<usize>,
+    init_order: &Vec<usize>,
+    plans: &Vec<MmapPlan>,
+    r: usize,
+    first: usize,
+    pcs: &Vec<u64>,
+)
+    requires
+        r < init_order@.len(),
+        (init_order@[r as int] as int) < order@.len(),
+        (order@[init_order@[r as int] as int] as int) < parsed@.len(),
+        forall|k: int|
+            0 <= k < pcs@.len() ==> term_rank_pc(
+                parsed@,
+                order@,
+                init_order@,
+                plans@,
+                r as int,
+                first + (pcs@.len() - 1 - k),
+                pcs@[k],
+            ),
+        forall|k: int|
+            0 <= k < old(calls)@.len() ==> term_call_sound(parsed@, order@, plans@, old(calls)@[k]),
+        destructors_ordered(parsed@, order@, init_order@, plans@, old(calls)@),
+        forall|k: int|
+            0 <= k < old(calls)@.len() ==> term_key_before(
+                parsed@,
+                order@,
+                init_order@,
+                plans@,
+                old(calls)@[k].pc,
+                r as int,
+                first as int,
+            ),
+    ensures
+        forall|k: int| 0 <= k < calls@.len() ==> term_call_sound(parsed@, order@, plans@, calls@[k]),
+        destructors_ordered(parsed@, order@, init_order@, plans@, calls@),
+        forall|k: int|
+            0 <= k < calls@.len() ==> term_key_before(
+                parsed@,
+                order@,
+                init_order@,
+                plans@,
+                calls@[k].pc,
+                r as int,
+                first + pcs@.len(),
+            ),
+{
+    let p = init_order[r];
+    let mut i: usize = pcs.len();
+    while i > 0
+        invariant
+            i <= pcs@.len(),
+            r < init_order@.len(),
+            p == init_order@[r as int],
+            (p as int) < order@.len(),
+            (order@[p as int] as int) < parsed@.len(),
+            forall|k: int|
+                0 <= k < pcs@.len() ==> term_rank_pc(
+                    parsed@,
+                    order@,
+                    init_order@,
+                    plans@,
+                    r as int,
+                    first + (pcs@.len() - 1 - k),
+                    pcs@[k],
+                ),
+            forall|k: int| 0 <= k < calls@.len() ==> term_call_sound(parsed@, order@, plans@, calls@[k]),
+            destructors_ordered(parsed@, order@, init_order@, plans@, calls@),
+            forall|k: int|
+                0 <= k < calls@.len() ==> term_key_before(
+                    parsed@,
+                    order@,
+                    init_order@,
+                    plans@,
+                    calls@[k].pc,
+                    r as int,
+                    first + (pcs@.len() - i),
+                ),
+        decreases i,
+    {
+        let idx = i - 1;
+        let call = TermCall { object_name: clone_u8_vec(&parsed[order[p]].input_name), pc: pcs[idx] };
+        let ghost old_calls = calls@;
+        let ghost key = first + (pcs@.len() - i);
+        proof {
+            let obj = parsed@[order@[p as int] as int];
+            let base = object_base(parsed@, order@, order@[p as int] as int);
+            let len = obj.fini_array@.len() as int;
+            assert(key == first + (pcs@.len() - 1 - idx));
+            assert(term_rank_pc(parsed@, order@, init_order@, plans@, r as int, key, call.pc));
+            if key != len {
+                assert(plan_u64_at(
+                    parsed@,
+                    plans@,
+                    array_slot_addr(base, obj.dynamic.fini_array_vaddr, len - 1 - key),
+                    call.pc,
+                ));
+            }
+            assert(fini_pc_at(parsed@, order@, plans@, p as int, call.pc));
+            assert(term_call_sound(parsed@, order@, plans@, call));
+        }
+        calls.push(call);
+        proof {
+            assert(calls@ == old_calls.push(call));
+            assert forall|x: int, y: int| 0 <= x < y < calls@.len() implies exists|
+                ri: int,
+                ki: int,
+                rj: int,
+                kj: int,
+            |
+                (ri > rj || (ri == rj && ki < kj)) && term_rank_pc(
+                    parsed@,
+                    order@,
+                    init_order@,
+                    plans@,
+                    ri,
+                    ki,
+                    calls@[x].pc,
+                ) && term_rank_pc(parsed@, order@, init_order@, plans@, rj, kj, calls@[y].pc) by {
+                if y == old_calls.len() {
+                    assert(term_key_before(parsed@, order@, init_order@, plans@, old_calls[x].pc, r as int, key));
+                    let (ri, ki) = choose|ri: int, ki: int|
+                        (ri > r || (ri == r && ki < key)) && term_rank_pc(
+                            parsed@,
+                            order@,
+                            init_order@,
+                            plans@,
+                            ri,
+                            ki,
+                            old_calls[x].pc,
+                        );
+                    assert(term_rank_pc(parsed@, order@, init_order@, plans@, ri, ki, calls@[x].pc));
+                    assert(term_rank_pc(parsed@, order@, init_order@, plans@, r as int, key, calls@[y].pc));
+                } else {
+                    assert(calls@[x] == old_calls[x] && calls@[y] == old_calls[y]);
+                }
+            };
+            assert forall|x: int| 0 <= x < calls@.len() implies term_key_before(
+                parsed@,
+                order@,
+                init_order@,
+                plans@,
+                calls@[x].pc,
+                r as int,
+                key + 1,
+            ) by {
+                if x < old_calls.len() {
+                    lemma_term_key_before_mono(
+                        parsed@,
+                        order@,
+                        init_order@,
+                        plans@,
+                        calls@[x].pc,
+                        r as int,
+                        key,
+                        r as int,
+                        key + 1,
+                    );
+                } else {
+                    assert(term_rank_pc(parsed@, order@, init_order@, plans@, r as int, key, calls@[x].pc));
+                }
+            };
+        }
+        i = idx;
+    }
 }
 
 pub fn final_stage(plan: RelocateApplyOutput) -> (out: Result<LoaderOutput, LoaderError>)
@@ -78,152 +690,370 @@ pub fn final_stage(plan: RelocateApplyOutput) -> (out: Result<LoaderOutput, Load
                 plan.mmap_plans@[i],
             ),
         mmap_plans_non_overlapping(plan.mmap_plans@),
-        reloc_writes_sound(plan.parsed@, plan.discovered.order@, plan.resolved, plan.reloc_writes@),
+        reloc_writes_sound(plan.parsed@, plan.discovered.order@, plan.tls, plan.resolved, plan.reloc_writes@),
     ensures
         out.is_ok() ==> final_stage_spec(plan, out.unwrap()),
 {
     let RelocateApplyOutput {
         mmap_plans,
+        tls,
         reloc_writes,
+        resolver_writes,
         parsed,
         discovered,
         resolved,
     } = plan;
 
     let mut constructors: Vec<InitCall> = Vec::new();
-    let mut pos: usize = discovered.order.len();
-    while pos > 0
+    if discovered.order.len() > 0 {
+        let main_idx = discovered.order[0];
+        if main_idx >= parsed.len() {
+            return Err(loader_error(
+                LoaderStage::Final,
+                &Vec::new(),
+                LoaderErrorReason::InvalidObjectIndex { index: main_idx },
+            ));
+        }
+        if !parsed[main_idx].static_exec {
+            let base = object_base_exec(&parsed, &discovered.order, main_idx);
+            let preinit = match array_slot_values(
+                &parsed,
+                &mmap_plans,
+                &parsed[main_idx].input_name,
+                base,
+                parsed[main_idx].dynamic.preinit_array_vaddr,
+                parsed[main_idx].preinit_array.len(),
+            ) {
+                Ok(v) => v,
+                Err(e) => {
+                    return Err(e);
+                },
+            };
+            append_preinit_calls(&mut constructors, &parsed, &discovered.order, &mmap_plans, &preinit);
+        }
+    }
+    proof {
+        assert(constructors_ordered(
+            parsed@,
+            discovered.order@,
+            discovered.init_order@,
+            mmap_plans@,
+            constructors@,
+        ));
+    }
+
+    let mut r: usize = 0;
+    while r < discovered.init_order.len()
         invariant
-            pos <= discovered.order.len(),
+            r <= discovered.init_order@.len(),
             forall|k: int| 0 <= k < constructors@.len() ==> init_call_sound(
                 parsed@,
                 discovered.order@,
+                mmap_plans@,
                 constructors@[k],
             ),
-        decreases pos,
+            constructors_ordered(
+                parsed@,
+                discovered.order@,
+                discovered.init_order@,
+                mmap_plans@,
+                constructors@,
+            ),
+            forall|k: int|
+                0 <= k < constructors@.len() ==> init_key_before(
+                    parsed@,
+                    discovered.order@,
+                    discovered.init_order@,
+                    mmap_plans@,
+                    constructors@[k].pc,
+                    r as int,
+                    0,
+                ),
+        decreases discovered.init_order.len() - r,
     {
-        let obj_pos = pos - 1;
+        let obj_pos = discovered.init_order[r];
+        if obj_pos >= discovered.order.len() {
+            return Err(loader_error(
+                LoaderStage::Final,
+                &Vec::new(),
+                LoaderErrorReason::InvalidObjectIndex { index: obj_pos },
+            ));
+        }
         let obj_idx = discovered.order[obj_pos];
         if obj_idx >= parsed.len() {
-            return Err(LoaderError {});
+            return Err(loader_error(
+                LoaderStage::Final,
+                &Vec::new(),
+                LoaderErrorReason::InvalidObjectIndex { index: obj_idx },
+            ));
         }
         let base = object_base_exec(&parsed, &discovered.order, obj_idx);
-        let mut j: usize = 0;
-        while j < parsed[obj_idx].init_array.len()
-            invariant
-                j <= parsed@[obj_idx as int].init_array@.len(),
-                obj_pos < discovered.order.len(),
-                obj_idx == discovered.order@[obj_pos as int],
-                obj_idx < parsed.len(),
-                base == object_base(parsed@, discovered.order@, obj_idx as int),
-                forall|k: int| 0 <= k < constructors@.len() ==> init_call_sound(
-                    parsed@,
-                    discovered.order@,
-                    constructors@[k],
-                ),
-            decreases parsed@[obj_idx as int].init_array@.len() - j,
-        {
-            let call = InitCall {
-                object_name: clone_u8_vec(&parsed[obj_idx].input_name),
-                pc: add_u64_or_zero_exec(base, parsed[obj_idx].init_array[j]),
-            };
-            let ghost old_calls = constructors@;
-            constructors.push(call);
-            proof {
-                assert forall|k: int|
-                    0 <= k < constructors@.len() implies init_call_sound(
-                        parsed@,
-                        discovered.order@,
-                        constructors@[k],
-                    ) by {
-                    if k < old_calls.len() {
-                    } else {
-                        assert(k == old_calls.len());
-                        let p = obj_pos as int;
-                        let i0 = j as int;
-                        assert(0 <= p < discovered.order@.len());
-                        assert(discovered.order@[p] == obj_idx);
-                        assert((discovered.order@[p] as int) < parsed@.len());
-                        assert(0 <= i0 < parsed@[obj_idx as int].init_array@.len());
-                        assert(constructors@[k].pc == add_u64_or_zero(
-                            object_base(parsed@, discovered.order@, obj_idx as int),
-                            parsed@[obj_idx as int].init_array@[i0],
+        let ghost mut next_step: int = 0;
+        if !parsed[obj_idx].static_exec {
+            match parsed[obj_idx].init {
+                Some(f) => {
+                    let mut pcs: Vec<u64> = Vec::new();
+                    pcs.push(add_u64_or_zero_exec(base, f));
+                    proof {
+                        assert(init_rank_pc(
+                            parsed@,
+                            discovered.order@,
+                            discovered.init_order@,
+                            mmap_plans@,
+                            r as int,
+                            0,
+                            pcs@[0],
                         ));
-                        assert(init_call_sound(parsed@, discovered.order@, constructors@[k]));
                     }
+                    append_init_calls(
+                        &mut constructors,
+                        &parsed,
+                        &discovered.order,
+                        &discovered.init_order,
+                        &mmap_plans,
+                        r,
+                        0,
+                        &pcs,
+                    );
+                },
+                None => {
+                    proof {
+                        assert forall|k: int| 0 <= k < constructors@.len() implies init_key_before(
+                            parsed@,
+                            discovered.order@,
+                            discovered.init_order@,
+                            mmap_plans@,
+                            constructors@[k].pc,
+                            r as int,
+                            1,
+                        ) by {
+                            lemma_init_key_before_mono(
+                                parsed@,
+                                discovered.order@,
+                                discovered.init_order@,
+                                mmap_plans@,
+                                constructors@[k].pc,
+                                r as int,
+                                0,
+                                r as int,
+                                1,
+                            );
+                        };
+                    }
+                },
+            }
+            let pcs = match array_slot_values(
+                &parsed,
+                &mmap_plans,
+                &parsed[obj_idx].input_name,
+                base,
+                parsed[obj_idx].dynamic.init_array_vaddr,
+                parsed[obj_idx].init_array.len(),
+            ) {
+                Ok(v) => v,
+                Err(e) => {
+                    return Err(e);
+                },
+            };
+            proof {
+                assert forall|k: int| 0 <= k < pcs@.len() implies init_rank_pc(
+                    parsed@,
+                    discovered.order@,
+                    discovered.init_order@,
+                    mmap_plans@,
+                    r as int,
+                    1 + k,
+                    pcs@[k],
+                ) by {
+                    assert(1 + k - 1 == k);
                 };
             }
-            j = j + 1;
+            append_init_calls(
+                &mut constructors,
+                &parsed,
+                &discovered.order,
+                &discovered.init_order,
+                &mmap_plans,
+                r,
+                1,
+                &pcs,
+            );
+            proof {
+                next_step = 1 + pcs@.len();
+            }
+        }
+        proof {
+            assert forall|k: int| 0 <= k < constructors@.len() implies init_key_before(
+                parsed@,
+                discovered.order@,
+                discovered.init_order@,
+                mmap_plans@,
+                constructors@[k].pc,
+                r + 1,
+                0,
+            ) by {
+                lemma_init_key_before_mono(
+                    parsed@,
+                    discovered.order@,
+                    discovered.init_order@,
+                    mmap_plans@,
+                    constructors@[k].pc,
+                    r as int,
+                    next_step,
+                    r + 1,
+                    0,
+                );
+            };
         }
-        pos = obj_pos;
+        r = r + 1;
     }
 
     let mut destructors: Vec<TermCall> = Vec::new();
-    let mut pos2: usize = 0;
-    while pos2 < discovered.order.len()
+    let mut rank: usize = discovered.init_order.len();
+    while rank > 0
         invariant
-            pos2 <= discovered.order.len(),
+            rank <= discovered.init_order@.len(),
             forall|k: int| 0 <= k < destructors@.len() ==> term_call_sound(
                 parsed@,
                 discovered.order@,
+                mmap_plans@,
                 destructors@[k],
             ),
-        decreases discovered.order.len() - pos2,
+            destructors_ordered(
+                parsed@,
+                discovered.order@,
+                discovered.init_order@,
+                mmap_plans@,
+                destructors@,
+            ),
+            forall|k: int|
+                0 <= k < destructors@.len() ==> term_key_before(
+                    parsed@,
+                    discovered.order@,
+                    discovered.init_order@,
+                    mmap_plans@,
+                    destructors@[k].pc,
+                    rank - 1,
+                    0,
+                ),
+        decreases rank,
     {
-        let obj_idx = discovered.order[pos2];
+        let r = rank - 1;
+        let obj_pos = discovered.init_order[r];
+        if obj_pos >= discovered.order.len() {
+            return Err(loader_error(
+                LoaderStage::Final,
+                &Vec::new(),
+                LoaderErrorReason::InvalidObjectIndex { index: obj_pos },
+            ));
+        }
+        let obj_idx = discovered.order[obj_pos];
         if obj_idx >= parsed.len() {
-            return Err(LoaderError {});
+            return Err(loader_error(
+                LoaderStage::Final,
+                &Vec::new(),
+                LoaderErrorReason::InvalidObjectIndex { index: obj_idx },
+            ));
         }
         let base = object_base_exec(&parsed, &discovered.order, obj_idx);
-        let mut j: usize = parsed[obj_idx].fini_array.len();
-        while j > 0
-            invariant
-                j <= parsed@[obj_idx as int].fini_array@.len(),
-                pos2 < discovered.order.len(),
-                obj_idx == discovered.order@[pos2 as int],
-                obj_idx < parsed.len(),
-                base == object_base(parsed@, discovered.order@, obj_idx as int),
-                forall|k: int| 0 <= k < destructors@.len() ==> term_call_sound(
+        let ghost mut next_step: int = 0;
+        if !parsed[obj_idx].static_exec {
+            let pcs = match array_slot_values(
+                &parsed,
+                &mmap_plans,
+                &parsed[obj_idx].input_name,
+                base,
+                parsed[obj_idx].dynamic.fini_array_vaddr,
+                parsed[obj_idx].fini_array.len(),
+            ) {
+                Ok(v) => v,
+                Err(e) => {
+                    return Err(e);
+                },
+            };
+            let n = pcs.len();
+            proof {
+                assert forall|k: int| 0 <= k < pcs@.len() implies term_rank_pc(
                     parsed@,
                     discovered.order@,
-                    destructors@[k],
-                ),
-            decreases j,
-        {
-            let idx = j - 1;
-            let call = TermCall {
-                object_name: clone_u8_vec(&parsed[obj_idx].input_name),
-                pc: add_u64_or_zero_exec(base, parsed[obj_idx].fini_array[idx]),
-            };
-            let ghost old_calls = destructors@;
-            destructors.push(call);
+                    discovered.init_order@,
+                    mmap_plans@,
+                    r as int,
+                    0 + (pcs@.len() - 1 - k),
+                    pcs@[k],
+                ) by {
+                    assert(n - 1 - (n - 1 - k) == k);
+                };
+            }
+            append_term_calls(
+                &mut destructors,
+                &parsed,
+                &discovered.order,
+                &discovered.init_order,
+                &mmap_plans,
+                r,
+                0,
+                &pcs,
+            );
             proof {
-                assert forall|k: int|
-                    0 <= k < destructors@.len() implies term_call_sound(
-                        parsed@,
-                        discovered.order@,
-                        destructors@[k],
-                    ) by {
-                    if k < old_calls.len() {
-                    } else {
-                        assert(k == old_calls.len());
-                        let p = pos2 as int;
-                        let i0 = idx as int;
-                        assert(0 <= p < discovered.order@.len());
-                        assert(discovered.order@[p] == obj_idx);
-                        assert((discovered.order@[p] as int) < parsed@.len());
-                        assert(0 <= i0 < parsed@[obj_idx as int].fini_array@.len());
-                        assert(destructors@[k].pc == add_u64_or_zero(
-                            object_base(parsed@, discovered.order@, obj_idx as int),
-                            parsed@[obj_idx as int].fini_array@[i0],
+                next_step = n as int;
+            }
+            match parsed[obj_idx].fini {
+                Some(f) => {
+                    let mut pcs: Vec<u64> = Vec::new();
+                    pcs.push(add_u64_or_zero_exec(base, f));
+                    proof {
+                        assert(term_rank_pc(
+                            parsed@,
+                            discovered.order@,
+                            discovered.init_order@,
+                            mmap_plans@,
+                            r as int,
+                            n + (pcs@.len() - 1 - 0),
+                            pcs@[0],
                         ));
-                        assert(term_call_sound(parsed@, discovered.order@, destructors@[k]));
                     }
-                };
+                    append_term_calls(
+                        &mut destructors,
+                        &parsed,
+                        &discovered.order,
+                        &discovered.init_order,
+                        &mmap_plans,
+                        r,
+                        n,
+                        &pcs,
+                    );
+                    proof {
+                        next_step = n + 1;
+                    }
+                },
+                None => {},
             }
-            j = idx;
         }
-        pos2 = pos2 + 1;
+        proof {
+            assert forall|k: int| 0 <= k < destructors@.len() implies term_key_before(
+                parsed@,
+                discovered.order@,
+                discovered.init_order@,
+                mmap_plans@,
+                destructors@[k].pc,
+                r - 1,
+                0,
+            ) by {
+                lemma_term_key_before_mono(
+                    parsed@,
+                    discovered.order@,
+                    discovered.init_order@,
+                    mmap_plans@,
+                    destructors@[k].pc,
+                    r as int,
+                    next_step,
+                    r - 1,
+                    0,
+                );
+            };
+        }
+        rank = r;
     }
 
     let entry_pc = if parsed.len() == 0 {
@@ -233,11 +1063,37 @@ pub fn final_stage(plan: RelocateApplyOutput) -> (out: Result<LoaderOutput, Load
         add_u64_or_zero_exec(main_base, parsed[0].entry)
     };
 
+    let mut ri: usize = 0;
+    while ri < resolver_writes.len()
+        invariant
+            ri <= resolver_writes.len(),
+            forall|k: int| 0 <= k < ri ==> resolver_write_mapped(mmap_plans@, resolver_writes@[k]),
+        decreases resolver_writes.len() - ri,
+    {
+        if !resolver_target_mapped_exec(&mmap_plans, &resolver_writes[ri]) {
+            return Err(loader_error(
+                LoaderStage::Final,
+                &resolver_writes[ri].object_name,
+                LoaderErrorReason::UnmappedAddress { vaddr: resolver_writes[ri].write_addr },
+            ));
+        }
+        if !resolver_pc_executable_exec(&mmap_plans, &resolver_writes[ri]) {
+            return Err(loader_error(
+                LoaderStage::Final,
+                &resolver_writes[ri].object_name,
+                LoaderErrorReason::UnmappedAddress { vaddr: resolver_writes[ri].resolver_pc },
+            ));
+        }
+        ri = ri + 1;
+    }
+
     let out_plan = LoaderOutput {
         entry_pc,
         constructors,
         destructors,
         mmap_plans,
+        tls,
+        resolver_writes,
         reloc_writes,
         parsed,
         discovered,
@@ -248,13 +1104,29 @@ pub fn final_stage(plan: RelocateApplyOutput) -> (out: Result<LoaderOutput, Load
         assert(forall|i: int| 0 <= i < out_plan.constructors@.len() ==> init_call_sound(
             out_plan.parsed@,
             out_plan.discovered.order@,
+            out_plan.mmap_plans@,
             out_plan.constructors@[i],
         ));
+        assert(constructors_ordered(
+            out_plan.parsed@,
+            out_plan.discovered.order@,
+            out_plan.discovered.init_order@,
+            out_plan.mmap_plans@,
+            out_plan.constructors@,
+        ));
         assert(forall|i: int| 0 <= i < out_plan.destructors@.len() ==> term_call_sound(
             out_plan.parsed@,
             out_plan.discovered.order@,
+            out_plan.mmap_plans@,
             out_plan.destructors@[i],
         ));
+        assert(destructors_ordered(
+            out_plan.parsed@,
+            out_plan.discovered.order@,
+            out_plan.discovered.init_order@,
+            out_plan.mmap_plans@,
+            out_plan.destructors@,
+        ));
         assert(forall|i: int|
             0 <= i < out_plan.mmap_plans@.len() ==> mmap_plan_sound(
                 out_plan.parsed@,
@@ -262,9 +1134,15 @@ pub fn final_stage(plan: RelocateApplyOutput) -> (out: Result<LoaderOutput, Load
                 out_plan.mmap_plans@[i],
             ));
         assert(mmap_plans_non_overlapping(out_plan.mmap_plans@));
+        assert(forall|i: int|
+            0 <= i < out_plan.resolver_writes@.len() ==> resolver_write_mapped(
+                out_plan.mmap_plans@,
+                out_plan.resolver_writes@[i],
+            ));
         assert(reloc_writes_sound(
             out_plan.parsed@,
             out_plan.discovered.order@,
+            out_plan.tls,
             out_plan.resolved,
             out_plan.reloc_writes@,
         ));
@@ -6,38 +6,251 @@ use vstd::prelude::*;
 
 verus! {
 
+pub open spec fn plan_covers_u64(plan: MmapPlan, addr: u64) -> bool {
+    addr >= plan.start && (addr - plan.start) as int + 8 <= plan.len
+}
+
+pub open spec fn le_u64_at(bytes: Seq<u8>, off: int) -> u64 {
+    (bytes[off] as u64) | ((bytes[off + 1] as u64) << 8u64) | ((bytes[off + 2] as u64) << 16u64)
+        | ((bytes[off + 3] as u64) << 24u64) | ((bytes[off + 4] as u64) << 32u64)
+        | ((bytes[off + 5] as u64) << 40u64) | ((bytes[off + 6] as u64) << 48u64)
+        | ((bytes[off + 7] as u64) << 56u64)
+}
+
+// `value` is the 8-byte word at `addr` in the relocated image.
+pub open spec fn plan_u64_at(parsed: Seq<ParsedObject>, plans: Seq<MmapPlan>, addr: u64, value: u64) -> bool {
+    exists|j: int|
+        0 <= j < plans.len() && plan_covers_u64(plans[j], addr) && value == le_u64_at(
+            plan_image(plans[j], plan_file(parsed, plans[j])),
+            (addr - plans[j].start) as int,
+        )
+}
+
+pub open spec fn array_slot_addr(base: u64, vaddr: u64, i: int) -> u64 {
+    add_u64_or_zero(add_u64_or_zero(base, vaddr), (i * 8) as u64)
+}
+
+pub open spec fn array_call_sound(
+    parsed: Seq<ParsedObject>,
+    plans: Seq<MmapPlan>,
+    base: u64,
+    vaddr: u64,
+    len: nat,
+    pc: u64,
+) -> bool {
+    exists|i: int| 0 <= i < len && plan_u64_at(parsed, plans, array_slot_addr(base, vaddr, i), pc)
+}
+
+pub open spec fn func_call_sound(base: u64, func: Option<u64>, pc: u64) -> bool {
+    match func {
+        Some(f) => pc == add_u64_or_zero(base, f),
+        None => false,
+    }
+}
+
+// Initializers of the object at load position `p`: `DT_INIT` and the
+// `DT_INIT_ARRAY` slots. A static executable runs its own.
+pub open spec fn object_init_pc(parsed: Seq<ParsedObject>, order: Seq<usize>, plans: Seq<MmapPlan>, p: int, pc: u64) -> bool {
+    let obj = parsed[order[p] as int];
+    let base = object_base(parsed, order, order[p] as int);
+    &&& !obj.static_exec
+    &&& {
+        ||| func_call_sound(base, obj.init, pc)
+        ||| array_call_sound(parsed, plans, base, obj.dynamic.init_array_vaddr, obj.init_array@.len(), pc)
+    }
+}
+
+// A `DT_PREINIT_ARRAY` slot of the main program.
+pub open spec fn preinit_pc(parsed: Seq<ParsedObject>, order: Seq<usize>, plans: Seq<MmapPlan>, pc: u64) -> bool {
+    let obj = parsed[order[0] as int];
+    let base = object_base(parsed, order, order[0] as int);
+    &&& order.len() > 0
+    &&& (order[0] as int) < parsed.len()
+    &&& !obj.static_exec
+    &&& array_call_sound(parsed, plans, base, obj.dynamic.preinit_array_vaddr, obj.preinit_array@.len(), pc)
+}
+
+pub open spec fn init_pc_at(parsed: Seq<ParsedObject>, order: Seq<usize>, plans: Seq<MmapPlan>, p: int, pc: u64) -> bool {
+    ||| p == 0 && preinit_pc(parsed, order, plans, pc)
+    ||| object_init_pc(parsed, order, plans, p, pc)
+}
+
+// Step `k` of the initializers of the object at load position `p`: 0 is
+// `DT_INIT`, and `k >= 1` is slot `k - 1` of `DT_INIT_ARRAY`.
+pub open spec fn init_slot_pc(parsed: Seq<ParsedObject>, order: Seq<usize>, plans: Seq<MmapPlan>, p: int, k: int, pc: u64) -> bool {
+    let obj = parsed[order[p] as int];
+    let base = object_base(parsed, order, order[p] as int);
+    &&& !obj.static_exec
+    &&& if k == 0 {
+        func_call_sound(base, obj.init, pc)
+    } else {
+        &&& 1 <= k <= obj.init_array@.len()
+        &&& plan_u64_at(parsed, plans, array_slot_addr(base, obj.dynamic.init_array_vaddr, k - 1), pc)
+    }
+}
+
+// `pc` is step `k` of the initializers of the object at rank `r` of `init_order`.
+pub open spec fn init_rank_pc(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    init_order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    r: int,
+    k: int,
+    pc: u64,
+) -> bool {
+    &&& 0 <= r < init_order.len()
+    &&& (init_order[r] as int) < order.len()
+    &&& (order[init_order[r] as int] as int) < parsed.len()
+    &&& init_slot_pc(parsed, order, plans, init_order[r] as int, k, pc)
+}
+
+// Constructors run the main program's `DT_PREINIT_ARRAY` first, then the
+// remaining initializers object by object in `init_order`, each object's
+// `DT_INIT` before its `DT_INIT_ARRAY` slots in index order.
+pub open spec fn constructors_ordered(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    init_order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    calls: Seq<InitCall>,
+) -> bool {
+    forall|i: int, j: int|
+        0 <= i < j < calls.len() ==> preinit_pc(parsed, order, plans, calls[i].pc) || exists|
+            ri: int,
+            ki: int,
+            rj: int,
+            kj: int,
+        |
+            (ri < rj || (ri == rj && ki < kj)) && init_rank_pc(parsed, order, init_order, plans, ri, ki, calls[i].pc)
+                && init_rank_pc(parsed, order, init_order, plans, rj, kj, calls[j].pc)
+}
+
+// `pc` is a preinitializer, or an initializer that precedes step `k` at rank `r`.
+pub open spec fn init_key_before(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    init_order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    pc: u64,
+    r: int,
+    k: int,
+) -> bool {
+    ||| preinit_pc(parsed, order, plans, pc)
+    ||| exists|ri: int, ki: int|
+        (ri < r || (ri == r && ki < k)) && init_rank_pc(parsed, order, init_order, plans, ri, ki, pc)
+}
+
+pub open spec fn fini_pc_at(parsed: Seq<ParsedObject>, order: Seq<usize>, plans: Seq<MmapPlan>, p: int, pc: u64) -> bool {
+    let obj = parsed[order[p] as int];
+    let base = object_base(parsed, order, order[p] as int);
+    &&& !obj.static_exec
+    &&& {
+        ||| func_call_sound(base, obj.fini, pc)
+        ||| array_call_sound(parsed, plans, base, obj.dynamic.fini_array_vaddr, obj.fini_array@.len(), pc)
+    }
+}
+
+// Step `k` of the finalizers of the object at load position `p`: the
+// `DT_FINI_ARRAY` slots from last to first, then `DT_FINI` at `k == len`.
+pub open spec fn fini_slot_pc(parsed: Seq<ParsedObject>, order: Seq<usize>, plans: Seq<MmapPlan>, p: int, k: int, pc: u64) -> bool {
+    let obj = parsed[order[p] as int];
+    let base = object_base(parsed, order, order[p] as int);
+    let len = obj.fini_array@.len() as int;
+    &&& !obj.static_exec
+    &&& if k == len {
+        func_call_sound(base, obj.fini, pc)
+    } else {
+        &&& 0 <= k < len
+        &&& plan_u64_at(parsed, plans, array_slot_addr(base, obj.dynamic.fini_array_vaddr, len - 1 - k), pc)
+    }
+}
+
+pub open spec fn term_rank_pc(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    init_order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    r: int,
+    k: int,
+    pc: u64,
+) -> bool {
+    &&& 0 <= r < init_order.len()
+    &&& (init_order[r] as int) < order.len()
+    &&& (order[init_order[r] as int] as int) < parsed.len()
+    &&& fini_slot_pc(parsed, order, plans, init_order[r] as int, k, pc)
+}
+
+// Destructors run object by object in reverse `init_order`, each object's
+// steps in `fini_slot_pc` order.
+pub open spec fn destructors_ordered(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    init_order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    calls: Seq<TermCall>,
+) -> bool {
+    forall|i: int, j: int|
+        0 <= i < j < calls.len() ==> exists|ri: int, ki: int, rj: int, kj: int|
+            (ri > rj || (ri == rj && ki < kj)) && term_rank_pc(parsed, order, init_order, plans, ri, ki, calls[i].pc)
+                && term_rank_pc(parsed, order, init_order, plans, rj, kj, calls[j].pc)
+}
+
+// `pc` is a finalizer that precedes step `k` at rank `r`.
+pub open spec fn term_key_before(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    init_order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    pc: u64,
+    r: int,
+    k: int,
+) -> bool {
+    exists|ri: int, ki: int|
+        (ri > r || (ri == r && ki < k)) && term_rank_pc(parsed, order, init_order, plans, ri, ki, pc)
+}
+
 pub open spec fn init_call_sound(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    plans: Seq<MmapPlan>,
     call: InitCall,
 ) -> bool {
-    exists|p: int, i: int| {
+    exists|p: int| {
         &&& 0 <= p < order.len()
         &&& (order[p] as int) < parsed.len()
-        &&& 0 <= i < parsed[order[p] as int].init_array@.len()
-        &&& call.pc == add_u64_or_zero(
-            object_base(parsed, order, order[p] as int),
-            parsed[order[p] as int].init_array@[i],
-        )
+        &&& init_pc_at(parsed, order, plans, p, call.pc)
     }
 }
 
 pub open spec fn term_call_sound(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    plans: Seq<MmapPlan>,
     call: TermCall,
 ) -> bool {
-    exists|p: int, i: int| {
+    exists|p: int| {
         &&& 0 <= p < order.len()
         &&& (order[p] as int) < parsed.len()
-        &&& 0 <= i < parsed[order[p] as int].fini_array@.len()
-        &&& call.pc == add_u64_or_zero(
-            object_base(parsed, order, order[p] as int),
-            parsed[order[p] as int].fini_array@[i],
-        )
+        &&& fini_pc_at(parsed, order, plans, p, call.pc)
     }
 }
 
+pub open spec fn plan_executes(plan: MmapPlan, pc: u64) -> bool {
+    plan.prot.execute && pc >= plan.start && ((pc - plan.start) as int) < plan.len
+}
+
+// Runtime contract for resolver writes: after mapping and protecting every
+// plan, and before any constructor, the runtime calls each resolver in order
+// from an executable planned region, then stores `result + addend` at
+// `write_addr`, which lies inside a planned region. Regions being written
+// are made writable but not executable, then restored to their planned
+// protection.
+pub open spec fn resolver_write_mapped(plans: Seq<MmapPlan>, w: ResolverWrite) -> bool {
+    &&& exists|j: int| 0 <= j < plans.len() && plan_covers_u64(plans[j], w.write_addr)
+    &&& exists|j: int| 0 <= j < plans.len() && plan_executes(plans[j], w.resolver_pc)
+}
+
 pub open spec fn expected_entry_pc(parsed: Seq<ParsedObject>, load_order: Seq<usize>) -> u64 {
     if parsed.len() == 0 {
         0
@@ -52,15 +265,38 @@ pub open spec fn final_stage_spec(in_plan: RelocateApplyOutput, out_plan: Loader
         0 <= i < out_plan.constructors@.len() ==> init_call_sound(
             out_plan.parsed@,
             out_plan.discovered.order@,
+            out_plan.mmap_plans@,
             out_plan.constructors@[i],
         )
+    &&& constructors_ordered(
+        out_plan.parsed@,
+        out_plan.discovered.order@,
+        out_plan.discovered.init_order@,
+        out_plan.mmap_plans@,
+        out_plan.constructors@,
+    )
     &&& forall|i: int|
         0 <= i < out_plan.destructors@.len() ==> term_call_sound(
             out_plan.parsed@,
             out_plan.discovered.order@,
+            out_plan.mmap_plans@,
             out_plan.destructors@[i],
         )
+    &&& destructors_ordered(
+        out_plan.parsed@,
+        out_plan.discovered.order@,
+        out_plan.discovered.init_order@,
+        out_plan.mmap_plans@,
+        out_plan.destructors@,
+    )
     &&& out_plan.mmap_plans@ == in_plan.mmap_plans@
+    &&& out_plan.tls == in_plan.tls
+    &&& out_plan.resolver_writes@ == in_plan.resolver_writes@
+    &&& forall|i: int|
+        0 <= i < out_plan.resolver_writes@.len() ==> resolver_write_mapped(
+            out_plan.mmap_plans@,
+            out_plan.resolver_writes@[i],
+        )
     &&& out_plan.reloc_writes@ == in_plan.reloc_writes@
     &&& out_plan.parsed@ == in_plan.parsed@
     &&& out_plan.discovered == in_plan.discovered
@@ -75,6 +311,7 @@ pub open spec fn final_stage_spec(in_plan: RelocateApplyOutput, out_plan: Loader
     &&& reloc_writes_sound(
         out_plan.parsed@,
         out_plan.discovered.order@,
+        out_plan.tls,
         out_plan.resolved,
         out_plan.reloc_writes@,
     )
@@ -1,3 +1,4 @@
+mod aslr;
 mod debug;
 mod discover_impl;
 mod discover_spec;
@@ -16,10 +17,13 @@ mod relocate_plan_spec;
 mod runtime;
 mod resolve_impl;
 mod resolve_spec;
+mod search_path;
+mod tls_layout_impl;
+mod tls_layout_spec;
 mod types;
 
-use crate::debug::print_loader_plan;
-use crate::types::{LoaderError, LoaderInput, LoaderObject, LoaderOutput};
+use crate::debug::{print_loader_plan, render_loader_error};
+use crate::types::{LoaderError, LoaderErrorReason, LoaderInput, LoaderObject, LoaderOutput, LoaderStage};
 use vstd::prelude::*;
 
 verus! {
@@ -28,6 +32,8 @@ pub fn plan_loader(input: LoaderInput) -> (out: Result<LoaderOutput, LoaderError
     ensures
         main_spec::plan_result_spec(input, out),
 {
+    let strong_before_weak = input.strong_before_weak;
+    let w_xor_x = input.w_xor_x;
     let parsed_res = parse_impl::parse_stage(input);
     match parsed_res {
         Err(e) => Err(e),
@@ -36,27 +42,38 @@ pub fn plan_loader(input: LoaderInput) -> (out: Result<LoaderOutput, LoaderError
             match discovered_res {
                 Err(e) => Err(e),
                 Ok(discovered) => {
-                    let resolved_res = resolve_impl::resolve_stage_ref(&parsed, &discovered);
+                    let resolved_res = resolve_impl::resolve_stage_ref(&parsed, &discovered, strong_before_weak);
                     match resolved_res {
                         Err(e) => Err(e),
                         Ok(resolved) => {
-                            let mmap_plans_res = mmap_plan_impl::mmap_plan_stage(&parsed, &discovered);
+                            let mmap_plans_res = mmap_plan_impl::mmap_plan_stage(&parsed, &discovered, w_xor_x);
                             match mmap_plans_res {
                                 Err(e) => Err(e),
                                 Ok(mmap_plans) => {
-                                    let plan_reloc_res = relocate_plan_impl::plan_relocate_stage(
-                                        parsed,
-                                        discovered,
-                                        resolved,
-                                        mmap_plans,
-                                    );
-                                    match plan_reloc_res {
+                                    let tls_res = tls_layout_impl::tls_layout_stage(&parsed, &discovered);
+                                    match tls_res {
                                         Err(e) => Err(e),
-                                        Ok(plan_reloc) => {
-                                            let reloc_apply_res = relocate_apply_impl::relocate_apply_stage(plan_reloc);
-                                            match reloc_apply_res {
+                                        Ok(tls) => {
+                                            let plan_reloc_res = relocate_plan_impl::plan_relocate_stage(
+                                                parsed,
+                                                discovered,
+                                                resolved,
+                                                mmap_plans,
+                                                tls,
+                                            );
+                                            match plan_reloc_res {
                                                 Err(e) => Err(e),
-                                                Ok(reloc_applied) => final_stage_impl::final_stage(reloc_applied),
+                                                Ok(plan_reloc) => {
+                                                    let reloc_apply_res = relocate_apply_impl::relocate_apply_stage(
+                                                        plan_reloc,
+                                                    );
+                                                    match reloc_apply_res {
+                                                        Err(e) => Err(e),
+                                                        Ok(reloc_applied) => final_stage_impl::final_stage(
+                                                            reloc_applied,
+                                                        ),
+                                                    }
+                                                }
                                             }
                                         }
                                     }
@@ -72,55 +89,236 @@ pub fn plan_loader(input: LoaderInput) -> (out: Result<LoaderOutput, LoaderError
 
 } // verus!
 
-fn read_loader_input(paths: &[String]) -> Result<LoaderInput, LoaderError> {
-    let mut objects: Vec<LoaderObject> = Vec::new();
+struct LoadedFile {
+    path: std::path::PathBuf,
+    name: Vec<u8>,
+    file: std::fs::File,
+    bytes: Vec<u8>,
+    peek: search_path::DynamicPeek,
+}
+
+fn load_file(path: &std::path::Path) -> Result<LoadedFile, LoaderError> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned().into_bytes())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned().into_bytes());
+
+    // The handle is kept so the runtime maps the same file that was parsed.
+    let mut bytes = Vec::new();
+    let opened = std::fs::File::open(path)
+        .and_then(|mut f| std::io::Read::read_to_end(&mut f, &mut bytes).map(|_| f));
+    let file = match opened {
+        Ok(f) => f,
+        Err(e) => {
+            return Err(LoaderError {
+                stage: LoaderStage::Input,
+                object_name: path.to_string_lossy().into_owned().into_bytes(),
+                reason: LoaderErrorReason::Io { errno: e.raw_os_error().unwrap_or(0) },
+            })
+        }
+    };
+
+    let peek = search_path::peek_dynamic(&bytes).unwrap_or_default();
+    Ok(LoadedFile { path: path.to_path_buf(), name, file, bytes, peek })
+}
+
+fn is_loaded(files: &[LoadedFile], needed: &[u8]) -> bool {
+    files.iter().any(|f| f.name == needed || f.peek.soname.as_deref() == Some(needed))
+}
+
+// Loads the main executable and any explicitly listed objects, then pulls in
+// the remaining DT_NEEDED dependencies breadth-first through the search path.
+// Names that cannot be located are left for the discover stage to reject.
+fn read_loader_input(
+    paths: &[std::ffi::OsString],
+    config: &search_path::SearchConfig,
+    aslr_seed: Option<u64>,
+) -> Result<(LoaderInput, Vec<std::fs::File>), LoaderError> {
+    let mut files: Vec<LoadedFile> = Vec::new();
     for path in paths {
-        let bytes = std::fs::read(path).unwrap_or_else(|_| panic!("failed to read file: {}", path));
+        files.push(load_file(std::path::Path::new(path))?);
+    }
 
-        let name = std::path::Path::new(path)
-            .file_name()
-            .map(|n| n.to_string_lossy().into_owned().into_bytes())
-            .unwrap_or_else(|| path.clone().into_bytes());
+    let mut next = 0;
+    while next < files.len() {
+        for needed in files[next].peek.needed.clone() {
+            if is_loaded(&files, &needed) {
+                continue;
+            }
+            let found = search_path::find_library(
+                &needed,
+                &files[next].path,
+                &files[next].peek,
+                &files[0].path,
+                &files[0].peek,
+                config,
+            );
+            if let Some(path) = found {
+                let file = load_file(&path)?;
+                if !is_loaded(&files, &file.name) {
+                    files.push(file);
+                }
+            }
+        }
+        next += 1;
+    }
 
-        objects.push(LoaderObject { name, bytes });
+    let bases = match aslr_seed {
+        Some(seed) => {
+            let peeks: Vec<&search_path::DynamicPeek> = files.iter().map(|f| &f.peek).collect();
+            aslr::pick_load_bases(&peeks, &mut aslr::BaseRng::from_seed(seed))
+        }
+        None => vec![None; files.len()],
+    };
+    let mut handles = Vec::new();
+    let mut objects = Vec::new();
+    for (f, load_base) in files.into_iter().zip(bases) {
+        handles.push(f.file);
+        objects.push(LoaderObject { name: f.name, bytes: f.bytes, load_base });
     }
+    Ok((LoaderInput { objects, strong_before_weak: false, w_xor_x: false }, handles))
+}
 
-    Ok(LoaderInput { objects })
+#[derive(Clone, Copy)]
+enum Aslr {
+    Off,
+    Random,
+    Seeded(u64),
 }
 
-fn run_program(paths: &[String], print_debug: bool) {
-    let input = match read_loader_input(paths) {
-        Ok(v) => v,
-        Err(_) => panic!("planning failed"),
+fn run_program(
+    paths: &[std::ffi::OsString],
+    config: &search_path::SearchConfig,
+    args: &runtime::ProcessArgs,
+    strong_before_weak: bool,
+    w_xor_x: bool,
+    aslr: Aslr,
+    print_debug: bool,
+) -> Result<(), LoaderError> {
+    let aslr_seed = match aslr {
+        Aslr::Off => None,
+        Aslr::Random => Some(aslr::random_seed()?),
+        Aslr::Seeded(seed) => Some(seed),
     };
+    let (mut input, files) = read_loader_input(paths, config, aslr_seed)?;
+    input.strong_before_weak = strong_before_weak;
+    input.w_xor_x = w_xor_x;
 
-    let plan = match plan_loader(input) {
-        Ok(v) => v,
-        Err(_) => panic!("planning failed"),
-    };
+    // Reserve address space for the first plan, then re-plan against the
+    // bases that were actually obtained.
+    let first = plan_loader(input.clone())?;
+    let bases = runtime::reserve_objects(&first)?;
+    for (obj, base) in input.objects.iter_mut().zip(bases) {
+        if base.is_some() {
+            obj.load_base = base;
+        }
+    }
+    let plan = plan_loader(input)?;
 
     if print_debug {
+        if let Some(seed) = aslr_seed {
+            println!("aslr_seed={}", seed);
+        }
         print_loader_plan(&plan);
     }
 
-    if runtime::run_runtime(&plan).is_err() {
-        panic!("main failed");
-    }
+    runtime::run_runtime(&plan, &files, args)
+}
+
+fn usage() -> ! {
+    eprintln!("usage:");
+    eprintln!("  veriload [options] <main> [<lib> ...] [-- <arg> ...]");
+    eprintln!("options:");
+    eprintln!("  --debug                        print the loader plan before running");
+    eprintln!("  --default-lib-path <dir:...>   default library search path (default: /lib:/usr/lib:$ORIGIN/lib)");
+    eprintln!("  --clear-env                    start the program with an empty environment");
+    eprintln!("  --strong-before-weak           prefer a global definition over an earlier weak one");
+    eprintln!("  --w-xor-x                      refuse segments that would be writable and executable");
+    eprintln!("  --aslr                         load ET_DYN objects at random bases");
+    eprintln!("  --seed <n>                     like --aslr, with bases derived from a fixed seed");
+    std::process::exit(2);
 }
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    let (print_debug, paths): (bool, &[String]) = if args.len() >= 2 && args[1] == "--debug" {
-        (true, &args[2..])
-    } else {
-        (false, &args[1..])
+    use std::os::unix::ffi::OsStrExt;
+
+    let args: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    let mut print_debug = false;
+    let mut clear_env = false;
+    let mut strong_before_weak = false;
+    let mut w_xor_x = false;
+    let mut aslr = Aslr::Off;
+    let mut default_paths = search_path::DEFAULT_LIBRARY_PATH.to_string();
+    let mut idx = 1;
+    while idx < args.len() {
+        if args[idx] == "--debug" {
+            print_debug = true;
+            idx += 1;
+        } else if args[idx] == "--clear-env" {
+            clear_env = true;
+            idx += 1;
+        } else if args[idx] == "--strong-before-weak" {
+            strong_before_weak = true;
+            idx += 1;
+        } else if args[idx] == "--w-xor-x" {
+            w_xor_x = true;
+            idx += 1;
+        } else if args[idx] == "--aslr" {
+            if let Aslr::Off = aslr {
+                aslr = Aslr::Random;
+            }
+            idx += 1;
+        } else if args[idx] == "--seed" {
+            match args.get(idx + 1).and_then(|a| a.to_str()).and_then(|s| s.parse::<u64>().ok()) {
+                Some(seed) => aslr = Aslr::Seeded(seed),
+                None => usage(),
+            }
+            idx += 2;
+        } else if args[idx] == "--default-lib-path" {
+            if idx + 1 >= args.len() {
+                usage();
+            }
+            default_paths = args[idx + 1].to_string_lossy().into_owned();
+            idx += 2;
+        } else {
+            break;
+        }
+    }
+    let rest = &args[idx..];
+    let (paths, program_args) = match rest.iter().position(|a| a == "--") {
+        Some(sep) => (&rest[..sep], &rest[sep + 1..]),
+        None => (rest, &rest[rest.len()..]),
     };
 
     if paths.is_empty() {
-        eprintln!("usage:");
-        eprintln!("  veriload [--debug] <elf> [<elf> ...]");
-        return;
+        usage();
     }
 
-    run_program(paths, print_debug);
+    let mut argv: Vec<Vec<u8>> = vec![paths[0].as_bytes().to_vec()];
+    argv.extend(program_args.iter().map(|a| a.as_bytes().to_vec()));
+    let envp: Vec<Vec<u8>> = if clear_env {
+        Vec::new()
+    } else {
+        std::env::vars_os()
+            .map(|(k, v)| {
+                let mut kv = k.as_bytes().to_vec();
+                kv.push(b'=');
+                kv.extend_from_slice(v.as_bytes());
+                kv
+            })
+            .collect()
+    };
+    let process_args = runtime::ProcessArgs {
+        execfn: paths[0].as_bytes().to_vec(),
+        argv,
+        envp,
+    };
+
+    let ld_library_path = std::env::var_os("LD_LIBRARY_PATH");
+    let loader_path = std::env::current_exe().unwrap_or_default();
+    let config = search_path::SearchConfig::new(ld_library_path.as_deref(), &default_paths, &loader_path);
+    if let Err(e) = run_program(paths, &config, &process_args, strong_before_weak, w_xor_x, aslr, print_debug) {
+        eprintln!("veriload: {}", render_loader_error(&e));
+        std::process::exit(1);
+    }
 }
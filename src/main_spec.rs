@@ -5,6 +5,7 @@ use crate::parse_spec::*;
 use crate::relocate_apply_spec::*;
 use crate::relocate_plan_spec::*;
 use crate::resolve_spec::*;
+use crate::tls_layout_spec::*;
 use crate::types::*;
 use vstd::prelude::*;
 
@@ -16,14 +17,17 @@ pub open spec fn plan_ok_spec(input: LoaderInput, out: LoaderOutput) -> bool {
         discovered: DiscoveryResult,
         resolved: ResolutionResult,
         mmap_plans: Seq<MmapPlan>,
+        tls: TlsLayout,
         plan_reloc: RelocatePlanOutput,
         reloc_applied: RelocateApplyOutput,
     | {
         &&& parse_stage_spec(input, parsed)
         &&& discover_stage_spec(parsed, discovered)
-        &&& resolve_stage_spec(parsed, discovered, resolved)
+        &&& resolve_stage_spec(parsed, discovered, input.strong_before_weak, resolved)
         &&& mmap_plan_stage_spec(parsed, discovered, mmap_plans)
-        &&& plan_relocate_stage_spec(parsed, discovered, resolved, mmap_plans, plan_reloc)
+        &&& input.w_xor_x ==> mmap_plans_w_xor_x(mmap_plans)
+        &&& tls_layout_stage_spec(parsed, discovered, tls)
+        &&& plan_relocate_stage_spec(parsed, discovered, resolved, mmap_plans, tls, plan_reloc)
         &&& relocate_apply_stage_spec(plan_reloc, reloc_applied)
         &&& final_stage_spec(reloc_applied, out)
     }
@@ -1,7 +1,9 @@
 use crate::consts::*;
 use crate::mmap_plan_spec::*;
 use crate::relocate_plan_spec::*;
+use crate::tls_layout_spec::*;
 use crate::types::*;
+use vstd::arithmetic::div_mod::*;
 use vstd::prelude::*;
 
 verus! {
@@ -67,28 +69,506 @@ fn rounded_seg_len_exec(vaddr: u64, memsz: u64) -> (n: usize)
     }
 }
 
-fn dyn_base_for_pos_exec(pos: usize) -> (r: u64)
+proof fn lemma_mod_trans(a: int, b: int, c: int)
+    requires
+        b > 0,
+        c > 0,
+        a % b == 0,
+        b % c == 0,
     ensures
-        r == dyn_base_for_pos(pos as int),
+        a % c == 0,
 {
-    let mul = (pos as i128).checked_mul(DYN_BASE_STRIDE as i128);
-    if mul.is_none() {
+    lemma_fundamental_div_mod(a, b);
+    lemma_fundamental_div_mod(b, c);
+    let k = a / b;
+    let j = b / c;
+    assert(a == (j * k) * c) by (nonlinear_arith)
+        requires
+            a == b * k,
+            b == c * j,
+    ;
+    lemma_mod_multiples_basic(j * k, c);
+}
+
+proof fn lemma_sum_diff_mod(a: int, b: int, m: int)
+    requires
+        m > 0,
+        a % m == 0,
+        b % m == 0,
+    ensures
+        (a + b) % m == 0,
+        (a - b) % m == 0,
+{
+    lemma_fundamental_div_mod(a, m);
+    lemma_fundamental_div_mod(b, m);
+    let qa = a / m;
+    let qb = b / m;
+    assert(a + b == (qa + qb) * m && a - b == (qa - qb) * m) by (nonlinear_arith)
+        requires
+            a == m * qa,
+            b == m * qb,
+    ;
+    lemma_mod_multiples_basic(qa + qb, m);
+    lemma_mod_multiples_basic(qa - qb, m);
+}
+
+proof fn lemma_page_floor_aligned(addr: u64)
+    ensures
+        page_floor_u64(addr) % PAGE_SIZE == 0,
+{
+    lemma_fundamental_div_mod(addr as int, PAGE_SIZE as int);
+    let q = addr as int / PAGE_SIZE as int;
+    assert(page_floor_u64(addr) as int == q * PAGE_SIZE as int);
+    lemma_mod_multiples_basic(q, PAGE_SIZE as int);
+}
+
+proof fn lemma_align_up_aligned(v: u64, align: u64)
+    requires
+        align > 0,
+    ensures
+        align_up_or_zero(v, align) % align == 0,
+{
+    let rem = v % align;
+    if rem != 0 && (v as int) + (align as int) - (rem as int) <= u64::MAX as int {
+        let q = v as int / align as int;
+        lemma_fundamental_div_mod(v as int, align as int);
+        assert((v as int) + (align as int) - (rem as int) == (q + 1) * align as int) by (nonlinear_arith)
+            requires
+                v as int == align as int * q + rem as int,
+        ;
+        lemma_mod_multiples_basic(q + 1, align as int);
+    }
+}
+
+proof fn lemma_load_align_pages(phdrs: Seq<ProgramHeader>, i: nat)
+    ensures
+        load_align_from(phdrs, i) > 0,
+        load_align_from(phdrs, i) % PAGE_SIZE == 0,
+    decreases phdrs.len() - i,
+{
+    if i < phdrs.len() {
+        lemma_load_align_pages(phdrs, (i + 1) as nat);
+    }
+}
+
+proof fn lemma_page_multiple_le(a: int, b: int)
+    requires
+        a % (PAGE_SIZE as int) == 0,
+        b % (PAGE_SIZE as int) == 0,
+        a < b,
+    ensures
+        a + PAGE_SIZE <= b,
+{
+    lemma_fundamental_div_mod(a, PAGE_SIZE as int);
+    lemma_fundamental_div_mod(b, PAGE_SIZE as int);
+    let qa = a / PAGE_SIZE as int;
+    let qb = b / PAGE_SIZE as int;
+    assert(a == qa * PAGE_SIZE as int);
+    assert(b == qb * PAGE_SIZE as int);
+    assert(qa < qb);
+}
+
+proof fn lemma_rounded_seg_len_pages(vaddr: u64, memsz: u64)
+    ensures
+        rounded_seg_len(vaddr, memsz) as int % PAGE_SIZE as int == 0,
+{
+    let lo = page_floor_u64(vaddr);
+    let hi = page_ceil_u64(seg_end_or_zero(vaddr, memsz));
+    lemma_page_floor_aligned(vaddr);
+    assert(hi == align_up_or_zero(seg_end_or_zero(vaddr, memsz), PAGE_SIZE));
+    lemma_align_up_aligned(seg_end_or_zero(vaddr, memsz), PAGE_SIZE);
+    if hi >= lo && hi - lo <= usize::MAX as u64 {
+        lemma_sum_diff_mod(hi as int, lo as int, PAGE_SIZE as int);
+    }
+}
+
+proof fn lemma_plans_extend_push(old: Seq<MmapPlan>, mid: Seq<MmapPlan>, cand: MmapPlan)
+    requires
+        plans_extend(old, mid),
+    ensures
+        plans_extend(old, mid.push(cand)),
+{
+    assert forall|k: int| 0 <= k < old.len() implies mid.push(cand)[k] == old[k] by {
+        assert(mid.push(cand)[k] == mid[k]);
+    };
+}
+
+proof fn lemma_plans_push_keeps(old: Seq<MmapPlan>, cand: MmapPlan)
+    ensures
+        forall|a: int| addr_planned(old, a) ==> addr_planned(old.push(cand), a),
+        forall|a: int| plan_ends_at(old, a) ==> plan_ends_at(old.push(cand), a),
+        forall|a: int| plan_starts_at(old, a) ==> plan_starts_at(old.push(cand), a),
+        forall|a: int| page_plan_at(old, a) ==> page_plan_at(old.push(cand), a),
+{
+    let new = old.push(cand);
+    assert forall|a: int| addr_planned(old, a) implies addr_planned(new, a) by {
+        let i = choose|i: int| 0 <= i < old.len() && plan_covers(old[i], a);
+        assert(new[i] == old[i]);
+        assert(plan_covers(new[i], a));
+    };
+    assert forall|a: int| plan_ends_at(old, a) implies plan_ends_at(new, a) by {
+        let i = choose|i: int|
+            0 <= i < old.len() && plan_covers(old[i], a - PAGE_SIZE) && old[i].start as int + old[i].len as int == a;
+        assert(new[i] == old[i]);
+        assert(plan_covers(new[i], a - PAGE_SIZE) && new[i].start as int + new[i].len as int == a);
+    };
+    assert forall|a: int| plan_starts_at(old, a) implies plan_starts_at(new, a) by {
+        let j = choose|j: int| 0 <= j < old.len() && plan_covers(old[j], a) && old[j].start as int == a;
+        assert(new[j] == old[j]);
+        assert(plan_covers(new[j], a) && new[j].start as int == a);
+    };
+    assert forall|a: int| page_plan_at(old, a) implies page_plan_at(new, a) by {
+        let k = choose|k: int| 0 <= k < old.len() && old[k].start as int == a && old[k].len == PAGE_SIZE;
+        assert(new[k] == old[k]);
+        assert(new[k].start as int == a && new[k].len == PAGE_SIZE);
+    };
+}
+
+proof fn lemma_addr_planned_extend(old: Seq<MmapPlan>, new: Seq<MmapPlan>, addr: int)
+    requires
+        plans_extend(old, new),
+        addr_planned(old, addr),
+    ensures
+        addr_planned(new, addr),
+{
+    let i = choose|i: int| 0 <= i < old.len() && plan_covers(old[i], addr);
+    assert(new[i] == old[i]);
+    assert(plan_covers(new[i], addr));
+}
+
+proof fn lemma_seg_pages_planned_extend(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    old: Seq<MmapPlan>,
+    new: Seq<MmapPlan>,
+    obj_pos: int,
+    ph_idx: int,
+)
+    requires
+        plans_extend(old, new),
+        seg_pages_planned(parsed, order, old, obj_pos, ph_idx),
+    ensures
+        seg_pages_planned(parsed, order, new, obj_pos, ph_idx),
+{
+    assert forall|off: int| is_seg_page(parsed, order, obj_pos, ph_idx, off) implies addr_planned(
+        new,
+        seg_page_addr(parsed, order, obj_pos, ph_idx, off),
+    ) by {
+        lemma_addr_planned_extend(old, new, seg_page_addr(parsed, order, obj_pos, ph_idx, off));
+    };
+}
+
+proof fn lemma_relro_split_planned_extend(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    old: Seq<MmapPlan>,
+    new: Seq<MmapPlan>,
+    obj_pos: int,
+    ph_idx: int,
+)
+    requires
+        plans_extend(old, new),
+        relro_split_planned_at(parsed, order, old, obj_pos, ph_idx),
+    ensures
+        relro_split_planned_at(parsed, order, new, obj_pos, ph_idx),
+{
+    let obj = parsed[order[obj_pos] as int];
+    let split = relro_split_len(obj, obj.phdrs@[ph_idx]) as int;
+    let at = seg_page_addr(parsed, order, obj_pos, ph_idx, split);
+    if split > 0 && is_seg_page(parsed, order, obj_pos, ph_idx, split) {
+        let i = choose|i: int|
+            0 <= i < old.len() && plan_covers(old[i], at - PAGE_SIZE) && old[i].start as int + old[i].len as int
+                == at;
+        let j = choose|j: int| 0 <= j < old.len() && plan_covers(old[j], at) && old[j].start as int == at;
+        assert(new[i] == old[i]);
+        assert(new[j] == old[j]);
+        assert(plan_covers(new[i], at - PAGE_SIZE) && new[i].start as int + new[i].len as int == at);
+        assert(plan_covers(new[j], at) && new[j].start as int == at);
+    }
+}
+
+proof fn lemma_addr_planned_once(plans: Seq<MmapPlan>, addr: int)
+    requires
+        mmap_plans_non_overlapping(plans),
+        addr_planned(plans, addr),
+    ensures
+        addr_planned_once(plans, addr),
+{
+    let i = choose|i: int| 0 <= i < plans.len() && plan_covers(plans[i], addr);
+    assert forall|j: int| 0 <= j < plans.len() && plan_covers(plans[j], addr) implies j == i by {
+        if j != i {
+            assert(!plan_ranges_overlap(plans[i], plans[j]));
+        }
+    };
+}
+
+proof fn lemma_plans_meet_at(plans: Seq<MmapPlan>, at: int)
+    requires
+        mmap_plans_non_overlapping(plans),
+        plan_ends_at(plans, at),
+        plan_starts_at(plans, at),
+    ensures
+        plans_meet_at(plans, at),
+{
+    let i0 = choose|i: int|
+        0 <= i < plans.len() && plan_covers(plans[i], at - PAGE_SIZE) && plans[i].start as int + plans[i].len as int
+            == at;
+    let j0 = choose|j: int| 0 <= j < plans.len() && plan_covers(plans[j], at) && plans[j].start as int == at;
+    assert forall|i: int, j: int|
+        0 <= i < plans.len() && 0 <= j < plans.len() && plan_covers(plans[i], at - PAGE_SIZE) && plan_covers(
+            plans[j],
+            at,
+        ) implies plans[i].start as int + plans[i].len as int == at && plans[j].start as int == at by {
+        if i != i0 {
+            assert(!plan_ranges_overlap(plans[i], plans[i0]));
+        }
+        if j != j0 {
+            assert(!plan_ranges_overlap(plans[j], plans[j0]));
+        }
+    };
+}
+
+proof fn lemma_relro_split_adjacent(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    obj_pos: int,
+    ph_idx: int,
+)
+    requires
+        mmap_plans_non_overlapping(plans),
+        relro_split_planned_at(parsed, order, plans, obj_pos, ph_idx),
+    ensures
+        relro_split_adjacent_at(parsed, order, plans, obj_pos, ph_idx),
+{
+    let obj = parsed[order[obj_pos] as int];
+    let split = relro_split_len(obj, obj.phdrs@[ph_idx]) as int;
+    if split > 0 && is_seg_page(parsed, order, obj_pos, ph_idx, split) {
+        lemma_plans_meet_at(plans, seg_page_addr(parsed, order, obj_pos, ph_idx, split));
+    }
+}
+
+proof fn lemma_shared_page_planned_extend(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    old: Seq<MmapPlan>,
+    new: Seq<MmapPlan>,
+    obj_pos: int,
+    ha: int,
+    hb: int,
+)
+    requires
+        plans_extend(old, new),
+        shared_page_planned_at(parsed, order, old, obj_pos, ha, hb),
+    ensures
+        shared_page_planned_at(parsed, order, new, obj_pos, ha, hb),
+{
+    let pa = parsed[order[obj_pos] as int].phdrs@[ha];
+    let at = seg_page_addr(parsed, order, obj_pos, hb, 0);
+    if shares_page_with_next(parsed, order, obj_pos, ha, hb) {
+        let k = choose|k: int| 0 <= k < old.len() && old[k].start as int == at && old[k].len == PAGE_SIZE;
+        assert(new[k] == old[k]);
+        if rounded_seg_len(pa.p_vaddr, pa.p_memsz) > PAGE_SIZE {
+            let i = choose|i: int|
+                0 <= i < old.len() && plan_covers(old[i], at - PAGE_SIZE) && old[i].start as int + old[i].len as int
+                    == at;
+            assert(new[i] == old[i]);
+            assert(plan_covers(new[i], at - PAGE_SIZE) && new[i].start as int + new[i].len as int == at);
+        }
+    }
+}
+
+proof fn lemma_shared_page_bounded(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    obj_pos: int,
+    ha: int,
+    hb: int,
+)
+    requires
+        mmap_plans_non_overlapping(plans),
+        shared_page_planned_at(parsed, order, plans, obj_pos, ha, hb),
+    ensures
+        shared_page_bounded_at(parsed, order, plans, obj_pos, ha, hb),
+{
+    let pa = parsed[order[obj_pos] as int].phdrs@[ha];
+    let at = seg_page_addr(parsed, order, obj_pos, hb, 0);
+    if shares_page_with_next(parsed, order, obj_pos, ha, hb) && rounded_seg_len(pa.p_vaddr, pa.p_memsz) > PAGE_SIZE {
+        let k = choose|k: int| 0 <= k < plans.len() && plans[k].start as int == at && plans[k].len == PAGE_SIZE;
+        assert(plan_covers(plans[k], at));
+        assert(plan_starts_at(plans, at));
+        lemma_plans_meet_at(plans, at);
+    }
+}
+
+fn align_up_or_zero_exec(v: u64, align: u64) -> (r: u64)
+    ensures
+        r == align_up_or_zero(v, align),
+{
+    if align == 0 {
+        return 0;
+    }
+    let rem = v % align;
+    if rem == 0 {
+        v
+    } else if v <= u64::MAX - (align - rem) {
+        v + (align - rem)
+    } else {
         0
+    }
+}
+
+pub fn load_span_lo_exec(phdrs: &Vec<ProgramHeader>) -> (r: u64)
+    ensures
+        r == load_span_lo_from(phdrs@, 0),
+{
+    let mut acc: u64 = u64::MAX;
+    let mut i: usize = phdrs.len();
+    while i > 0
+        invariant
+            i <= phdrs@.len(),
+            acc == load_span_lo_from(phdrs@, i as nat),
+        decreases i,
+    {
+        i = i - 1;
+        let lo = page_floor_u64_exec(phdrs[i].p_vaddr);
+        if phdrs[i].p_type == PT_LOAD && lo < acc {
+            acc = lo;
+        }
+    }
+    acc
+}
+
+pub fn load_span_hi_exec(phdrs: &Vec<ProgramHeader>) -> (r: u64)
+    ensures
+        r == load_span_hi_from(phdrs@, 0),
+{
+    let mut acc: u64 = 0;
+    let mut i: usize = phdrs.len();
+    while i > 0
+        invariant
+            i <= phdrs@.len(),
+            acc == load_span_hi_from(phdrs@, i as nat),
+        decreases i,
+    {
+        i = i - 1;
+        let hi = page_ceil_u64_exec(seg_end_or_zero_exec(phdrs[i].p_vaddr, phdrs[i].p_memsz));
+        if phdrs[i].p_type == PT_LOAD && hi > acc {
+            acc = hi;
+        }
+    }
+    acc
+}
+
+pub fn load_align_exec(phdrs: &Vec<ProgramHeader>) -> (r: u64)
+    ensures
+        r == load_align_from(phdrs@, 0),
+{
+    let mut acc: u64 = PAGE_SIZE;
+    let mut i: usize = phdrs.len();
+    while i > 0
+        invariant
+            i <= phdrs@.len(),
+            acc == load_align_from(phdrs@, i as nat),
+        decreases i,
+    {
+        i = i - 1;
+        let ph = &phdrs[i];
+        if ph.p_type == PT_LOAD && ph.p_align % PAGE_SIZE == 0 && ph.p_align > acc {
+            acc = ph.p_align;
+        }
+    }
+    acc
+}
+
+fn dyn_base_at_exec(obj: &ParsedObject, free: u64) -> (r: u64)
+    ensures
+        r == dyn_base_at(*obj, free),
+        r % load_align_from(obj.phdrs@, 0) == 0,
+        r % PAGE_SIZE == 0,
+{
+    let lo = load_span_lo_exec(&obj.phdrs);
+    let align = load_align_exec(&obj.phdrs);
+    proof {
+        lemma_load_align_pages(obj.phdrs@, 0);
+    }
+    if lo <= free {
+        let base = align_up_or_zero_exec(free - lo, align);
+        proof {
+            lemma_align_up_aligned((free - lo) as u64, align);
+            lemma_mod_trans(base as int, align as int, PAGE_SIZE as int);
+        }
+        base
     } else {
-        let raw_opt = (DYN_BASE_START as i128).checked_add(mul.unwrap());
-        if raw_opt.is_none() {
-            0
-        } else {
-            let raw = raw_opt.unwrap();
-            if raw >= 0 && raw <= u64::MAX as i128 {
-                raw as u64
-            } else {
+        0
+    }
+}
+
+pub fn dyn_base_for_pos_exec(parsed: &Vec<ParsedObject>, order: &Vec<usize>, pos: usize) -> (r: u64)
+    requires
+        pos < order@.len(),
+        (order@[pos as int] as int) < parsed@.len(),
+    ensures
+        r == dyn_base_for_pos(parsed@, order@, pos as int),
+        r % load_align_from(parsed@[order@[pos as int] as int].phdrs@, 0) == 0,
+        r % PAGE_SIZE == 0,
+{
+    let mut free: u64 = DYN_BASE_START;
+    let mut p: usize = 0;
+    while p < pos
+        invariant
+            p <= pos,
+            pos < order@.len(),
+            free == dyn_next_free(parsed@, order@, p as nat),
+        decreases pos - p,
+    {
+        let obj_idx = order[p];
+        if obj_idx < parsed.len() && parsed[obj_idx].elf_type != ET_EXEC && parsed[obj_idx].load_base.is_none() {
+            let end = add_u64_or_zero_exec(
+                dyn_base_at_exec(&parsed[obj_idx], free),
+                load_span_hi_exec(&parsed[obj_idx].phdrs),
+            );
+            if end > free {
+                free = end;
+            }
+        }
+        p = p + 1;
+    }
+    dyn_base_at_exec(&parsed[order[pos]], free)
+}
+
+fn object_base_from_exec(parsed: &Vec<ParsedObject>, order: &Vec<usize>, obj_idx: usize, scan: usize) -> (r: u64)
+    ensures
+        r == object_base_from(parsed@, order@, obj_idx as int, scan as nat),
+    decreases if scan < order.len() { order.len() - scan } else { 0 },
+{
+    if scan >= order.len() {
+        0
+    } else {
+        let cur = order[scan];
+        if cur == obj_idx && cur < parsed.len() {
+            if parsed[cur].elf_type == ET_EXEC {
                 0
+            } else {
+                match parsed[cur].load_base {
+                    Some(base) => base,
+                    None => dyn_base_for_pos_exec(parsed, order, scan),
+                }
             }
+        } else {
+            object_base_from_exec(parsed, order, obj_idx, scan + 1)
         }
     }
 }
 
+pub fn object_base_exec(parsed: &Vec<ParsedObject>, order: &Vec<usize>, obj_idx: usize) -> (r: u64)
+    ensures
+        r == object_base(parsed@, order@, obj_idx as int),
+{
+    object_base_from_exec(parsed, order, obj_idx, 0)
+}
+
 fn prot_of_flags_exec(flags: u32) -> (p: ProtFlags)
     ensures
         p == prot_of_flags(flags),
@@ -100,123 +580,899 @@ fn prot_of_flags_exec(flags: u32) -> (p: ProtFlags)
     }
 }
 
-fn mem_len_exec(memsz: u64) -> (n: usize)
+fn segment_file_range_exec(obj: &ParsedObject, ph: &ProgramHeader, len: usize) -> (r: Option<(u64, usize)>)
+    requires
+        len as nat == rounded_seg_len(ph.p_vaddr, ph.p_memsz),
     ensures
-        n as nat == if memsz <= usize::MAX as u64 {
-            memsz as nat
-        } else {
-            0
+        match r {
+            Some((off, flen)) => {
+                &&& off == segment_file_offset(*ph)
+                &&& flen as nat == segment_file_len(*ph)
+                &&& flen <= len
+                &&& flen > 0 ==> off as int + flen as int <= obj.file_bytes@.len()
+            },
+            None => true,
         },
 {
-    if memsz <= usize::MAX as u64 {
-        memsz as usize
+    let lead = ph.p_vaddr - page_floor_u64_exec(ph.p_vaddr);
+    let off = if ph.p_offset >= lead {
+        ph.p_offset - lead
     } else {
         0
+    };
+    if ph.p_filesz == 0 {
+        return Some((off, 0));
     }
+    if ph.p_offset < lead {
+        return None;
+    }
+    let want = lead as u128 + ph.p_filesz as u128;
+    let flen = if want < len as u128 {
+        want as usize
+    } else {
+        len
+    };
+    if off as u128 + flen as u128 > obj.file_bytes.len() as u128 {
+        return None;
+    }
+    Some((off, flen))
 }
 
-fn segment_bytes_exec(obj: &ParsedObject, ph: &ProgramHeader) -> (out: Vec<u8>)
+fn relro_split_len_exec(obj: &ParsedObject, ph: &ProgramHeader) -> (n: usize)
     ensures
-        out@.len() == if ph.p_memsz <= usize::MAX as u64 {
-            ph.p_memsz as nat
-        } else {
-            0
-        },
+        n as nat == relro_split_len(*obj, *ph),
 {
-    let len = mem_len_exec(ph.p_memsz);
-    let mut out: Vec<u8> = Vec::new();
-    let mut i: usize = 0;
-    while i < len
-        invariant
-            i <= len,
-            out@.len() == i,
-        decreases len - i,
-    {
-        let mut b: u8 = 0;
-        if (i as u64) < ph.p_filesz {
-            let off_u64_opt = ph.p_offset.checked_add(i as u64);
-            if off_u64_opt.is_some() {
-                let off_u64 = off_u64_opt.unwrap();
-                if off_u64 <= usize::MAX as u64 {
-                    let off = off_u64 as usize;
-                    if off < obj.file_bytes.len() {
-                        b = obj.file_bytes[off];
-                    }
-                }
+    match obj.relro {
+        Some(r) => {
+            let lo = page_floor_u64_exec(ph.p_vaddr);
+            let r_lo = page_floor_u64_exec(r.p_vaddr);
+            let r_hi = page_floor_u64_exec(seg_end_or_zero_exec(r.p_vaddr, r.p_memsz));
+            let len = rounded_seg_len_exec(ph.p_vaddr, ph.p_memsz);
+            if !obj.static_exec && ph.p_flags & PF_W == PF_W && r_lo == lo && r_hi > lo && r_hi - lo <= len as u64 {
+                (r_hi - lo) as usize
+            } else {
+                0
             }
         }
-        out.push(b);
-        i = i + 1;
+        None => 0,
     }
-    out
 }
 
-fn segment_mmap_bytes_exec(obj: &ParsedObject, ph: &ProgramHeader) -> (out: Vec<u8>)
+fn next_load_index_exec(phdrs: &Vec<ProgramHeader>, from: usize) -> (r: Option<usize>)
     ensures
-        out@.len() == rounded_seg_len(ph.p_vaddr, ph.p_memsz),
+        match r {
+            Some(j) => {
+                &&& from <= j < phdrs@.len()
+                &&& phdrs@[j as int].p_type == PT_LOAD
+                &&& forall|k: int| from <= k < j ==> phdrs@[k].p_type != PT_LOAD
+            },
+            None => forall|k: int| from <= k < phdrs@.len() ==> phdrs@[k].p_type != PT_LOAD,
+        },
 {
-    let lo = page_floor_u64_exec(ph.p_vaddr);
-    let off_u64 = ph.p_vaddr - lo;
-    let off = if off_u64 <= usize::MAX as u64 {
-        off_u64 as usize
-    } else {
-        0
-    };
-    let len = rounded_seg_len_exec(ph.p_vaddr, ph.p_memsz);
-    let seg = segment_bytes_exec(obj, ph);
-
-    let mut out: Vec<u8> = Vec::new();
-    let mut i: usize = 0;
-    while i < off && out.len() < len
+    let mut j = from;
+    while j < phdrs.len()
         invariant
-            i <= off,
-            out@.len() <= len,
-            out@.len() == i,
-        decreases off - i,
+            from <= j,
+            forall|k: int| from <= k < j ==> phdrs@[k].p_type != PT_LOAD,
+        decreases phdrs.len() - j,
     {
-        out.push(0u8);
-        i = i + 1;
+        if phdrs[j].p_type == PT_LOAD {
+            return Some(j);
+        }
+        j = j + 1;
     }
+    None
+}
 
-    i = 0;
-    while i < seg.len() && out.len() < len
-        invariant
-            i <= seg.len(),
-            out@.len() <= len,
-            out@.len() <= off + i,
-        decreases seg.len() - i,
-    {
-        out.push(seg[i]);
-        i = i + 1;
+// Bytes `[lo, hi)` of a segment's page-rounded region as one plan.
+fn segment_piece_exec(
+    obj: &ParsedObject,
+    obj_idx: usize,
+    ph: &ProgramHeader,
+    seg_start: u64,
+    seg_len: usize,
+    file_offset: u64,
+    file_len: usize,
+    lo: usize,
+    hi: usize,
+    prot: ProtFlags,
+) -> (plan: MmapPlan)
+    requires
+        seg_start % PAGE_SIZE == 0,
+        seg_start as int + seg_len as int <= u64::MAX as int,
+        lo <= hi <= seg_len,
+        (lo as int) % (PAGE_SIZE as int) == 0,
+        seg_len as nat == rounded_seg_len(ph.p_vaddr, ph.p_memsz),
+        file_offset == segment_file_offset(*ph),
+        file_len as nat == segment_file_len(*ph),
+        file_len <= seg_len,
+        file_len > 0 ==> file_offset as int + file_len as int <= obj.file_bytes@.len(),
+    ensures
+        plan.object_name@ == obj.input_name@,
+        plan.object_index == obj_idx,
+        plan.start as int == seg_start as int + lo as int,
+        plan.start % PAGE_SIZE == 0,
+        plan.len == hi - lo,
+        plan.prot == prot,
+        plan.file_len <= plan.len,
+        plan.overlay.is_none(),
+        plan.patches@.len() == 0,
+        plan_matches_segment(plan, *obj, *ph, seg_start),
+{
+    let (f_off, f_len) = if file_len > lo {
+        (file_offset + lo as u64, if file_len - lo < hi - lo {
+            file_len - lo
+        } else {
+            hi - lo
+        })
+    } else {
+        (file_offset, 0)
+    };
+    let plan = MmapPlan {
+        object_name: clone_u8_vec(&obj.input_name),
+        object_index: obj_idx,
+        start: seg_start + lo as u64,
+        len: hi - lo,
+        prot,
+        file_offset: f_off,
+        file_len: f_len,
+        overlay: None,
+        patches: Vec::new(),
+    };
+    proof {
+        assert forall|k: int| 0 <= k < plan.len implies plan_file_byte(plan, obj.file_bytes@, k) == segment_image(
+            *obj,
+            *ph,
+        )[plan.start - seg_start + k] by {
+            assert(plan.start - seg_start + k == lo + k);
+            assert(k < f_len <==> lo + k < file_len);
+        };
     }
+    plan
+}
 
-    while out.len() < len
-        invariant
-            out@.len() <= len,
-        decreases len - out.len(),
-    {
-        out.push(0u8);
+// The page holding the end of `pa` and the start of `pb`: `pa`'s last page,
+// with the part `pb` covers laid over it.
+fn shared_page_exec(
+    obj: &ParsedObject,
+    obj_idx: usize,
+    pa: &ProgramHeader,
+    pb: &ProgramHeader,
+    start: u64,
+    a_len: usize,
+    a_file_offset: u64,
+    a_file_len: usize,
+    b_file_offset: u64,
+    b_file_len: usize,
+    prot: ProtFlags,
+) -> (plan: MmapPlan)
+    requires
+        start % PAGE_SIZE == 0,
+        a_len as nat == rounded_seg_len(pa.p_vaddr, pa.p_memsz),
+        a_len >= PAGE_SIZE,
+        rounded_seg_len(pb.p_vaddr, pb.p_memsz) >= PAGE_SIZE,
+        a_file_offset == segment_file_offset(*pa),
+        a_file_len as nat == segment_file_len(*pa),
+        a_file_len <= a_len,
+        a_file_len > 0 ==> a_file_offset as int + a_file_len as int <= obj.file_bytes@.len(),
+        b_file_offset == segment_file_offset(*pb),
+        b_file_len as nat == segment_file_len(*pb),
+        b_file_len > 0 ==> b_file_offset as int + b_file_len as int <= obj.file_bytes@.len(),
+    ensures
+        plan.object_name@ == obj.input_name@,
+        plan.object_index == obj_idx,
+        plan.start == start,
+        plan.len == PAGE_SIZE,
+        plan.prot == prot,
+        plan.file_len <= plan.len,
+        plan.patches@.len() == 0,
+        forall|k: int|
+            0 <= k < plan.len ==> plan_initial_byte(plan, obj.file_bytes@, k) == shared_page_byte(*obj, *pa, *pb, k),
+{
+    let page = PAGE_SIZE as usize;
+    let skip = a_len - page;
+    let (file_offset, file_len) = if a_file_len > skip {
+        (a_file_offset + skip as u64, a_file_len - skip)
+    } else {
+        (a_file_offset, 0)
+    };
+    let lead = (pb.p_vaddr - page_floor_u64_exec(pb.p_vaddr)) as usize;
+    let end = if pb.p_memsz < PAGE_SIZE - lead as u64 {
+        lead + pb.p_memsz as usize
+    } else {
+        page
+    };
+    let (fill_offset, fill_len) = if b_file_len > lead {
+        (b_file_offset + lead as u64, if b_file_len - lead < end - lead {
+            b_file_len - lead
+        } else {
+            end - lead
+        })
+    } else {
+        (b_file_offset, 0)
+    };
+    let fill = PlanFill { offset: lead, len: end - lead, file_offset: fill_offset, file_len: fill_len };
+    let plan = MmapPlan {
+        object_name: clone_u8_vec(&obj.input_name),
+        object_index: obj_idx,
+        start,
+        len: page,
+        prot,
+        file_offset,
+        file_len,
+        overlay: Some(fill),
+        patches: Vec::new(),
+    };
+    proof {
+        assert forall|k: int| 0 <= k < plan.len implies plan_initial_byte(plan, obj.file_bytes@, k) == shared_page_byte(
+            *obj,
+            *pa,
+            *pb,
+            k,
+        ) by {
+            if lead <= k < lead + pb.p_memsz {
+                assert(k < end);
+                assert(k - lead < fill_len <==> k < b_file_len);
+                assert(plan_initial_byte(plan, obj.file_bytes@, k) == plan_fill_byte(fill, obj.file_bytes@, k));
+                assert(shared_page_byte(*obj, *pa, *pb, k) == segment_byte_at(*obj, *pb, k));
+            } else {
+                assert(!(lead <= k < end));
+                assert(k < file_len <==> skip + k < a_file_len);
+                assert(plan_initial_byte(plan, obj.file_bytes@, k) == plan_file_byte(plan, obj.file_bytes@, k));
+                assert(shared_page_byte(*obj, *pa, *pb, k) == segment_byte_at(*obj, *pa, skip + k));
+            }
+        };
     }
-    out
+    plan
 }
 
 fn plan_ranges_overlap_values_exec(a: &MmapPlan, b_start: u64, b_len: usize) -> (r: bool)
     ensures
-        r == ranges_overlap_values(a.start, a.bytes@.len(), b_start, b_len as nat),
+        r == ranges_overlap_values(a.start, a.len as nat, b_start, b_len as nat),
 {
     let a_lo = a.start as u128;
-    let a_hi = a_lo + a.bytes.len() as u128;
+    let a_hi = a_lo + a.len as u128;
     let b_lo = b_start as u128;
     let b_hi = b_lo + b_len as u128;
     a_lo < b_hi && b_lo < a_hi
 }
 
+fn push_plan(
+    parsed: &Vec<ParsedObject>,
+    discovered: &DiscoveryResult,
+    mmap_plans: &mut Vec<MmapPlan>,
+    w_xor_x: bool,
+    cand: MmapPlan,
+) -> (out: Result<(), LoaderError>)
+    requires
+        mmap_plan_sound(parsed@, discovered.order@, cand),
+        forall|k: int|
+            0 <= k < old(mmap_plans)@.len() ==> mmap_plan_sound(parsed@, discovered.order@, old(mmap_plans)@[k]),
+        mmap_plans_non_overlapping(old(mmap_plans)@),
+        cand.patches@.len() == 0,
+        forall|k: int| 0 <= k < old(mmap_plans)@.len() ==> old(mmap_plans)@[k].patches@.len() == 0,
+        w_xor_x ==> mmap_plans_w_xor_x(old(mmap_plans)@),
+    ensures
+        out.is_ok() ==> forall|k: int|
+            0 <= k < mmap_plans@.len() ==> mmap_plan_sound(parsed@, discovered.order@, mmap_plans@[k]),
+        out.is_ok() ==> mmap_plans_non_overlapping(mmap_plans@),
+        out.is_ok() ==> forall|k: int| 0 <= k < mmap_plans@.len() ==> mmap_plans@[k].patches@.len() == 0,
+        out.is_ok() && w_xor_x ==> mmap_plans_w_xor_x(mmap_plans@),
+        out.is_ok() ==> mmap_plans@ == old(mmap_plans)@.push(cand),
+{
+    if w_xor_x && cand.prot.write && cand.prot.execute {
+        return Err(loader_error(
+            LoaderStage::MmapPlan,
+            &cand.object_name,
+            LoaderErrorReason::WritableExecutable { start: cand.start, len: cand.len as u64 },
+        ));
+    }
+    let cand_start = cand.start;
+    let cand_len = cand.len;
+    let mut collides: bool = false;
+    let mut ci: usize = 0;
+    while ci < mmap_plans.len()
+        invariant
+            ci <= mmap_plans.len(),
+            mmap_plans@ == old(mmap_plans)@,
+            !collides ==> forall|k: int|
+                0 <= k < ci ==> !ranges_overlap_values(
+                    mmap_plans@[k].start,
+                    mmap_plans@[k].len as nat,
+                    cand_start,
+                    cand_len as nat,
+                ),
+        decreases mmap_plans.len() - ci,
+    {
+        let hit = plan_ranges_overlap_values_exec(&mmap_plans[ci], cand_start, cand_len);
+        if hit {
+            collides = true;
+            ci = mmap_plans.len();
+        } else {
+            ci = ci + 1;
+        }
+    }
+    if collides {
+        return Err(loader_error(
+            LoaderStage::MmapPlan,
+            &cand.object_name,
+            LoaderErrorReason::SegmentOverlap { start: cand_start, len: cand_len as u64 },
+        ));
+    }
+    let ghost old_plans = mmap_plans@;
+    mmap_plans.push(cand);
+    proof {
+        assert(ci == old_plans.len());
+        let old_last = old_plans.len() as int;
+        assert(mmap_plans@[old_last] == cand);
+        assert forall|k: int| 0 <= k < old_plans.len() implies !plan_ranges_overlap(
+            old_plans[k],
+            mmap_plans@[old_last],
+        ) by {
+            assert(!ranges_overlap_values(
+                old_plans[k].start,
+                old_plans[k].len as nat,
+                cand_start,
+                cand_len as nat,
+            ));
+        };
+        assert forall|k: int|
+            0 <= k < mmap_plans@.len() implies mmap_plan_sound(
+                parsed@,
+                discovered.order@,
+                mmap_plans@[k],
+            ) by {
+            if k < old_plans.len() {
+                assert(mmap_plans@[k] == old_plans[k]);
+            } else {
+                assert(mmap_plans@[k] == cand);
+            }
+        };
+        assert(mmap_plans_non_overlapping(mmap_plans@)) by {
+            assert forall|i0: int, j0: int|
+                0 <= i0 < mmap_plans@.len() && 0 <= j0 < mmap_plans@.len() && i0 != j0 implies !plan_ranges_overlap(
+                    mmap_plans@[i0],
+                    mmap_plans@[j0],
+                ) by {
+                if i0 < old_plans.len() && j0 < old_plans.len() {
+                    assert(!plan_ranges_overlap(old_plans[i0], old_plans[j0]));
+                } else if i0 < old_plans.len() {
+                    assert(j0 == old_plans.len());
+                    assert(!plan_ranges_overlap(old_plans[i0], mmap_plans@[j0]));
+                } else {
+                    assert(i0 == old_plans.len());
+                    assert(j0 < old_plans.len());
+                    assert(!plan_ranges_overlap(old_plans[j0], mmap_plans@[i0]));
+                }
+            };
+        };
+        assert forall|k: int| 0 <= k < mmap_plans@.len() implies mmap_plans@[k].patches@.len() == 0 by {
+            if k < old_plans.len() {
+                assert(mmap_plans@[k] == old_plans[k]);
+            }
+        };
+        if w_xor_x {
+            assert forall|k: int| 0 <= k < mmap_plans@.len() implies !(mmap_plans@[k].prot.write
+                && mmap_plans@[k].prot.execute) by {
+                if k < old_plans.len() {
+                    assert(mmap_plans@[k] == old_plans[k]);
+                }
+            };
+        }
+    }
+    Ok(())
+}
+
+// Plans PT_LOAD segment `pi` of the object at load position `oi`, leaving out
+// its first page when `shared_head` says the previous segment already planned
+// it. When the segment's last page is the first page of the next PT_LOAD, that
+// page is planned here as a shared page and the result is `true`.
+fn plan_segment(
+    parsed: &Vec<ParsedObject>,
+    discovered: &DiscoveryResult,
+    mmap_plans: &mut Vec<MmapPlan>,
+    w_xor_x: bool,
+    oi: usize,
+    pi: usize,
+    base: u64,
+    shared_head: bool,
+) -> (out: Result<bool, LoaderError>)
+    requires
+        oi < discovered.order.len(),
+        (discovered.order@[oi as int] as int) < parsed@.len(),
+        pi < parsed@[discovered.order@[oi as int] as int].phdrs@.len(),
+        parsed@[discovered.order@[oi as int] as int].phdrs@[pi as int].p_type == PT_LOAD,
+        base == base_for_load_pos(parsed@, discovered.order@, oi as int),
+        base % PAGE_SIZE == 0,
+        forall|k: int|
+            0 <= k < old(mmap_plans)@.len() ==> mmap_plan_sound(parsed@, discovered.order@, old(mmap_plans)@[k]),
+        mmap_plans_non_overlapping(old(mmap_plans)@),
+        forall|k: int| 0 <= k < old(mmap_plans)@.len() ==> old(mmap_plans)@[k].patches@.len() == 0,
+        w_xor_x ==> mmap_plans_w_xor_x(old(mmap_plans)@),
+        shared_head ==> page_plan_at(
+            old(mmap_plans)@,
+            seg_page_addr(parsed@, discovered.order@, oi as int, pi as int, 0),
+        ),
+    ensures
+        out.is_ok() ==> forall|k: int|
+            0 <= k < mmap_plans@.len() ==> mmap_plan_sound(parsed@, discovered.order@, mmap_plans@[k]),
+        out.is_ok() ==> mmap_plans_non_overlapping(mmap_plans@),
+        out.is_ok() ==> forall|k: int| 0 <= k < mmap_plans@.len() ==> mmap_plans@[k].patches@.len() == 0,
+        out.is_ok() && w_xor_x ==> mmap_plans_w_xor_x(mmap_plans@),
+        out.is_ok() ==> plans_extend(old(mmap_plans)@, mmap_plans@),
+        out.is_ok() ==> seg_pages_planned(parsed@, discovered.order@, mmap_plans@, oi as int, pi as int),
+        out.is_ok() ==> relro_split_planned_at(parsed@, discovered.order@, mmap_plans@, oi as int, pi as int),
+        out.is_ok() && out.unwrap() ==> forall|q: int|
+            pi < q < parsed@[discovered.order@[oi as int] as int].phdrs@.len() && parsed@[discovered.order@[oi as int] as int].phdrs@[q].p_type
+                == PT_LOAD && (forall|r: int|
+                pi < r < q ==> parsed@[discovered.order@[oi as int] as int].phdrs@[r].p_type != PT_LOAD)
+                ==> page_plan_at(mmap_plans@, seg_page_addr(parsed@, discovered.order@, oi as int, q, 0)),
+        out.is_ok() ==> forall|hb: int|
+            shared_page_planned_at(parsed@, discovered.order@, mmap_plans@, oi as int, pi as int, hb),
+{
+    let obj_idx = discovered.order[oi];
+    let obj = &parsed[obj_idx];
+    let ph = &obj.phdrs[pi];
+    let ghost o = obj_idx as int;
+    let ghost h = pi as int;
+    let seg_start = rounded_seg_start_exec(base, ph.p_vaddr);
+    let seg_len = rounded_seg_len_exec(ph.p_vaddr, ph.p_memsz);
+    let (file_offset, file_len) = match segment_file_range_exec(obj, ph, seg_len) {
+        Some(range) => range,
+        None => {
+            return Err(loader_error(LoaderStage::MmapPlan, &obj.input_name, LoaderErrorReason::BadProgramHeaders));
+        },
+    };
+    if seg_start > u64::MAX - seg_len as u64 {
+        return Err(loader_error(LoaderStage::MmapPlan, &obj.input_name, LoaderErrorReason::BadProgramHeaders));
+    }
+    let prot = prot_of_flags_exec(ph.p_flags);
+    let split = relro_split_len_exec(obj, ph);
+    let relro = ProtFlags { read: true, write: false, execute: false };
+    let page = PAGE_SIZE as usize;
+    proof {
+        lemma_page_floor_aligned(ph.p_vaddr);
+        if base as int + page_floor_u64(ph.p_vaddr) as int <= u64::MAX as int {
+            lemma_sum_diff_mod(base as int, page_floor_u64(ph.p_vaddr) as int, PAGE_SIZE as int);
+        }
+        assert(seg_start % PAGE_SIZE == 0);
+        assert(parsed@[o].phdrs@[h] == *ph);
+        assert(seg_start == rounded_seg_start(base_for_load_pos(parsed@, discovered.order@, oi as int), ph.p_vaddr));
+        assert(forall|off: int|
+            seg_page_addr(parsed@, discovered.order@, oi as int, h, off) == seg_start as int + off);
+        lemma_rounded_seg_len_pages(ph.p_vaddr, ph.p_memsz);
+        assert(seg_len as int % PAGE_SIZE as int == 0);
+        if split > 0 {
+            let lo = page_floor_u64(ph.p_vaddr);
+            let r = parsed@[o].relro.unwrap();
+            let r_hi = page_floor_u64(seg_end_or_zero(r.p_vaddr, r.p_memsz));
+            lemma_page_floor_aligned(r.p_vaddr);
+            lemma_page_floor_aligned(seg_end_or_zero(r.p_vaddr, r.p_memsz));
+            assert(split as int == r_hi as int - lo as int);
+            assert((split as int) % (PAGE_SIZE as int) == 0);
+        }
+    }
+
+    let mut shared: Option<MmapPlan> = None;
+    let ghost mut shared_next: int = 0;
+    match next_load_index_exec(&obj.phdrs, pi + 1) {
+        Some(pj) => {
+            let pb = &obj.phdrs[pj];
+            let b_start = rounded_seg_start_exec(base, pb.p_vaddr);
+            let b_len = rounded_seg_len_exec(pb.p_vaddr, pb.p_memsz);
+            let a_end = seg_start as u128 + seg_len as u128;
+            let apart = ph.p_vaddr <= u64::MAX - ph.p_memsz && ph.p_vaddr + ph.p_memsz <= pb.p_vaddr;
+            if seg_len >= page && b_len >= page && b_start as u128 + page as u128 == a_end && apart {
+                let (b_file_offset, b_file_len) = match segment_file_range_exec(obj, pb, b_len) {
+                    Some(range) => range,
+                    None => {
+                        return Err(loader_error(
+                            LoaderStage::MmapPlan,
+                            &obj.input_name,
+                            LoaderErrorReason::BadProgramHeaders,
+                        ));
+                    },
+                };
+                let a_prot = if seg_len - page < split {
+                    relro
+                } else {
+                    prot
+                };
+                let b_prot = if relro_split_len_exec(obj, pb) > 0 {
+                    relro
+                } else {
+                    prot_of_flags_exec(pb.p_flags)
+                };
+                let union = ProtFlags {
+                    read: a_prot.read || b_prot.read,
+                    write: a_prot.write || b_prot.write,
+                    execute: a_prot.execute || b_prot.execute,
+                };
+                let plan = shared_page_exec(
+                    obj,
+                    obj_idx,
+                    ph,
+                    pb,
+                    b_start,
+                    seg_len,
+                    file_offset,
+                    file_len,
+                    b_file_offset,
+                    b_file_len,
+                    union,
+                );
+                proof {
+                    let hb = pj as int;
+                    assert(parsed@[o].phdrs@[hb] == *pb);
+                    lemma_page_floor_aligned(pb.p_vaddr);
+                    if base as int + page_floor_u64(pb.p_vaddr) as int <= u64::MAX as int {
+                        lemma_sum_diff_mod(base as int, page_floor_u64(pb.p_vaddr) as int, PAGE_SIZE as int);
+                    }
+                    assert(b_start % PAGE_SIZE == 0);
+                    assert(mmap_plan_for_shared_page(parsed@, discovered.order@, oi as int, h, hb, plan));
+                }
+                shared = Some(plan);
+                proof {
+                    shared_next = pj as int;
+                    assert(pi < shared_next < parsed@[o].phdrs@.len());
+                    assert(parsed@[o].phdrs@[shared_next].p_type == PT_LOAD);
+                    assert(forall|r: int| pi < r < shared_next ==> parsed@[o].phdrs@[r].p_type != PT_LOAD);
+                    assert(shared.unwrap().start as int == seg_start as int + seg_len as int - PAGE_SIZE);
+                    assert(shared.unwrap().start as int == seg_page_addr(
+                        parsed@,
+                        discovered.order@,
+                        oi as int,
+                        shared_next,
+                        0,
+                    ));
+                }
+            }
+            proof {
+                let phdrs = parsed@[o].phdrs@;
+                assert forall|hb: int|
+                    shares_page_with_next(parsed@, discovered.order@, oi as int, h, hb) implies shared.is_some()
+                        && hb == shared_next by {
+                    if hb < pj {
+                        assert(phdrs[hb].p_type != PT_LOAD);
+                    } else if hb > pj {
+                        assert(phdrs[pj as int].p_type != PT_LOAD);
+                    } else {
+                        assert(phdrs[hb] == *pb);
+                        assert(b_start as int + PAGE_SIZE == seg_start as int + seg_len as int);
+                    }
+                };
+            }
+        },
+        None => {
+            proof {
+                assert forall|hb: int|
+                    shares_page_with_next(parsed@, discovered.order@, oi as int, h, hb) implies false by {
+                    assert(parsed@[o].phdrs@[hb].p_type != PT_LOAD);
+                };
+            }
+        },
+    }
+    let tail_shared = shared.is_some();
+    let lo: usize = if shared_head {
+        page
+    } else {
+        0
+    };
+    let hi: usize = if tail_shared {
+        seg_len - page
+    } else {
+        seg_len
+    };
+    let ghost a0 = seg_start as int;
+    let ghost r_end: int = if split < hi {
+        split as int
+    } else {
+        hi as int
+    };
+    let ghost t_start: int = if split > lo {
+        split as int
+    } else {
+        lo as int
+    };
+    proof {
+        assert(hi as int % PAGE_SIZE as int == 0) by {
+            if tail_shared {
+                lemma_sum_diff_mod(seg_len as int, PAGE_SIZE as int, PAGE_SIZE as int);
+            }
+        };
+        assert(shared_head ==> page_plan_at(mmap_plans@, a0));
+    }
+    // The RELRO piece `[0, split)` and the rest, each cut down to `[lo, hi)`.
+    if split == 0 {
+        if lo < hi {
+            let plan = segment_piece_exec(
+                obj,
+                obj_idx,
+                ph,
+                seg_start,
+                seg_len,
+                file_offset,
+                file_len,
+                lo,
+                hi,
+                prot,
+            );
+            proof {
+                assert(mmap_plan_piece(seg_start, seg_len as nat, split as nat, prot, plan));
+                assert(mmap_plan_for_segment(parsed@, discovered.order@, oi as int, h, plan));
+            }
+            let ghost before = mmap_plans@;
+            match push_plan(parsed, discovered, mmap_plans, w_xor_x, plan) {
+                Err(e) => return Err(e),
+                Ok(()) => {},
+            }
+            proof {
+                lemma_plans_extend_push(old(mmap_plans)@, before, plan);
+                lemma_plans_push_keeps(before, plan);
+                assert(mmap_plans@[before.len() as int] == plan);
+                assert forall|a: int| a0 + lo <= a < a0 + hi implies addr_planned(mmap_plans@, a) by {
+                    assert(plan_covers(mmap_plans@[before.len() as int], a));
+                };
+                lemma_page_multiple_le(lo as int, hi as int);
+                assert(plan_covers(mmap_plans@[before.len() as int], a0 + hi - PAGE_SIZE));
+                assert(plan_ends_at(mmap_plans@, a0 + hi));
+                assert(shared_head ==> page_plan_at(mmap_plans@, a0));
+            }
+        }
+    } else {
+        let r_hi = if split < hi {
+            split
+        } else {
+            hi
+        };
+        if lo < r_hi {
+            let plan = segment_piece_exec(
+                obj,
+                obj_idx,
+                ph,
+                seg_start,
+                seg_len,
+                file_offset,
+                file_len,
+                lo,
+                r_hi,
+                relro,
+            );
+            proof {
+                assert(mmap_plan_piece(seg_start, seg_len as nat, split as nat, prot, plan));
+                assert(mmap_plan_for_segment(parsed@, discovered.order@, oi as int, h, plan));
+            }
+            let ghost before = mmap_plans@;
+            match push_plan(parsed, discovered, mmap_plans, w_xor_x, plan) {
+                Err(e) => return Err(e),
+                Ok(()) => {},
+            }
+            proof {
+                lemma_plans_extend_push(old(mmap_plans)@, before, plan);
+                lemma_plans_push_keeps(before, plan);
+                let k = before.len() as int;
+                assert(mmap_plans@[k] == plan);
+                assert forall|a: int| a0 + lo <= a < a0 + r_hi implies addr_planned(mmap_plans@, a) by {
+                    assert(plan_covers(mmap_plans@[k], a));
+                };
+                lemma_page_multiple_le(lo as int, r_hi as int);
+                assert(plan_covers(mmap_plans@[k], a0 + r_hi - PAGE_SIZE));
+                assert(plan_ends_at(mmap_plans@, a0 + r_hi));
+                assert(shared_head ==> page_plan_at(mmap_plans@, a0));
+            }
+        }
+        proof {
+            assert(lo < r_end ==> forall|a: int| a0 + lo <= a < a0 + r_end ==> addr_planned(mmap_plans@, a));
+            assert(lo < r_end ==> plan_ends_at(mmap_plans@, a0 + r_end));
+        }
+        let t_lo = if split > lo {
+            split
+        } else {
+            lo
+        };
+        if t_lo < hi {
+            let plan = segment_piece_exec(
+                obj,
+                obj_idx,
+                ph,
+                seg_start,
+                seg_len,
+                file_offset,
+                file_len,
+                t_lo,
+                hi,
+                prot,
+            );
+            proof {
+                assert(mmap_plan_piece(seg_start, seg_len as nat, split as nat, prot, plan));
+                assert(mmap_plan_for_segment(parsed@, discovered.order@, oi as int, h, plan));
+            }
+            let ghost before = mmap_plans@;
+            match push_plan(parsed, discovered, mmap_plans, w_xor_x, plan) {
+                Err(e) => return Err(e),
+                Ok(()) => {},
+            }
+            proof {
+                lemma_plans_extend_push(old(mmap_plans)@, before, plan);
+                lemma_plans_push_keeps(before, plan);
+                let k = before.len() as int;
+                assert(mmap_plans@[k] == plan);
+                if lo < r_end {
+                    assert forall|a: int| a0 + lo <= a < a0 + r_end implies addr_planned(mmap_plans@, a) by {
+                        assert(addr_planned(before, a));
+                    };
+                    assert(plan_ends_at(before, a0 + r_end));
+                }
+                assert forall|a: int| a0 + t_lo <= a < a0 + hi implies addr_planned(mmap_plans@, a) by {
+                    assert(plan_covers(mmap_plans@[k], a));
+                };
+                assert(plan_covers(mmap_plans@[k], a0 + t_lo));
+                assert(plan_starts_at(mmap_plans@, a0 + t_lo));
+                lemma_page_multiple_le(t_lo as int, hi as int);
+                assert(plan_covers(mmap_plans@[k], a0 + hi - PAGE_SIZE));
+                assert(plan_ends_at(mmap_plans@, a0 + hi));
+                assert(shared_head ==> page_plan_at(mmap_plans@, a0));
+            }
+        }
+        proof {
+            assert(lo < r_end ==> plan_ends_at(mmap_plans@, a0 + r_end));
+            assert(t_start < hi ==> plan_starts_at(mmap_plans@, a0 + t_start));
+            assert(t_start < hi ==> plan_ends_at(mmap_plans@, a0 + hi));
+            assert(lo < hi ==> plan_ends_at(mmap_plans@, a0 + hi)) by {
+                if lo < hi && t_start >= hi {
+                    assert(r_end == hi);
+                }
+            };
+            assert forall|a: int| a0 + lo <= a < a0 + hi implies addr_planned(mmap_plans@, a) by {
+                if a < a0 + split {
+                    assert(a0 + lo <= a < a0 + r_end);
+                } else {
+                    assert(a0 + t_start <= a < a0 + hi);
+                }
+            };
+        }
+    }
+    let ghost pieces = mmap_plans@;
+    proof {
+        assert(plans_extend(old(mmap_plans)@, pieces));
+        assert(forall|a: int| a0 + lo <= a < a0 + hi ==> addr_planned(pieces, a));
+        assert(shared_head ==> page_plan_at(pieces, a0));
+        assert(split > 0 && lo < r_end ==> plan_ends_at(pieces, a0 + r_end));
+        assert(split > 0 && t_start < hi ==> plan_starts_at(pieces, a0 + t_start));
+        assert(lo < hi ==> plan_ends_at(pieces, a0 + hi));
+    }
+    match shared {
+        Some(plan) => {
+            match push_plan(parsed, discovered, mmap_plans, w_xor_x, plan) {
+                Err(e) => return Err(e),
+                Ok(()) => {},
+            }
+            proof {
+                lemma_plans_extend_push(old(mmap_plans)@, pieces, plan);
+                lemma_plans_push_keeps(pieces, plan);
+                let k = pieces.len() as int;
+                assert(mmap_plans@[k] == plan);
+                assert forall|a: int| a0 + lo <= a < a0 + hi implies addr_planned(mmap_plans@, a) by {
+                    assert(addr_planned(pieces, a));
+                };
+                if shared_head {
+                    assert(page_plan_at(pieces, a0));
+                }
+                if split > 0 && lo < r_end {
+                    assert(plan_ends_at(pieces, a0 + r_end));
+                }
+                if split > 0 && t_start < hi {
+                    assert(plan_starts_at(pieces, a0 + t_start));
+                }
+                if lo < hi {
+                    assert(plan_ends_at(pieces, a0 + hi));
+                }
+                assert(lo < hi ==> plan_ends_at(mmap_plans@, a0 + hi));
+                assert(shared_head ==> page_plan_at(mmap_plans@, a0));
+                assert(split > 0 && lo < r_end ==> plan_ends_at(mmap_plans@, a0 + r_end));
+                assert(split > 0 && t_start < hi ==> plan_starts_at(mmap_plans@, a0 + t_start));
+                assert forall|a: int| a0 + hi <= a < a0 + seg_len implies addr_planned(mmap_plans@, a) by {
+                    assert(plan_covers(mmap_plans@[k], a));
+                };
+                assert(plan_covers(mmap_plans@[k], a0 + hi));
+                assert(plan_starts_at(mmap_plans@, a0 + hi));
+                assert(page_plan_at(mmap_plans@, seg_page_addr(parsed@, discovered.order@, oi as int, shared_next, 0)));
+            }
+        },
+        None => {},
+    }
+    proof {
+        let order = discovered.order@;
+        let cur = mmap_plans@;
+        assert(forall|a: int| a0 + lo <= a < a0 + hi ==> addr_planned(cur, a));
+        assert(tail_shared ==> forall|a: int| a0 + hi <= a < a0 + seg_len ==> addr_planned(cur, a));
+        assert(shared_head ==> page_plan_at(cur, a0));
+        assert forall|off: int| is_seg_page(parsed@, order, oi as int, h, off) implies addr_planned(
+            cur,
+            seg_page_addr(parsed@, order, oi as int, h, off),
+        ) by {
+            assert(seg_page_addr(parsed@, order, oi as int, h, off) == a0 + off);
+            if off < lo {
+                if off > 0 {
+                    lemma_page_multiple_le(0, off);
+                }
+                let k = choose|k: int| 0 <= k < cur.len() && cur[k].start as int == a0 && cur[k].len == PAGE_SIZE;
+                assert(plan_covers(cur[k], a0 + off));
+            } else if off >= hi {
+                assert(tail_shared);
+            }
+        };
+        if split > 0 && is_seg_page(parsed@, order, oi as int, h, split as int) {
+            let at = seg_page_addr(parsed@, order, oi as int, h, split as int);
+            assert(at == a0 + split);
+            lemma_page_multiple_le(0, split as int);
+            lemma_page_multiple_le(split as int, seg_len as int);
+            assert(r_end == split);
+            if lo as int + PAGE_SIZE <= split {
+                assert(lo < r_end);
+            } else {
+                if split > PAGE_SIZE {
+                    lemma_page_multiple_le(PAGE_SIZE as int, split as int);
+                }
+                assert(shared_head && split == PAGE_SIZE);
+                let k = choose|k: int| 0 <= k < cur.len() && cur[k].start as int == a0 && cur[k].len == PAGE_SIZE;
+                assert(plan_covers(cur[k], at - PAGE_SIZE));
+            }
+            assert(plan_ends_at(cur, at));
+            if split < hi {
+                assert(t_start == split);
+            } else {
+                assert(tail_shared && hi == split);
+            }
+            assert(plan_starts_at(cur, at));
+        }
+        assert(relro_split_planned_at(parsed@, order, cur, oi as int, h));
+        assert forall|hb: int| shared_page_planned_at(parsed@, order, cur, oi as int, h, hb) by {
+            if shares_page_with_next(parsed@, order, oi as int, h, hb) {
+                assert(tail_shared && hb == shared_next);
+                let at = seg_page_addr(parsed@, order, oi as int, hb, 0);
+                assert(at == a0 + hi);
+                assert(page_plan_at(cur, at));
+                if seg_len > PAGE_SIZE {
+                    lemma_page_multiple_le(PAGE_SIZE as int, seg_len as int);
+                    if lo >= hi {
+                        assert(shared_head && hi == PAGE_SIZE);
+                        let k = choose|k: int|
+                            0 <= k < cur.len() && cur[k].start as int == a0 && cur[k].len == PAGE_SIZE;
+                        assert(plan_covers(cur[k], at - PAGE_SIZE));
+                    }
+                    assert(plan_ends_at(cur, at));
+                }
+            }
+        };
+        if tail_shared {
+            let phdrs = parsed@[o].phdrs@;
+            assert(page_plan_at(cur, seg_page_addr(parsed@, order, oi as int, shared_next, 0)));
+            assert forall|q: int|
+                pi < q < phdrs.len() && phdrs[q].p_type == PT_LOAD && (forall|r: int|
+                    pi < r < q ==> phdrs[r].p_type != PT_LOAD) implies page_plan_at(
+                cur,
+                seg_page_addr(parsed@, order, oi as int, q, 0),
+            ) by {
+                if q < shared_next {
+                    assert(phdrs[q].p_type != PT_LOAD);
+                } else if q > shared_next {
+                    assert(phdrs[shared_next].p_type != PT_LOAD);
+                }
+            };
+        }
+    }
+    Ok(tail_shared)
+}
+
 pub fn mmap_plan_stage(
     parsed: &Vec<ParsedObject>,
     discovered: &DiscoveryResult,
+    w_xor_x: bool,
 ) -> (out: Result<Vec<MmapPlan>, LoaderError>)
     ensures
         out.is_ok() ==> mmap_plan_stage_spec(parsed@, *discovered, out.unwrap()@),
+        out.is_ok() && w_xor_x ==> mmap_plans_w_xor_x(out.unwrap()@),
 {
     let mut mmap_plans: Vec<MmapPlan> = Vec::new();
     let mut oi: usize = 0;
@@ -226,6 +1482,15 @@ pub fn mmap_plan_stage(
             forall|k: int|
                 0 <= k < mmap_plans@.len() ==> mmap_plan_sound(parsed@, discovered.order@, mmap_plans@[k]),
             mmap_plans_non_overlapping(mmap_plans@),
+            forall|k: int| 0 <= k < mmap_plans@.len() ==> mmap_plans@[k].patches@.len() == 0,
+            w_xor_x ==> mmap_plans_w_xor_x(mmap_plans@),
+            forall|p: int| 0 <= p < oi ==> requested_base_ok_at(parsed@, discovered.order@, p),
+            forall|p: int| 0 <= p < oi ==> load_base_aligned_at(parsed@, discovered.order@, p),
+            forall|p: int, h: int| 0 <= p < oi ==> seg_pages_planned(parsed@, discovered.order@, mmap_plans@, p, h),
+            forall|p: int, h: int|
+                0 <= p < oi ==> relro_split_planned_at(parsed@, discovered.order@, mmap_plans@, p, h),
+            forall|p: int, ha: int, hb: int|
+                0 <= p < oi ==> shared_page_planned_at(parsed@, discovered.order@, mmap_plans@, p, ha, hb),
         decreases discovered.order.len() - oi,
     {
         let obj_idx = discovered.order[oi];
@@ -233,12 +1498,37 @@ pub fn mmap_plan_stage(
             let base = if parsed[obj_idx].elf_type == ET_EXEC {
                 0
             } else {
-                dyn_base_for_pos_exec(oi)
+                match parsed[obj_idx].load_base {
+                    Some(b) => {
+                        let align = load_align_exec(&parsed[obj_idx].phdrs);
+                        let hi = load_span_hi_exec(&parsed[obj_idx].phdrs);
+                        proof {
+                            lemma_load_align_pages(parsed@[obj_idx as int].phdrs@, 0);
+                        }
+                        if b % align != 0 || b > u64::MAX - hi {
+                            return Err(loader_error(
+                                LoaderStage::MmapPlan,
+                                &parsed[obj_idx].input_name,
+                                LoaderErrorReason::BadLoadBase { base: b },
+                            ));
+                        }
+                        proof {
+                            lemma_mod_trans(b as int, align as int, PAGE_SIZE as int);
+                            assert(load_base_ok(parsed@[obj_idx as int], b));
+                        }
+                        b
+                    },
+                    None => dyn_base_for_pos_exec(parsed, &discovered.order, oi),
+                }
             };
             proof {
                 assert(base == base_for_load_pos(parsed@, discovered.order@, oi as int));
+                assert(base % PAGE_SIZE == 0);
+                assert(requested_base_ok_at(parsed@, discovered.order@, oi as int));
+                assert(load_base_aligned_at(parsed@, discovered.order@, oi as int));
             }
             let mut pi: usize = 0;
+            let mut shared_head = false;
             while pi < parsed[obj_idx].phdrs.len()
                 invariant
                     pi <= parsed@[obj_idx as int].phdrs@.len(),
@@ -246,138 +1536,144 @@ pub fn mmap_plan_stage(
                     obj_idx == discovered.order@[oi as int],
                     obj_idx < parsed.len(),
                     base == base_for_load_pos(parsed@, discovered.order@, oi as int),
+                    base % PAGE_SIZE == 0,
+                    forall|p: int| 0 <= p <= oi ==> requested_base_ok_at(parsed@, discovered.order@, p),
+                    forall|p: int| 0 <= p <= oi ==> load_base_aligned_at(parsed@, discovered.order@, p),
                     forall|k: int|
                         0 <= k < mmap_plans@.len() ==> mmap_plan_sound(parsed@, discovered.order@, mmap_plans@[k]),
                     mmap_plans_non_overlapping(mmap_plans@),
+                    forall|k: int| 0 <= k < mmap_plans@.len() ==> mmap_plans@[k].patches@.len() == 0,
+                    w_xor_x ==> mmap_plans_w_xor_x(mmap_plans@),
+                    forall|p: int, h: int|
+                        0 <= p < oi ==> seg_pages_planned(parsed@, discovered.order@, mmap_plans@, p, h),
+                    forall|p: int, h: int|
+                        0 <= p < oi ==> relro_split_planned_at(parsed@, discovered.order@, mmap_plans@, p, h),
+                    forall|h: int|
+                        0 <= h < pi ==> seg_pages_planned(parsed@, discovered.order@, mmap_plans@, oi as int, h),
+                    forall|h: int|
+                        0 <= h < pi ==> relro_split_planned_at(parsed@, discovered.order@, mmap_plans@, oi as int, h),
+                    forall|p: int, ha: int, hb: int|
+                        0 <= p < oi || (p == oi && 0 <= ha < pi) ==> shared_page_planned_at(
+                            parsed@,
+                            discovered.order@,
+                            mmap_plans@,
+                            p,
+                            ha,
+                            hb,
+                        ),
+                    shared_head ==> forall|q: int|
+                        pi <= q < parsed@[obj_idx as int].phdrs@.len() && parsed@[obj_idx as int].phdrs@[q].p_type
+                            == PT_LOAD && (forall|r: int|
+                            pi <= r < q ==> parsed@[obj_idx as int].phdrs@[r].p_type != PT_LOAD) ==> page_plan_at(
+                            mmap_plans@,
+                            seg_page_addr(parsed@, discovered.order@, oi as int, q, 0),
+                        ),
                 decreases parsed@[obj_idx as int].phdrs@.len() - pi,
             {
-                let ph = &parsed[obj_idx].phdrs[pi];
-                if ph.p_type == PT_LOAD {
-                    let cand_start = rounded_seg_start_exec(base, ph.p_vaddr);
-                    let bytes = segment_mmap_bytes_exec(&parsed[obj_idx], ph);
-                    let cand_len = bytes.len();
-                    let cand = MmapPlan {
-                        object_name: clone_u8_vec(&parsed[obj_idx].input_name),
-                        start: cand_start,
-                        bytes,
-                        prot: prot_of_flags_exec(ph.p_flags),
-                    };
-                    let mut collides: bool = false;
-                    let mut ci: usize = 0;
-                    while ci < mmap_plans.len()
-                        invariant
-                            ci <= mmap_plans.len(),
-                            !collides ==> forall|k: int|
-                                0 <= k < ci ==> !ranges_overlap_values(
-                                    mmap_plans@[k].start,
-                                    mmap_plans@[k].bytes@.len(),
-                                    cand_start,
-                                    cand_len as nat,
-                                ),
-                        decreases mmap_plans.len() - ci,
-                    {
-                        let hit = plan_ranges_overlap_values_exec(&mmap_plans[ci], cand_start, cand_len);
-                        if hit {
-                            collides = true;
-                            ci = mmap_plans.len();
-                        } else {
-                            ci = ci + 1;
+                if parsed[obj_idx].phdrs[pi].p_type == PT_LOAD {
+                    let ghost before = mmap_plans@;
+                    proof {
+                        if shared_head {
+                            assert(page_plan_at(
+                                mmap_plans@,
+                                seg_page_addr(parsed@, discovered.order@, oi as int, pi as int, 0),
+                            ));
                         }
                     }
-                    if !collides {
-                        let ghost old_plans = mmap_plans@;
-                        mmap_plans.push(cand);
-                        proof {
-                            assert(ci == old_plans.len());
-                            let old_last = old_plans.len() as int;
-                            assert(mmap_plans@[old_last].object_name@ == parsed@[obj_idx as int].input_name@);
-                            assert(mmap_plans@[old_last].prot == prot_of_flags(parsed@[obj_idx as int].phdrs@[pi as int].p_flags));
-                            assert(mmap_plans@[old_last].start == cand_start);
-                            assert(mmap_plans@[old_last].bytes@.len() == cand_len as nat);
-                            assert forall|k: int| 0 <= k < old_plans.len() implies !ranges_overlap_values(
-                                old_plans[k].start,
-                                old_plans[k].bytes@.len(),
-                                cand_start,
-                                cand_len as nat,
-                            ) by {
-                                assert(!ranges_overlap_values(
-                                    old_plans[k].start,
-                                    old_plans[k].bytes@.len(),
-                                    cand_start,
-                                    cand_len as nat,
-                                ));
-                            };
-                            assert forall|k: int| 0 <= k < old_plans.len() implies !plan_ranges_overlap(
-                                old_plans[k],
-                                mmap_plans@[old_last],
+                    shared_head = match plan_segment(
+                        parsed,
+                        discovered,
+                        &mut mmap_plans,
+                        w_xor_x,
+                        oi,
+                        pi,
+                        base,
+                        shared_head,
+                    ) {
+                        Err(e) => return Err(e),
+                        Ok(shared) => shared,
+                    };
+                    proof {
+                        assert forall|p: int, h: int| 0 <= p < oi || (p == oi && 0 <= h < pi) implies seg_pages_planned(
+                            parsed@,
+                            discovered.order@,
+                            mmap_plans@,
+                            p,
+                            h,
+                        ) && relro_split_planned_at(parsed@, discovered.order@, mmap_plans@, p, h) by {
+                            lemma_seg_pages_planned_extend(parsed@, discovered.order@, before, mmap_plans@, p, h);
+                            lemma_relro_split_planned_extend(parsed@, discovered.order@, before, mmap_plans@, p, h);
+                        };
+                        assert forall|p: int, ha: int, hb: int|
+                            0 <= p < oi || (p == oi && 0 <= ha < pi) implies shared_page_planned_at(
+                            parsed@,
+                            discovered.order@,
+                            mmap_plans@,
+                            p,
+                            ha,
+                            hb,
+                        ) by {
+                            lemma_shared_page_planned_extend(
+                                parsed@,
+                                discovered.order@,
+                                before,
+                                mmap_plans@,
+                                p,
+                                ha,
+                                hb,
+                            );
+                        };
+                    }
+                } else {
+                    proof {
+                        assert(!is_seg_page(parsed@, discovered.order@, oi as int, pi as int, 0));
+                        assert(seg_pages_planned(parsed@, discovered.order@, mmap_plans@, oi as int, pi as int));
+                        assert(relro_split_planned_at(parsed@, discovered.order@, mmap_plans@, oi as int, pi as int));
+                        assert(forall|hb: int|
+                            !shares_page_with_next(parsed@, discovered.order@, oi as int, pi as int, hb));
+                        if shared_head {
+                            assert forall|q: int|
+                                pi + 1 <= q < parsed@[obj_idx as int].phdrs@.len()
+                                    && parsed@[obj_idx as int].phdrs@[q].p_type == PT_LOAD && (forall|r: int|
+                                    pi + 1 <= r < q ==> parsed@[obj_idx as int].phdrs@[r].p_type != PT_LOAD)
+                                    implies page_plan_at(
+                                mmap_plans@,
+                                seg_page_addr(parsed@, discovered.order@, oi as int, q, 0),
                             ) by {
-                                assert(!ranges_overlap_values(
-                                    old_plans[k].start,
-                                    old_plans[k].bytes@.len(),
-                                    mmap_plans@[old_last].start,
-                                    mmap_plans@[old_last].bytes@.len(),
-                                ));
-                                assert(!plan_ranges_overlap(old_plans[k], mmap_plans@[old_last]));
-                            };
-                            assert forall|k: int|
-                                0 <= k < mmap_plans@.len() implies mmap_plan_sound(
-                                    parsed@,
-                                    discovered.order@,
-                                    mmap_plans@[k],
-                                ) by {
-                                if k < old_plans.len() {
-                                } else {
-                                    assert(k == old_plans.len());
-                                    assert(mmap_plan_sound(parsed@, discovered.order@, mmap_plans@[k])) by {
-                                        let p = oi as int;
-                                        let h = pi as int;
-                                        let obj = obj_idx as int;
-                                        assert(0 <= p < discovered.order@.len());
-                                        assert(discovered.order@[p] == obj_idx);
-                                        assert(0 <= obj < parsed@.len());
-                                        assert(obj == discovered.order@[p] as int);
-                                        assert(0 <= h < parsed@[obj].phdrs@.len());
-                                        assert(parsed@[obj].phdrs@[h].p_type == PT_LOAD);
-                                        assert(mmap_plans@[k].object_name@ == parsed@[obj].input_name@);
-                                        assert(mmap_plans@[k].prot == prot_of_flags(parsed@[obj].phdrs@[h].p_flags));
-                                        assert(mmap_plans@[k].start == rounded_seg_start(
-                                            base_for_load_pos(parsed@, discovered.order@, p),
-                                            parsed@[obj].phdrs@[h].p_vaddr,
-                                        ));
-                                        assert(mmap_plans@[k].bytes@.len() == rounded_seg_len(
-                                            parsed@[obj].phdrs@[h].p_vaddr,
-                                            parsed@[obj].phdrs@[h].p_memsz,
-                                        ));
-                                        assert(mmap_plans@[k].start % PAGE_SIZE == 0);
-                                        assert(mmap_plan_for_segment(parsed@, discovered.order@, p, h, mmap_plans@[k]));
-                                    };
-                                }
-                            };
-                            assert(mmap_plans_non_overlapping(mmap_plans@)) by {
-                                assert forall|i0: int, j0: int|
-                                    0 <= i0 < mmap_plans@.len() && 0 <= j0 < mmap_plans@.len() && i0 != j0 implies !plan_ranges_overlap(
-                                        mmap_plans@[i0],
-                                        mmap_plans@[j0],
-                                    ) by {
-                                    if i0 < old_plans.len() && j0 < old_plans.len() {
-                                        assert(!plan_ranges_overlap(old_plans[i0], old_plans[j0]));
-                                    } else if i0 < old_plans.len() {
-                                        assert(j0 == old_plans.len());
-                                        assert(!plan_ranges_overlap(old_plans[i0], mmap_plans@[j0]));
-                                    } else {
-                                        assert(i0 == old_plans.len());
-                                        assert(j0 < old_plans.len());
-                                        assert(!plan_ranges_overlap(old_plans[j0], mmap_plans@[i0]));
-                                    }
-                                };
+                                assert(forall|r: int|
+                                    pi <= r < q ==> parsed@[obj_idx as int].phdrs@[r].p_type != PT_LOAD);
                             };
                         }
                     }
                 }
                 pi = pi + 1;
             }
+            proof {
+                assert forall|h: int|
+                    seg_pages_planned(parsed@, discovered.order@, mmap_plans@, oi as int, h) && relro_split_planned_at(
+                        parsed@,
+                        discovered.order@,
+                        mmap_plans@,
+                        oi as int,
+                        h,
+                    ) by {
+                    if !(0 <= h < pi) {
+                        assert(forall|off: int| !is_seg_page(parsed@, discovered.order@, oi as int, h, off));
+                    }
+                };
+                assert forall|ha: int, hb: int|
+                    shared_page_planned_at(parsed@, discovered.order@, mmap_plans@, oi as int, ha, hb) by {
+                    if !(0 <= ha < pi) {
+                        assert(!shares_page_with_next(parsed@, discovered.order@, oi as int, ha, hb));
+                    }
+                };
+            }
         } else {
-            return Err(LoaderError {});
+            return Err(loader_error(
+                LoaderStage::MmapPlan,
+                &Vec::new(),
+                LoaderErrorReason::InvalidObjectIndex { index: obj_idx },
+            ));
         }
         oi = oi + 1;
     }
@@ -389,6 +1685,29 @@ pub fn mmap_plan_stage(
                 mmap_plans@[k],
             ));
         assert(mmap_plans_non_overlapping(mmap_plans@));
+        assert(forall|k: int| 0 <= k < mmap_plans@.len() ==> mmap_plans@[k].patches@.len() == 0);
+        assert(forall|p: int|
+            0 <= p < discovered.order@.len() ==> requested_base_ok_at(parsed@, discovered.order@, p));
+        assert(forall|p: int|
+            0 <= p < discovered.order@.len() ==> load_base_aligned_at(parsed@, discovered.order@, p));
+        assert forall|p: int, h: int, off: int| is_seg_page(parsed@, discovered.order@, p, h, off) implies addr_planned_once(
+            mmap_plans@,
+            seg_page_addr(parsed@, discovered.order@, p, h, off),
+        ) by {
+            assert(seg_pages_planned(parsed@, discovered.order@, mmap_plans@, p, h));
+            lemma_addr_planned_once(mmap_plans@, seg_page_addr(parsed@, discovered.order@, p, h, off));
+        };
+        assert forall|p: int, h: int| relro_split_adjacent_at(parsed@, discovered.order@, mmap_plans@, p, h) by {
+            if 0 <= p < discovered.order@.len() {
+                lemma_relro_split_adjacent(parsed@, discovered.order@, mmap_plans@, p, h);
+            }
+        };
+        assert forall|p: int, ha: int, hb: int|
+            shared_page_bounded_at(parsed@, discovered.order@, mmap_plans@, p, ha, hb) by {
+            if 0 <= p < discovered.order@.len() {
+                lemma_shared_page_bounded(parsed@, discovered.order@, mmap_plans@, p, ha, hb);
+            }
+        };
     }
     Ok(mmap_plans)
 }
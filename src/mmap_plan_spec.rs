@@ -53,27 +53,77 @@ pub open spec fn prot_of_flags(flags: u32) -> ProtFlags {
     }
 }
 
-pub open spec fn segment_byte_at(obj: ParsedObject, ph: ProgramHeader, i: int) -> u8 {
-    if 0 <= i < ph.p_filesz as int {
-        let off = (ph.p_offset as int) + i;
-        if 0 <= off < obj.file_bytes@.len() {
-            obj.file_bytes@[off]
-        } else {
-            0
+pub open spec fn relro_prot() -> ProtFlags {
+    ProtFlags { read: true, write: false, execute: false }
+}
+
+pub open spec fn prot_union(a: ProtFlags, b: ProtFlags) -> ProtFlags {
+    ProtFlags { read: a.read || b.read, write: a.write || b.write, execute: a.execute || b.execute }
+}
+
+// Length of the leading read-only piece of a writable PT_LOAD segment: the
+// pages that PT_GNU_RELRO covers entirely, when RELRO starts on the
+// segment's first page. Zero means the segment is planned as one region. A
+// static executable writes its RELRO data during startup and protects it
+// itself, so it is never split.
+pub open spec fn relro_split_len(obj: ParsedObject, ph: ProgramHeader) -> nat {
+    match obj.relro {
+        Some(r) => {
+            let lo = page_floor_u64(ph.p_vaddr);
+            let r_lo = page_floor_u64(r.p_vaddr);
+            let r_hi = page_floor_u64(seg_end_or_zero(r.p_vaddr, r.p_memsz));
+            if !obj.static_exec && ph.p_flags & PF_W == PF_W && r_lo == lo && r_hi > lo
+                && ((r_hi - lo) as nat) <= rounded_seg_len(ph.p_vaddr, ph.p_memsz)
+            {
+                (r_hi - lo) as nat
+            } else {
+                0
+            }
         }
+        None => 0,
+    }
+}
+
+// A segment's page-rounded region starts `p_vaddr % PAGE_SIZE` bytes before
+// `p_vaddr`, so its file bytes start as far before `p_offset` and run to
+// `p_filesz` bytes past it.
+pub open spec fn segment_file_offset(ph: ProgramHeader) -> u64 {
+    let lead = ph.p_vaddr - page_floor_u64(ph.p_vaddr);
+    if ph.p_offset >= lead {
+        (ph.p_offset - lead) as u64
     } else {
         0
     }
 }
 
-pub open spec fn segment_image(obj: ParsedObject, ph: ProgramHeader) -> Seq<u8> {
-    if ph.p_memsz <= usize::MAX as u64 {
-        Seq::new(ph.p_memsz as nat, |i: int| segment_byte_at(obj, ph, i))
+pub open spec fn segment_file_len(ph: ProgramHeader) -> nat {
+    let lead = ph.p_vaddr - page_floor_u64(ph.p_vaddr);
+    let len = rounded_seg_len(ph.p_vaddr, ph.p_memsz);
+    if ph.p_filesz == 0 {
+        0
+    } else if lead + ph.p_filesz < len {
+        (lead + ph.p_filesz) as nat
     } else {
-        Seq::empty()
+        len
     }
 }
 
+pub open spec fn segment_byte_at(obj: ParsedObject, ph: ProgramHeader, k: int) -> u8 {
+    let off = (segment_file_offset(ph) as int) + k;
+    if 0 <= k < segment_file_len(ph) && 0 <= off < obj.file_bytes@.len() {
+        obj.file_bytes@[off]
+    } else {
+        0
+    }
+}
+
+// Initial contents of a segment's page-rounded region: file bytes, then zeros
+// through the end of the last page. Plans describe it by file range and
+// length only, so a large `.bss` costs nothing until it is mapped.
+pub open spec fn segment_image(obj: ParsedObject, ph: ProgramHeader) -> Seq<u8> {
+    Seq::new(rounded_seg_len(ph.p_vaddr, ph.p_memsz), |k: int| segment_byte_at(obj, ph, k))
+}
+
 pub open spec fn mmap_plan_for_segment(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
@@ -88,14 +138,160 @@ pub open spec fn mmap_plan_for_segment(
     &&& 0 <= ph_idx < parsed[obj_idx].phdrs@.len()
     &&& ph.p_type == PT_LOAD
     &&& plan.object_name@ == parsed[obj_idx].input_name@
-    &&& plan.prot == prot_of_flags(ph.p_flags)
-    &&& plan.start == rounded_seg_start(base_for_load_pos(parsed, order, obj_pos), ph.p_vaddr)
-    &&& plan.bytes@.len() == rounded_seg_len(ph.p_vaddr, ph.p_memsz)
+    &&& plan.object_index == obj_idx
+    &&& plan.start % PAGE_SIZE == 0
+    &&& plan.file_len <= plan.len
+    &&& plan.overlay.is_none()
+    &&& plan_matches_segment(
+        plan,
+        parsed[obj_idx],
+        ph,
+        rounded_seg_start(base_for_load_pos(parsed, order, obj_pos), ph.p_vaddr),
+    )
+    &&& mmap_plan_piece(
+        rounded_seg_start(base_for_load_pos(parsed, order, obj_pos), ph.p_vaddr),
+        rounded_seg_len(ph.p_vaddr, ph.p_memsz),
+        relro_split_len(parsed[obj_idx], ph),
+        prot_of_flags(ph.p_flags),
+        plan,
+    )
+}
+
+// `plan` lies inside one piece of its segment's region: the RELRO piece
+// `[0, split)` or the rest. A first or last page the region shares with a
+// neighbouring segment is planned separately (`mmap_plan_for_shared_page`).
+pub open spec fn mmap_plan_piece(seg_start: u64, seg_len: nat, split: nat, prot: ProtFlags, plan: MmapPlan) -> bool {
+    let lo = plan.start as int - seg_start as int;
+    let hi = lo + plan.len as int;
+    if split == 0 {
+        &&& plan.prot == prot
+        &&& 0 <= lo
+        &&& hi <= seg_len
+    } else {
+        ||| {
+            &&& plan.prot == relro_prot()
+            &&& 0 <= lo
+            &&& hi <= split
+        }
+        ||| {
+            &&& plan.prot == prot
+            &&& split <= lo
+            &&& hi <= seg_len
+        }
+    }
+}
+
+// Protection of the region byte at offset `off` of a segment.
+pub open spec fn seg_page_prot(obj: ParsedObject, ph: ProgramHeader, off: nat) -> ProtFlags {
+    if off < relro_split_len(obj, ph) {
+        relro_prot()
+    } else {
+        prot_of_flags(ph.p_flags)
+    }
+}
+
+// Byte `k` of the page holding the end of segment `pa` and the start of
+// segment `pb`: `pb`'s bytes where `pb` has them, `pa`'s image elsewhere.
+pub open spec fn shared_page_byte(obj: ParsedObject, pa: ProgramHeader, pb: ProgramHeader, k: int) -> u8 {
+    let lead = pb.p_vaddr - page_floor_u64(pb.p_vaddr);
+    if lead <= k < lead + pb.p_memsz {
+        segment_image(obj, pb)[k]
+    } else {
+        segment_image(obj, pa)[rounded_seg_len(pa.p_vaddr, pa.p_memsz) - PAGE_SIZE + k]
+    }
+}
+
+// A page-rounded region of `pa` whose last page is the first page of a later
+// segment `pb` that starts past `pa`'s end. The page is planned once, with the
+// union of the protections both segments ask for there.
+pub open spec fn mmap_plan_for_shared_page(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    obj_pos: int,
+    ha: int,
+    hb: int,
+    plan: MmapPlan,
+) -> bool {
+    let obj_idx = order[obj_pos] as int;
+    let obj = parsed[obj_idx];
+    let pa = obj.phdrs@[ha];
+    let pb = obj.phdrs@[hb];
+    let base = base_for_load_pos(parsed, order, obj_pos);
+    let a_len = rounded_seg_len(pa.p_vaddr, pa.p_memsz);
+    &&& 0 <= obj_pos < order.len()
+    &&& 0 <= obj_idx < parsed.len()
+    &&& 0 <= ha < hb < obj.phdrs@.len()
+    &&& pa.p_type == PT_LOAD
+    &&& pb.p_type == PT_LOAD
+    &&& pa.p_vaddr as int + pa.p_memsz as int <= pb.p_vaddr as int
+    &&& a_len >= PAGE_SIZE
+    &&& rounded_seg_len(pb.p_vaddr, pb.p_memsz) >= PAGE_SIZE
+    &&& plan.object_name@ == obj.input_name@
+    &&& plan.object_index == obj_idx
     &&& plan.start % PAGE_SIZE == 0
+    &&& plan.start == rounded_seg_start(base, pb.p_vaddr)
+    &&& plan.start as int + PAGE_SIZE == rounded_seg_start(base, pa.p_vaddr) as int + a_len
+    &&& plan.len == PAGE_SIZE
+    &&& plan.file_len <= plan.len
+    &&& plan.prot == prot_union(
+        seg_page_prot(obj, pa, (a_len - PAGE_SIZE) as nat),
+        seg_page_prot(obj, pb, 0),
+    )
+    &&& forall|k: int|
+        0 <= k < plan.len ==> plan_initial_byte(plan, obj.file_bytes@, k) == shared_page_byte(obj, pa, pb, k)
+}
+
+// Initial contents of a region before its patches: file bytes, then zeros.
+pub open spec fn plan_file_byte(plan: MmapPlan, file: Seq<u8>, k: int) -> u8 {
+    let off = (plan.file_offset as int) + k;
+    if 0 <= k < plan.file_len && 0 <= off < file.len() {
+        file[off]
+    } else {
+        0
+    }
+}
+
+pub open spec fn plan_fill_byte(fill: PlanFill, file: Seq<u8>, k: int) -> u8 {
+    let j = k - fill.offset;
+    let off = (fill.file_offset as int) + j;
+    if 0 <= j < fill.file_len && 0 <= off < file.len() {
+        file[off]
+    } else {
+        0
+    }
+}
+
+// Initial contents with the overlay, if any, in place.
+pub open spec fn plan_initial_byte(plan: MmapPlan, file: Seq<u8>, k: int) -> u8 {
+    match plan.overlay {
+        Some(fill) => if fill.offset <= k < fill.offset + fill.len {
+            plan_fill_byte(fill, file, k)
+        } else {
+            plan_file_byte(plan, file, k)
+        },
+        None => plan_file_byte(plan, file, k),
+    }
+}
+
+// Before any patch, byte `k` of `plan` is the byte of its segment's image at
+// the same address.
+pub open spec fn plan_matches_segment(plan: MmapPlan, obj: ParsedObject, ph: ProgramHeader, seg_start: u64) -> bool {
+    forall|k: int|
+        0 <= k < plan.len ==> plan_file_byte(plan, obj.file_bytes@, k) == segment_image(obj, ph)[plan.start
+            - seg_start + k]
+}
+
+pub open spec fn plan_file(parsed: Seq<ParsedObject>, plan: MmapPlan) -> Seq<u8> {
+    if plan.object_index < parsed.len() {
+        parsed[plan.object_index as int].file_bytes@
+    } else {
+        Seq::empty()
+    }
 }
 
 pub open spec fn mmap_plan_sound(parsed: Seq<ParsedObject>, order: Seq<usize>, plan: MmapPlan) -> bool {
-    exists|obj_pos: int, ph_idx: int| mmap_plan_for_segment(parsed, order, obj_pos, ph_idx, plan)
+    ||| exists|obj_pos: int, ph_idx: int| mmap_plan_for_segment(parsed, order, obj_pos, ph_idx, plan)
+    ||| exists|obj_pos: int, ha: int, hb: int| mmap_plan_for_shared_page(parsed, order, obj_pos, ha, hb, plan)
 }
 
 pub open spec fn ranges_overlap_values(a_start: u64, a_len: nat, b_start: u64, b_len: nat) -> bool {
@@ -107,14 +303,18 @@ pub open spec fn ranges_overlap_values(a_start: u64, a_len: nat, b_start: u64, b
 }
 
 pub open spec fn plan_ranges_overlap(a: MmapPlan, b: MmapPlan) -> bool {
-    ranges_overlap_values(a.start, a.bytes@.len(), b.start, b.bytes@.len())
+    ranges_overlap_values(a.start, a.len as nat, b.start, b.len as nat)
 }
 
 pub open spec fn same_plan_layout(a: MmapPlan, b: MmapPlan) -> bool {
     &&& a.object_name@ == b.object_name@
+    &&& a.object_index == b.object_index
     &&& a.start == b.start
+    &&& a.len == b.len
     &&& a.prot == b.prot
-    &&& a.bytes@.len() == b.bytes@.len()
+    &&& a.file_offset == b.file_offset
+    &&& a.file_len == b.file_len
+    &&& a.overlay == b.overlay
 }
 
 pub open spec fn same_mmap_layout(old_plans: Seq<MmapPlan>, new_plans: Seq<MmapPlan>) -> bool {
@@ -122,6 +322,10 @@ pub open spec fn same_mmap_layout(old_plans: Seq<MmapPlan>, new_plans: Seq<MmapP
     &&& forall|i: int| 0 <= i < old_plans.len() ==> same_plan_layout(old_plans[i], new_plans[i])
 }
 
+pub open spec fn mmap_plans_w_xor_x(plans: Seq<MmapPlan>) -> bool {
+    forall|i: int| 0 <= i < plans.len() ==> !(plans[i].prot.write && plans[i].prot.execute)
+}
+
 pub open spec fn mmap_plans_non_overlapping(plans: Seq<MmapPlan>) -> bool {
     forall|i: int, j: int|
         0 <= i < plans.len() && 0 <= j < plans.len() && i != j ==> !plan_ranges_overlap(plans[i], plans[j])
@@ -160,6 +364,208 @@ pub open spec fn count_load_segments(parsed: Seq<ParsedObject>, order: Seq<usize
     count_load_segments_from(parsed, order, 0)
 }
 
+// A requested load base keeps the object's most strictly aligned segment
+// aligned and its whole load span inside the address space.
+pub open spec fn load_base_ok(obj: ParsedObject, base: u64) -> bool {
+    &&& base % load_align_from(obj.phdrs@, 0) == 0
+    &&& (base as int) + (load_span_hi_from(obj.phdrs@, 0) as int) <= u64::MAX as int
+}
+
+pub open spec fn requested_base_ok_at(parsed: Seq<ParsedObject>, order: Seq<usize>, pos: int) -> bool {
+    let obj_idx = order[pos] as int;
+    0 <= obj_idx < parsed.len() && parsed[obj_idx].elf_type != ET_EXEC ==> match parsed[obj_idx].load_base {
+        Some(base) => load_base_ok(parsed[obj_idx], base),
+        None => true,
+    }
+}
+
+// Every `ET_DYN` object, packed or placed, sits at a multiple of its largest
+// `p_align`.
+pub open spec fn load_base_aligned_at(parsed: Seq<ParsedObject>, order: Seq<usize>, pos: int) -> bool {
+    let obj_idx = order[pos] as int;
+    0 <= obj_idx < parsed.len() && parsed[obj_idx].elf_type != ET_EXEC ==> base_for_load_pos(parsed, order, pos)
+        % load_align_from(parsed[obj_idx].phdrs@, 0) == 0
+}
+
+pub open spec fn plan_covers(plan: MmapPlan, addr: int) -> bool {
+    plan.start as int <= addr < plan.start as int + plan.len as int
+}
+
+pub open spec fn addr_planned(plans: Seq<MmapPlan>, addr: int) -> bool {
+    exists|i: int| 0 <= i < plans.len() && plan_covers(plans[i], addr)
+}
+
+pub open spec fn addr_planned_once(plans: Seq<MmapPlan>, addr: int) -> bool {
+    exists|i: int|
+        0 <= i < plans.len() && plan_covers(plans[i], addr) && forall|j: int|
+            0 <= j < plans.len() && plan_covers(plans[j], addr) ==> j == i
+}
+
+// Offset `off` is the start of a page in the page-rounded region of PT_LOAD
+// `ph_idx` of the object at load position `obj_pos`.
+pub open spec fn is_seg_page(parsed: Seq<ParsedObject>, order: Seq<usize>, obj_pos: int, ph_idx: int, off: int) -> bool {
+    let obj_idx = order[obj_pos] as int;
+    let ph = parsed[obj_idx].phdrs@[ph_idx];
+    &&& 0 <= obj_pos < order.len()
+    &&& 0 <= obj_idx < parsed.len()
+    &&& 0 <= ph_idx < parsed[obj_idx].phdrs@.len()
+    &&& ph.p_type == PT_LOAD
+    &&& 0 <= off < rounded_seg_len(ph.p_vaddr, ph.p_memsz)
+    &&& off % (PAGE_SIZE as int) == 0
+}
+
+pub open spec fn seg_page_addr(parsed: Seq<ParsedObject>, order: Seq<usize>, obj_pos: int, ph_idx: int, off: int) -> int {
+    let ph = parsed[order[obj_pos] as int].phdrs@[ph_idx];
+    rounded_seg_start(base_for_load_pos(parsed, order, obj_pos), ph.p_vaddr) as int + off
+}
+
+pub open spec fn seg_pages_planned(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    obj_pos: int,
+    ph_idx: int,
+) -> bool {
+    forall|off: int|
+        is_seg_page(parsed, order, obj_pos, ph_idx, off) ==> addr_planned(
+            plans,
+            seg_page_addr(parsed, order, obj_pos, ph_idx, off),
+        )
+}
+
+// Every page of every loaded PT_LOAD segment is in exactly one plan.
+pub open spec fn mmap_plans_cover_segments(parsed: Seq<ParsedObject>, order: Seq<usize>, plans: Seq<MmapPlan>) -> bool {
+    forall|obj_pos: int, ph_idx: int, off: int|
+        is_seg_page(parsed, order, obj_pos, ph_idx, off) ==> addr_planned_once(
+            plans,
+            seg_page_addr(parsed, order, obj_pos, ph_idx, off),
+        )
+}
+
+// Some plan ends exactly at `addr`, covering the page before it.
+pub open spec fn plan_ends_at(plans: Seq<MmapPlan>, addr: int) -> bool {
+    exists|i: int|
+        0 <= i < plans.len() && plan_covers(plans[i], addr - PAGE_SIZE) && plans[i].start as int + plans[i].len as int
+            == addr
+}
+
+pub open spec fn plan_starts_at(plans: Seq<MmapPlan>, addr: int) -> bool {
+    exists|j: int| 0 <= j < plans.len() && plan_covers(plans[j], addr) && plans[j].start as int == addr
+}
+
+// A plan of exactly the page at `addr`.
+pub open spec fn page_plan_at(plans: Seq<MmapPlan>, addr: int) -> bool {
+    exists|k: int| 0 <= k < plans.len() && plans[k].start as int == addr && plans[k].len == PAGE_SIZE
+}
+
+// Whichever plans hold the page before `addr` and the page at it, the first
+// ends and the second starts at `addr`.
+pub open spec fn plans_meet_at(plans: Seq<MmapPlan>, addr: int) -> bool {
+    forall|i: int, j: int|
+        0 <= i < plans.len() && 0 <= j < plans.len() && plan_covers(plans[i], addr - PAGE_SIZE) && plan_covers(
+            plans[j],
+            addr,
+        ) ==> plans[i].start as int + plans[i].len as int == addr && plans[j].start as int == addr
+}
+
+// Where PT_GNU_RELRO splits a segment inside its region, the region is
+// planned as two pieces that meet at the split.
+pub open spec fn relro_split_adjacent_at(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    obj_pos: int,
+    ph_idx: int,
+) -> bool {
+    let obj = parsed[order[obj_pos] as int];
+    let split = relro_split_len(obj, obj.phdrs@[ph_idx]) as int;
+    let at = seg_page_addr(parsed, order, obj_pos, ph_idx, split);
+    split > 0 && is_seg_page(parsed, order, obj_pos, ph_idx, split) ==> plans_meet_at(plans, at)
+}
+
+pub open spec fn relro_split_planned_at(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    obj_pos: int,
+    ph_idx: int,
+) -> bool {
+    let obj = parsed[order[obj_pos] as int];
+    let split = relro_split_len(obj, obj.phdrs@[ph_idx]) as int;
+    let at = seg_page_addr(parsed, order, obj_pos, ph_idx, split);
+    split > 0 && is_seg_page(parsed, order, obj_pos, ph_idx, split) ==> plan_ends_at(plans, at) && plan_starts_at(
+        plans,
+        at,
+    )
+}
+
+// PT_LOAD `hb` is the next one after `ha`, starts past `ha`'s end, and its
+// first page is `ha`'s last: the page `mmap_plan_for_shared_page` plans.
+pub open spec fn shares_page_with_next(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    obj_pos: int,
+    ha: int,
+    hb: int,
+) -> bool {
+    let obj_idx = order[obj_pos] as int;
+    let phdrs = parsed[obj_idx].phdrs@;
+    let pa = phdrs[ha];
+    let pb = phdrs[hb];
+    let base = base_for_load_pos(parsed, order, obj_pos);
+    let a_len = rounded_seg_len(pa.p_vaddr, pa.p_memsz);
+    &&& 0 <= obj_pos < order.len()
+    &&& 0 <= obj_idx < parsed.len()
+    &&& 0 <= ha < hb < phdrs.len()
+    &&& pa.p_type == PT_LOAD
+    &&& pb.p_type == PT_LOAD
+    &&& forall|r: int| ha < r < hb ==> phdrs[r].p_type != PT_LOAD
+    &&& pa.p_vaddr as int + pa.p_memsz as int <= pb.p_vaddr as int
+    &&& a_len >= PAGE_SIZE
+    &&& rounded_seg_len(pb.p_vaddr, pb.p_memsz) >= PAGE_SIZE
+    &&& rounded_seg_start(base, pb.p_vaddr) as int + PAGE_SIZE == rounded_seg_start(base, pa.p_vaddr) as int + a_len
+}
+
+pub open spec fn shared_page_planned_at(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    obj_pos: int,
+    ha: int,
+    hb: int,
+) -> bool {
+    let pa = parsed[order[obj_pos] as int].phdrs@[ha];
+    let at = seg_page_addr(parsed, order, obj_pos, hb, 0);
+    shares_page_with_next(parsed, order, obj_pos, ha, hb) ==> {
+        &&& page_plan_at(plans, at)
+        &&& rounded_seg_len(pa.p_vaddr, pa.p_memsz) > PAGE_SIZE ==> plan_ends_at(plans, at)
+    }
+}
+
+// A page shared by two segments is planned by itself, at the later segment's
+// first page, and the plan below it ends exactly there.
+pub open spec fn shared_page_bounded_at(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    plans: Seq<MmapPlan>,
+    obj_pos: int,
+    ha: int,
+    hb: int,
+) -> bool {
+    let pa = parsed[order[obj_pos] as int].phdrs@[ha];
+    let at = seg_page_addr(parsed, order, obj_pos, hb, 0);
+    shares_page_with_next(parsed, order, obj_pos, ha, hb) ==> {
+        &&& page_plan_at(plans, at)
+        &&& rounded_seg_len(pa.p_vaddr, pa.p_memsz) > PAGE_SIZE ==> plans_meet_at(plans, at)
+    }
+}
+
+// `new` keeps every plan of `old` at the same index.
+pub open spec fn plans_extend(old: Seq<MmapPlan>, new: Seq<MmapPlan>) -> bool {
+    &&& old.len() <= new.len()
+    &&& forall|k: int| 0 <= k < old.len() ==> new[k] == old[k]
+}
+
 pub open spec fn mmap_plan_stage_spec(
     parsed: Seq<ParsedObject>,
     discovered: DiscoveryResult,
@@ -167,6 +573,13 @@ pub open spec fn mmap_plan_stage_spec(
 ) -> bool {
     &&& forall|i: int| 0 <= i < mmap_plans.len() ==> mmap_plan_sound(parsed, discovered.order@, mmap_plans[i])
     &&& mmap_plans_non_overlapping(mmap_plans)
+    &&& mmap_plans_cover_segments(parsed, discovered.order@, mmap_plans)
+    &&& forall|obj_pos: int, ph_idx: int| relro_split_adjacent_at(parsed, discovered.order@, mmap_plans, obj_pos, ph_idx)
+    &&& forall|obj_pos: int, ha: int, hb: int|
+        shared_page_bounded_at(parsed, discovered.order@, mmap_plans, obj_pos, ha, hb)
+    &&& forall|i: int| 0 <= i < mmap_plans.len() ==> mmap_plans[i].patches@.len() == 0
+    &&& forall|pos: int| 0 <= pos < discovered.order@.len() ==> requested_base_ok_at(parsed, discovered.order@, pos)
+    &&& forall|pos: int| 0 <= pos < discovered.order@.len() ==> load_base_aligned_at(parsed, discovered.order@, pos)
 }
 
 } // verus!
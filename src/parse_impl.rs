@@ -1,10 +1,15 @@
 use crate::consts::*;
 use crate::parse_spec::*;
 use crate::types::*;
+use vstd::arithmetic::div_mod::*;
 use vstd::prelude::*;
 
 verus! {
 
+fn parse_error(reason: LoaderErrorReason) -> LoaderError {
+    LoaderError { stage: LoaderStage::Parse, object_name: Vec::new(), reason }
+}
+
 fn ensure_range(len: usize, off: usize, size: usize) -> (r: Result<usize, LoaderError>)
     ensures
         r.is_ok() ==> off + size <= len,
@@ -13,14 +18,14 @@ fn ensure_range(len: usize, off: usize, size: usize) -> (r: Result<usize, Loader
     match end {
         Some(e) => {
             if e > len {
-                Err(LoaderError {})
+                Err(parse_error(LoaderErrorReason::Truncated))
             } else {
                 assert(off + size == e);
                 assert(off + size <= len);
                 Ok(e)
             }
         }
-        None => Err(LoaderError {}),
+        None => Err(parse_error(LoaderErrorReason::Truncated)),
     }
 }
 
@@ -31,7 +36,7 @@ fn u64_to_usize(v: u64) -> (r: Result<usize, LoaderError>)
     if v <= usize::MAX as u64 {
         Ok(v as usize)
     } else {
-        Err(LoaderError {})
+        Err(parse_error(LoaderErrorReason::Truncated))
     }
 }
 
@@ -52,14 +57,14 @@ fn read_u8(bytes: &Vec<u8>, off: usize) -> (r: Result<u8, LoaderError>)
     if off < bytes.len() {
         Ok(bytes[off])
     } else {
-        Err(LoaderError {})
+        Err(parse_error(LoaderErrorReason::Truncated))
     }
 }
 
 fn read_u16_le(bytes: &Vec<u8>, off: usize) -> (r: Result<u16, LoaderError>) {
     let range = ensure_range(bytes.len(), off, 2);
     if range.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     assert(off + 2 <= bytes.len());
     let x0 = bytes[off] as u16;
@@ -67,10 +72,14 @@ fn read_u16_le(bytes: &Vec<u8>, off: usize) -> (r: Result<u16, LoaderError>) {
     Ok(x0 | (x1 << 8))
 }
 
-fn read_u32_le(bytes: &Vec<u8>, off: usize) -> (r: Result<u32, LoaderError>) {
+fn read_u32_le(bytes: &Vec<u8>, off: usize) -> (r: Result<u32, LoaderError>)
+    ensures
+        r.is_ok() ==> off + 4 <= bytes@.len(),
+        r.is_ok() ==> r.unwrap() == u32_le(bytes@, off as int),
+{
     let range = ensure_range(bytes.len(), off, 4);
     if range.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     assert(off + 4 <= bytes.len());
     let x0 = bytes[off] as u32;
@@ -83,7 +92,7 @@ fn read_u32_le(bytes: &Vec<u8>, off: usize) -> (r: Result<u32, LoaderError>) {
 fn read_u64_le(bytes: &Vec<u8>, off: usize) -> (r: Result<u64, LoaderError>) {
     let range = ensure_range(bytes.len(), off, 8);
     if range.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     assert(off + 8 <= bytes.len());
     let x0 = bytes[off] as u64;
@@ -101,7 +110,7 @@ fn read_u64_le(bytes: &Vec<u8>, off: usize) -> (r: Result<u64, LoaderError>) {
 fn read_i64_le(bytes: &Vec<u8>, off: usize) -> (r: Result<i64, LoaderError>) {
     let x = read_u64_le(bytes, off);
     if x.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     Ok(x.unwrap() as i64)
 }
@@ -109,10 +118,13 @@ fn read_i64_le(bytes: &Vec<u8>, off: usize) -> (r: Result<i64, LoaderError>) {
 fn vaddr_to_file_offset(phdrs: &Vec<ProgramHeader>, vaddr: u64, size: u64) -> (r: Result<
     u64,
     LoaderError,
->) {
+>)
+    ensures
+        r.is_ok() ==> vaddr_in_file(phdrs@, vaddr, size, r.unwrap() as int),
+{
     let req_end = vaddr.checked_add(size);
     if req_end.is_none() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     let req_end = req_end.unwrap();
 
@@ -126,22 +138,24 @@ fn vaddr_to_file_offset(phdrs: &Vec<ProgramHeader>, vaddr: u64, size: u64) -> (r
         if ph.p_type == PT_LOAD {
             let seg_end = ph.p_vaddr.checked_add(ph.p_filesz);
             if seg_end.is_none() {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::Truncated));
             }
             let seg_end = seg_end.unwrap();
             if vaddr >= ph.p_vaddr && req_end <= seg_end {
                 let delta = vaddr - ph.p_vaddr;
                 let out = ph.p_offset.checked_add(delta);
                 if out.is_none() {
-                    return Err(LoaderError {});
+                    return Err(parse_error(LoaderErrorReason::Truncated));
                 }
+                assert(phdrs@[i as int].p_type == PT_LOAD);
+                assert(vaddr_in_file(phdrs@, vaddr, size, out.unwrap() as int));
                 return Ok(out.unwrap());
             }
         }
         i = i + 1;
     }
 
-    Err(LoaderError {})
+    Err(parse_error(LoaderErrorReason::UnmappedAddress { vaddr }))
 }
 
 #[derive(Debug)]
@@ -152,9 +166,19 @@ struct DynamicScan {
     strsz: Option<u64>,
     symtab: Option<u64>,
     syment: Option<u64>,
+    hash: Option<u64>,
+    gnu_hash: Option<u64>,
+    versym: Option<u64>,
+    verdef: Option<u64>,
+    verdefnum: Option<u64>,
+    verneed: Option<u64>,
+    verneednum: Option<u64>,
     rela: Option<u64>,
     relasz: Option<u64>,
     relaent: Option<u64>,
+    rel: Option<u64>,
+    relsz: Option<u64>,
+    relent: Option<u64>,
     jmprel: Option<u64>,
     pltrelsz: Option<u64>,
     pltrel: Option<u64>,
@@ -165,6 +189,11 @@ struct DynamicScan {
     init_arraysz: Option<u64>,
     fini_array: Option<u64>,
     fini_arraysz: Option<u64>,
+    preinit_array: Option<u64>,
+    preinit_arraysz: Option<u64>,
+    init: Option<u64>,
+    fini: Option<u64>,
+    flags_1: Option<u64>,
 }
 
 fn empty_dynamic_scan() -> DynamicScan {
@@ -175,9 +204,19 @@ fn empty_dynamic_scan() -> DynamicScan {
         strsz: None,
         symtab: None,
         syment: None,
+        hash: None,
+        gnu_hash: None,
+        versym: None,
+        verdef: None,
+        verdefnum: None,
+        verneed: None,
+        verneednum: None,
         rela: None,
         relasz: None,
         relaent: None,
+        rel: None,
+        relsz: None,
+        relent: None,
         jmprel: None,
         pltrelsz: None,
         pltrel: None,
@@ -188,6 +227,11 @@ fn empty_dynamic_scan() -> DynamicScan {
         init_arraysz: None,
         fini_array: None,
         fini_arraysz: None,
+        preinit_array: None,
+        preinit_arraysz: None,
+        init: None,
+        fini: None,
+        flags_1: None,
     }
 }
 
@@ -195,10 +239,10 @@ fn scan_dynamic(bytes: &Vec<u8>, dyn_off: usize, dyn_size: usize) -> (r: Result<
     LoaderError,
 >) {
     if dyn_size % ELF64_DYN_SIZE != 0 {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
     if ensure_range(bytes.len(), dyn_off, dyn_size).is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
 
     let mut scan = empty_dynamic_scan();
@@ -213,22 +257,22 @@ fn scan_dynamic(bytes: &Vec<u8>, dyn_off: usize, dyn_size: usize) -> (r: Result<
     {
         let step = i.checked_mul(ELF64_DYN_SIZE);
         if step.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::BadDynamicSection));
         }
         let base = dyn_off.checked_add(step.unwrap());
         if base.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::BadDynamicSection));
         }
         let base = base.unwrap();
 
         let tag_r = read_i64_le(bytes, base);
         let base8 = base.checked_add(8);
         if base8.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::BadDynamicSection));
         }
         let val_r = read_u64_le(bytes, base8.unwrap());
         if tag_r.is_err() || val_r.is_err() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::BadDynamicSection));
         }
         let tag = tag_r.unwrap();
         let val = val_r.unwrap();
@@ -238,12 +282,12 @@ fn scan_dynamic(bytes: &Vec<u8>, dyn_off: usize, dyn_size: usize) -> (r: Result<
             break;
         } else if tag == DT_NEEDED {
             if val > u32::MAX as u64 {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::BadDynamicSection));
             }
             scan.needed_offsets.push(val as u32);
         } else if tag == DT_SONAME {
             if val > u32::MAX as u64 {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::BadDynamicSection));
             }
             scan.soname_offset = Some(val as u32);
         } else if tag == DT_STRTAB {
@@ -254,12 +298,32 @@ fn scan_dynamic(bytes: &Vec<u8>, dyn_off: usize, dyn_size: usize) -> (r: Result<
             scan.symtab = Some(val);
         } else if tag == DT_SYMENT {
             scan.syment = Some(val);
+        } else if tag == DT_HASH {
+            scan.hash = Some(val);
+        } else if tag == DT_GNU_HASH {
+            scan.gnu_hash = Some(val);
+        } else if tag == DT_VERSYM {
+            scan.versym = Some(val);
+        } else if tag == DT_VERDEF {
+            scan.verdef = Some(val);
+        } else if tag == DT_VERDEFNUM {
+            scan.verdefnum = Some(val);
+        } else if tag == DT_VERNEED {
+            scan.verneed = Some(val);
+        } else if tag == DT_VERNEEDNUM {
+            scan.verneednum = Some(val);
         } else if tag == DT_RELA {
             scan.rela = Some(val);
         } else if tag == DT_RELASZ {
             scan.relasz = Some(val);
         } else if tag == DT_RELAENT {
             scan.relaent = Some(val);
+        } else if tag == DT_REL {
+            scan.rel = Some(val);
+        } else if tag == DT_RELSZ {
+            scan.relsz = Some(val);
+        } else if tag == DT_RELENT {
+            scan.relent = Some(val);
         } else if tag == DT_JMPREL {
             scan.jmprel = Some(val);
         } else if tag == DT_PLTRELSZ {
@@ -280,33 +344,599 @@ fn scan_dynamic(bytes: &Vec<u8>, dyn_off: usize, dyn_size: usize) -> (r: Result<
             scan.fini_array = Some(val);
         } else if tag == DT_FINI_ARRAYSZ {
             scan.fini_arraysz = Some(val);
+        } else if tag == DT_PREINIT_ARRAY {
+            scan.preinit_array = Some(val);
+        } else if tag == DT_PREINIT_ARRAYSZ {
+            scan.preinit_arraysz = Some(val);
+        } else if tag == DT_INIT {
+            scan.init = Some(val);
+        } else if tag == DT_FINI {
+            scan.fini = Some(val);
+        } else if tag == DT_FLAGS_1 {
+            scan.flags_1 = Some(val);
         }
 
         i = i + 1;
     }
 
     if !saw_null {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
 
     Ok(scan)
 }
 
+fn table_file_offset(phdrs: &Vec<ProgramHeader>, vaddr: u64, size: u64) -> (r: Result<usize, LoaderError>)
+    ensures
+        r.is_ok() ==> vaddr_in_file(phdrs@, vaddr, size, r.unwrap() as int),
+{
+    let off_r = vaddr_to_file_offset(phdrs, vaddr, size);
+    if off_r.is_err() {
+        return Err(off_r.unwrap_err());
+    }
+    u64_to_usize(off_r.unwrap())
+}
+
+fn read_u16_at(bytes: &Vec<u8>, base: usize, delta: usize) -> (r: Result<u16, LoaderError>) {
+    let off = base.checked_add(delta);
+    if off.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    read_u16_le(bytes, off.unwrap())
+}
+
+fn read_u32_at(bytes: &Vec<u8>, base: usize, delta: usize) -> (r: Result<u32, LoaderError>)
+    ensures
+        r.is_ok() ==> r.unwrap() == u32_le(bytes@, base + delta),
+{
+    let off = base.checked_add(delta);
+    if off.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    read_u32_le(bytes, off.unwrap())
+}
+
+fn dynsym_count_from_hash(bytes: &Vec<u8>, phdrs: &Vec<ProgramHeader>, hash_vaddr: u64) -> (r: Result<
+    usize,
+    LoaderError,
+>)
+    ensures
+        r.is_ok() ==> exists|off: int|
+            vaddr_in_file(phdrs@, hash_vaddr, 8, off) && r.unwrap() as int == u32_le(bytes@, off + 4) as int,
+{
+    let off_r = table_file_offset(phdrs, hash_vaddr, 8);
+    if off_r.is_err() {
+        return Err(off_r.unwrap_err());
+    }
+    let off = off_r.unwrap();
+    let nchain_r = read_u32_at(bytes, off, 4);
+    if nchain_r.is_err() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    assert(vaddr_in_file(phdrs@, hash_vaddr, 8, off as int));
+    Ok(nchain_r.unwrap() as usize)
+}
+
+fn dynsym_count_from_gnu_hash(bytes: &Vec<u8>, phdrs: &Vec<ProgramHeader>, gnu_hash_vaddr: u64) -> (r:
+    Result<Option<usize>, LoaderError>)
+    ensures
+        r.is_ok() ==> exists|off: int|
+            vaddr_in_file(phdrs@, gnu_hash_vaddr, 16, off) && match r.unwrap() {
+                Some(count) => gnu_hash_count(bytes@, off, count as int),
+                None => gnu_hash_empty(bytes@, off),
+            },
+{
+    let off_r = table_file_offset(phdrs, gnu_hash_vaddr, 16);
+    if off_r.is_err() {
+        return Err(off_r.unwrap_err());
+    }
+    let off = off_r.unwrap();
+    let nbuckets_r = read_u32_at(bytes, off, 0);
+    let symoffset_r = read_u32_at(bytes, off, 4);
+    let bloom_size_r = read_u32_at(bytes, off, 8);
+    if nbuckets_r.is_err() || symoffset_r.is_err() || bloom_size_r.is_err() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let nbuckets = nbuckets_r.unwrap() as usize;
+    let symoffset = symoffset_r.unwrap() as usize;
+    let bloom_bytes = (bloom_size_r.unwrap() as usize).checked_mul(8);
+    if bloom_bytes.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let buckets_off = off.checked_add(16);
+    if buckets_off.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let buckets_off = buckets_off.unwrap().checked_add(bloom_bytes.unwrap());
+    let bucket_bytes = nbuckets.checked_mul(4);
+    if buckets_off.is_none() || bucket_bytes.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let buckets_off = buckets_off.unwrap();
+    let chains_off = buckets_off.checked_add(bucket_bytes.unwrap());
+    if chains_off.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let chains_off = chains_off.unwrap();
+    let ghost goff = off as int;
+    assert(nbuckets as int == gnu_nbuckets(bytes@, goff));
+    assert(symoffset as int == gnu_symoffset(bytes@, goff));
+    assert(buckets_off as int == gnu_buckets_off(bytes@, goff));
+
+    let mut max_bucket: usize = 0;
+    let mut b: usize = 0;
+    while b < nbuckets
+        invariant
+            b <= nbuckets,
+            nbuckets as int == gnu_nbuckets(bytes@, goff),
+            buckets_off as int == gnu_buckets_off(bytes@, goff),
+            forall|k: int| 0 <= k < b ==> gnu_bucket(bytes@, goff, k) <= max_bucket,
+            max_bucket == 0 || exists|k: int| 0 <= k < b && gnu_bucket(bytes@, goff, k) == max_bucket,
+        decreases nbuckets - b,
+    {
+        let step = b.checked_mul(4);
+        if step.is_none() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        let bucket_r = read_u32_at(bytes, buckets_off, step.unwrap());
+        if bucket_r.is_err() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        let bucket = bucket_r.unwrap() as usize;
+        assert(bucket as int == gnu_bucket(bytes@, goff, b as int));
+        if bucket > max_bucket {
+            max_bucket = bucket;
+        }
+        b = b + 1;
+    }
+    assert(gnu_max_bucket(bytes@, goff, max_bucket as int));
+    if max_bucket < symoffset {
+        assert(vaddr_in_file(phdrs@, gnu_hash_vaddr, 16, goff));
+        assert(gnu_hash_empty(bytes@, goff));
+        return Ok(None);
+    }
+
+    // Walk the highest bucket's chain, but only over the entries that fit in
+    // the file after `chains_off`.
+    let chain_room = if chains_off <= bytes.len() {
+        (bytes.len() - chains_off) / 4
+    } else {
+        0
+    };
+    let chain_end = symoffset.checked_add(chain_room);
+    if chain_end.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let chain_end = chain_end.unwrap();
+    let mut idx = max_bucket;
+    while idx < chain_end
+        invariant
+            symoffset <= max_bucket <= idx,
+            symoffset as int == gnu_symoffset(bytes@, goff),
+            chains_off as int == gnu_buckets_off(bytes@, goff) + 4 * gnu_nbuckets(bytes@, goff),
+            gnu_max_bucket(bytes@, goff, max_bucket as int),
+            forall|i: int| max_bucket <= i < idx ==> !gnu_chain_ends(bytes@, goff, i),
+        decreases chain_end - idx,
+    {
+        let step = (idx - symoffset).checked_mul(4);
+        if step.is_none() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        let chain_r = read_u32_at(bytes, chains_off, step.unwrap());
+        if chain_r.is_err() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        if chain_r.unwrap() & 1 != 0 {
+            assert(gnu_chain_ends(bytes@, goff, idx as int));
+            assert(vaddr_in_file(phdrs@, gnu_hash_vaddr, 16, goff));
+            assert(gnu_hash_count(bytes@, goff, idx + 1));
+            return Ok(Some(idx + 1));
+        }
+        idx = idx + 1;
+    }
+    Err(parse_error(LoaderErrorReason::Truncated))
+}
+
+fn read_u32_words(bytes: &Vec<u8>, off: usize, count: usize) -> (r: Result<Vec<u32>, LoaderError>)
+    ensures
+        r.is_ok() ==> r.unwrap()@.len() == count,
+{
+    let mut out: Vec<u32> = Vec::new();
+    let mut i: usize = 0;
+    while i < count
+        invariant
+            i <= count,
+            out@.len() == i,
+        decreases count - i,
+    {
+        let step = i.checked_mul(4);
+        if step.is_none() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        let v = read_u32_at(bytes, off, step.unwrap());
+        if v.is_err() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        out.push(v.unwrap());
+        i = i + 1;
+    }
+    Ok(out)
+}
+
+fn read_u64_words(bytes: &Vec<u8>, off: usize, count: usize) -> (r: Result<Vec<u64>, LoaderError>)
+    ensures
+        r.is_ok() ==> r.unwrap()@.len() == count,
+{
+    let mut out: Vec<u64> = Vec::new();
+    let mut i: usize = 0;
+    while i < count
+        invariant
+            i <= count,
+            out@.len() == i,
+        decreases count - i,
+    {
+        let step = i.checked_mul(8);
+        if step.is_none() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        let base = off.checked_add(step.unwrap());
+        if base.is_none() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        let v = read_u64_le(bytes, base.unwrap());
+        if v.is_err() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        out.push(v.unwrap());
+        i = i + 1;
+    }
+    Ok(out)
+}
+
+fn parse_gnu_hash_table(
+    bytes: &Vec<u8>,
+    phdrs: &Vec<ProgramHeader>,
+    gnu_hash_vaddr: u64,
+    nsyms: usize,
+) -> (r: Result<GnuHashTable, LoaderError>) {
+    let off_r = table_file_offset(phdrs, gnu_hash_vaddr, 16);
+    if off_r.is_err() {
+        return Err(off_r.unwrap_err());
+    }
+    let off = off_r.unwrap();
+    let nbuckets_r = read_u32_at(bytes, off, 0);
+    let symoffset_r = read_u32_at(bytes, off, 4);
+    let bloom_size_r = read_u32_at(bytes, off, 8);
+    let bloom_shift_r = read_u32_at(bytes, off, 12);
+    if nbuckets_r.is_err() || symoffset_r.is_err() || bloom_size_r.is_err() || bloom_shift_r.is_err() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let symoffset = symoffset_r.unwrap();
+    if symoffset as usize > nsyms {
+        return Err(parse_error(LoaderErrorReason::BadSymbolTable));
+    }
+    let nbuckets = nbuckets_r.unwrap() as usize;
+    let bloom_size = bloom_size_r.unwrap() as usize;
+
+    let bloom_off = off.checked_add(16);
+    if bloom_off.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let bloom_off = bloom_off.unwrap();
+    let bloom_r = read_u64_words(bytes, bloom_off, bloom_size);
+    if bloom_r.is_err() {
+        return Err(bloom_r.unwrap_err());
+    }
+    let bloom_bytes = bloom_size.checked_mul(8);
+    if bloom_bytes.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let buckets_off = bloom_off.checked_add(bloom_bytes.unwrap());
+    if buckets_off.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let buckets_off = buckets_off.unwrap();
+    let buckets_r = read_u32_words(bytes, buckets_off, nbuckets);
+    if buckets_r.is_err() {
+        return Err(buckets_r.unwrap_err());
+    }
+    let bucket_bytes = nbuckets.checked_mul(4);
+    if bucket_bytes.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let chains_off = buckets_off.checked_add(bucket_bytes.unwrap());
+    if chains_off.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let chains_r = read_u32_words(bytes, chains_off.unwrap(), nsyms - symoffset as usize);
+    if chains_r.is_err() {
+        return Err(chains_r.unwrap_err());
+    }
+    Ok(GnuHashTable {
+        symoffset,
+        bloom_shift: bloom_shift_r.unwrap(),
+        bloom: bloom_r.unwrap(),
+        buckets: buckets_r.unwrap(),
+        chains: chains_r.unwrap(),
+    })
+}
+
+fn parse_sysv_hash_table(bytes: &Vec<u8>, phdrs: &Vec<ProgramHeader>, hash_vaddr: u64) -> (r: Result<
+    SysvHashTable,
+    LoaderError,
+>) {
+    let off_r = table_file_offset(phdrs, hash_vaddr, 8);
+    if off_r.is_err() {
+        return Err(off_r.unwrap_err());
+    }
+    let off = off_r.unwrap();
+    let nbucket_r = read_u32_at(bytes, off, 0);
+    let nchain_r = read_u32_at(bytes, off, 4);
+    if nbucket_r.is_err() || nchain_r.is_err() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let nbucket = nbucket_r.unwrap() as usize;
+    let nchain = nchain_r.unwrap() as usize;
+    let buckets_off = off.checked_add(8);
+    if buckets_off.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let buckets_off = buckets_off.unwrap();
+    let buckets_r = read_u32_words(bytes, buckets_off, nbucket);
+    if buckets_r.is_err() {
+        return Err(buckets_r.unwrap_err());
+    }
+    let bucket_bytes = nbucket.checked_mul(4);
+    if bucket_bytes.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let chains_off = buckets_off.checked_add(bucket_bytes.unwrap());
+    if chains_off.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let chains_r = read_u32_words(bytes, chains_off.unwrap(), nchain);
+    if chains_r.is_err() {
+        return Err(chains_r.unwrap_err());
+    }
+    Ok(SysvHashTable { buckets: buckets_r.unwrap(), chains: chains_r.unwrap() })
+}
+
+fn parse_versym_table(bytes: &Vec<u8>, phdrs: &Vec<ProgramHeader>, vaddr: u64, count: usize) -> (r:
+    Result<Vec<u16>, LoaderError>)
+    ensures
+        r.is_ok() ==> r.unwrap()@.len() == count,
+{
+    let size = count.checked_mul(2);
+    if size.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
+    }
+    let off_r = table_file_offset(phdrs, vaddr, size.unwrap() as u64);
+    if off_r.is_err() {
+        return Err(off_r.unwrap_err());
+    }
+    let off = off_r.unwrap();
+    let mut out: Vec<u16> = Vec::new();
+    let mut i: usize = 0;
+    while i < count
+        invariant
+            i <= count,
+            out@.len() == i,
+        decreases count - i,
+    {
+        let step = i.checked_mul(2);
+        if step.is_none() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        let v = read_u16_at(bytes, off, step.unwrap());
+        if v.is_err() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        out.push(v.unwrap());
+        i = i + 1;
+    }
+    Ok(out)
+}
+
+fn parse_verdefs(bytes: &Vec<u8>, phdrs: &Vec<ProgramHeader>, vaddr: u64, num: u64, dynstr_len: usize) -> (r:
+    Result<Vec<VersionDef>, LoaderError>)
+    ensures
+        r.is_ok() ==> forall|k: int|
+            0 <= k < r.unwrap()@.len() ==> (r.unwrap()@[k].name_offset as int) < dynstr_len as int,
+{
+    let mut out: Vec<VersionDef> = Vec::new();
+    if num == 0 {
+        return Ok(out);
+    }
+    let off_r = table_file_offset(phdrs, vaddr, 20);
+    if off_r.is_err() {
+        return Err(off_r.unwrap_err());
+    }
+    let mut entry = off_r.unwrap();
+    let mut i: u64 = 0;
+    while i < num
+        invariant
+            i <= num,
+            forall|k: int| 0 <= k < out@.len() ==> (out@[k].name_offset as int) < dynstr_len as int,
+        decreases num - i,
+    {
+        let flags_r = read_u16_at(bytes, entry, 2);
+        let index_r = read_u16_at(bytes, entry, 4);
+        let aux_r = read_u32_at(bytes, entry, 12);
+        let next_r = read_u32_at(bytes, entry, 16);
+        if flags_r.is_err() || index_r.is_err() || aux_r.is_err() || next_r.is_err() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        let name_r = read_u32_at(bytes, entry, aux_r.unwrap() as usize);
+        if name_r.is_err() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        let name = name_r.unwrap();
+        if name as usize >= dynstr_len {
+            return Err(parse_error(LoaderErrorReason::BadSymbolTable));
+        }
+        proof {
+            lemma_u32_usize_lt_int_lt(name, dynstr_len);
+        }
+        out.push(VersionDef { index: index_r.unwrap(), flags: flags_r.unwrap(), name_offset: name });
+        let next = next_r.unwrap() as usize;
+        if next == 0 {
+            break;
+        }
+        let next_entry = entry.checked_add(next);
+        if next_entry.is_none() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        entry = next_entry.unwrap();
+        i = i + 1;
+    }
+    Ok(out)
+}
+
+fn parse_verneeds(bytes: &Vec<u8>, phdrs: &Vec<ProgramHeader>, vaddr: u64, num: u64, dynstr_len: usize) -> (r:
+    Result<Vec<VersionNeed>, LoaderError>)
+    ensures
+        r.is_ok() ==> forall|k: int|
+            0 <= k < r.unwrap()@.len() ==> (r.unwrap()@[k].name_offset as int) < dynstr_len as int && (
+            r.unwrap()@[k].file_offset as int) < dynstr_len as int,
+{
+    let mut out: Vec<VersionNeed> = Vec::new();
+    if num == 0 {
+        return Ok(out);
+    }
+    let off_r = table_file_offset(phdrs, vaddr, 16);
+    if off_r.is_err() {
+        return Err(off_r.unwrap_err());
+    }
+    let mut entry = off_r.unwrap();
+    let mut i: u64 = 0;
+    while i < num
+        invariant
+            i <= num,
+            forall|k: int|
+                0 <= k < out@.len() ==> (out@[k].name_offset as int) < dynstr_len as int && (
+                out@[k].file_offset as int) < dynstr_len as int,
+        decreases num - i,
+    {
+        let cnt_r = read_u16_at(bytes, entry, 2);
+        let file_r = read_u32_at(bytes, entry, 4);
+        let aux_r = read_u32_at(bytes, entry, 8);
+        let next_r = read_u32_at(bytes, entry, 12);
+        if cnt_r.is_err() || file_r.is_err() || aux_r.is_err() || next_r.is_err() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        let file = file_r.unwrap();
+        if file as usize >= dynstr_len {
+            return Err(parse_error(LoaderErrorReason::BadSymbolTable));
+        }
+        proof {
+            lemma_u32_usize_lt_int_lt(file, dynstr_len);
+        }
+        let cnt = cnt_r.unwrap();
+        let aux = entry.checked_add(aux_r.unwrap() as usize);
+        if aux.is_none() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        let mut aux = aux.unwrap();
+        let mut j: u16 = 0;
+        while j < cnt
+            invariant
+                j <= cnt,
+                (file as int) < dynstr_len as int,
+                forall|k: int|
+                    0 <= k < out@.len() ==> (out@[k].name_offset as int) < dynstr_len as int && (
+                    out@[k].file_offset as int) < dynstr_len as int,
+            decreases cnt - j,
+        {
+            let flags_r = read_u16_at(bytes, aux, 4);
+            let other_r = read_u16_at(bytes, aux, 6);
+            let name_r = read_u32_at(bytes, aux, 8);
+            let vna_next_r = read_u32_at(bytes, aux, 12);
+            if flags_r.is_err() || other_r.is_err() || name_r.is_err() || vna_next_r.is_err() {
+                return Err(parse_error(LoaderErrorReason::Truncated));
+            }
+            let name = name_r.unwrap();
+            if name as usize >= dynstr_len {
+                return Err(parse_error(LoaderErrorReason::BadSymbolTable));
+            }
+            proof {
+                lemma_u32_usize_lt_int_lt(name, dynstr_len);
+            }
+            out.push(VersionNeed {
+                file_offset: file,
+                index: other_r.unwrap(),
+                flags: flags_r.unwrap(),
+                name_offset: name,
+            });
+            let vna_next = vna_next_r.unwrap() as usize;
+            if vna_next == 0 {
+                break;
+            }
+            let next_aux = aux.checked_add(vna_next);
+            if next_aux.is_none() {
+                return Err(parse_error(LoaderErrorReason::Truncated));
+            }
+            aux = next_aux.unwrap();
+            j = j + 1;
+        }
+        let next = next_r.unwrap() as usize;
+        if next == 0 {
+            break;
+        }
+        let next_entry = entry.checked_add(next);
+        if next_entry.is_none() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
+        }
+        entry = next_entry.unwrap();
+        i = i + 1;
+    }
+    Ok(out)
+}
+
+fn dynsym_count_from_layout(
+    phdrs: &Vec<ProgramHeader>,
+    symtab_vaddr: u64,
+    dynstr_file_off: u64,
+) -> (r: Result<usize, LoaderError>) {
+    let symtab_file_off_r = vaddr_to_file_offset(phdrs, symtab_vaddr, 0);
+    if symtab_file_off_r.is_err() {
+        return Err(symtab_file_off_r.unwrap_err());
+    }
+    let symtab_file_off = symtab_file_off_r.unwrap();
+    if symtab_file_off > dynstr_file_off {
+        return Err(parse_error(LoaderErrorReason::BadSymbolTable));
+    }
+    let dynsym_span = dynstr_file_off - symtab_file_off;
+    if dynsym_span % (ELF64_SYM_SIZE as u64) != 0 {
+        return Err(parse_error(LoaderErrorReason::BadSymbolTable));
+    }
+    u64_to_usize(dynsym_span / (ELF64_SYM_SIZE as u64))
+}
+
+// A REL table has 16-byte entries whose addend is the word at the target,
+// which must lie in the file image.
 fn parse_rela_table(
     bytes: &Vec<u8>,
     phdrs: &Vec<ProgramHeader>,
     vaddr: u64,
     size: u64,
+    table: RelocTable,
 ) -> (r: Result<Vec<RelaEntry>, LoaderError>)
     ensures
         r.is_ok() ==> forall|i: int|
             0 <= i < r.unwrap()@.len() ==> supported_reloc_type(rela_type(r.unwrap()@[i])),
+        r.is_ok() ==> forall|i: int| 0 <= i < r.unwrap()@.len() ==> r.unwrap()@[i].table == table,
 {
     if size == 0 {
         return Ok(Vec::new());
     }
-    if size % (ELF64_RELA_SIZE as u64) != 0 {
-        return Err(LoaderError {});
+    let implicit_addend = matches!(table, RelocTable::Rel);
+    let ent_size = if implicit_addend {
+        ELF64_REL_SIZE
+    } else {
+        ELF64_RELA_SIZE
+    };
+    if size % (ent_size as u64) != 0 {
+        return Err(parse_error(LoaderErrorReason::BadRelocationTable));
     }
 
     let file_off_r = vaddr_to_file_offset(phdrs, vaddr, size);
@@ -317,15 +947,15 @@ fn parse_rela_table(
     let file_off_r = u64_to_usize(file_off_u64);
     let size_r = u64_to_usize(size);
     if file_off_r.is_err() || size_r.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     let file_off = file_off_r.unwrap();
     let size_usize = size_r.unwrap();
     if ensure_range(bytes.len(), file_off, size_usize).is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
 
-    let count = size_usize / ELF64_RELA_SIZE;
+    let count = size_usize / ent_size;
     let mut out: Vec<RelaEntry> = Vec::new();
     let mut i: usize = 0;
 
@@ -333,45 +963,57 @@ fn parse_rela_table(
         invariant
             i <= count,
             forall|k: int| 0 <= k < out@.len() ==> supported_reloc_type(rela_type(out@[k])),
+            forall|k: int| 0 <= k < out@.len() ==> out@[k].table == table,
         decreases count - i,
     {
-        let step = i.checked_mul(ELF64_RELA_SIZE);
+        let step = i.checked_mul(ent_size);
         if step.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         let base = file_off.checked_add(step.unwrap());
         if base.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         let base = base.unwrap();
 
         let base8 = base.checked_add(8);
         let base16 = base.checked_add(16);
         if base8.is_none() || base16.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
 
         let off_r = read_u64_le(bytes, base);
         let info_r = read_u64_le(bytes, base8.unwrap());
-        let add_r = read_i64_le(bytes, base16.unwrap());
-        if off_r.is_err() || info_r.is_err() || add_r.is_err() {
-            return Err(LoaderError {});
+        if off_r.is_err() || info_r.is_err() {
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
+        let offset = off_r.unwrap();
+        let addend = if implicit_addend {
+            let target_r = read_u64_from_image(bytes, phdrs, offset);
+            if target_r.is_err() {
+                return Err(target_r.unwrap_err());
+            }
+            0
+        } else {
+            let add_r = read_i64_le(bytes, base16.unwrap());
+            if add_r.is_err() {
+                return Err(parse_error(LoaderErrorReason::Truncated));
+            }
+            add_r.unwrap()
+        };
 
         let info = info_r.unwrap();
         let reloc_type = (info & 0xffff_ffff) as u32;
         if reloc_type != R_X86_64_RELATIVE && reloc_type != R_X86_64_JUMP_SLOT
             && reloc_type != R_X86_64_GLOB_DAT && reloc_type != R_X86_64_COPY
-            && reloc_type != R_X86_64_64
+            && reloc_type != R_X86_64_64 && reloc_type != R_X86_64_DTPMOD64
+            && reloc_type != R_X86_64_DTPOFF64 && reloc_type != R_X86_64_TPOFF64
+            && reloc_type != R_X86_64_IRELATIVE
         {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::UnsupportedRelocType { reloc_type, offset }));
         }
 
-        out.push(RelaEntry {
-            offset: off_r.unwrap(),
-            info,
-            addend: add_r.unwrap(),
-        });
+        out.push(RelaEntry { offset, info, addend, table });
         i = i + 1;
     }
 
@@ -388,7 +1030,7 @@ fn parse_relr_table(
         return Ok(Vec::new());
     }
     if size % 8 != 0 {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadRelocationTable));
     }
 
     let file_off_r = vaddr_to_file_offset(phdrs, vaddr, size);
@@ -399,12 +1041,12 @@ fn parse_relr_table(
     let file_off_r = u64_to_usize(file_off_u64);
     let size_r = u64_to_usize(size);
     if file_off_r.is_err() || size_r.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     let file_off = file_off_r.unwrap();
     let size_usize = size_r.unwrap();
     if ensure_range(bytes.len(), file_off, size_usize).is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
 
     let count = size_usize / 8;
@@ -417,11 +1059,11 @@ fn parse_relr_table(
     {
         let step = i.checked_mul(8);
         if step.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         let base = file_off.checked_add(step.unwrap());
         if base.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         let val_r = read_u64_le(bytes, base.unwrap());
         if val_r.is_err() {
@@ -448,18 +1090,18 @@ fn decode_relr_entries(entries: &Vec<u64>) -> (r: Result<Vec<u64>, LoaderError>)
         let e = entries[i];
         if (e & 1u64) == 0 {
             if e % 8 != 0 {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::BadRelocationTable));
             }
             out.push(e);
             let nxt = e.checked_add(8);
             if nxt.is_none() {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::BadRelocationTable));
             }
             where_next = nxt.unwrap();
             have_base = true;
         } else {
             if !have_base {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::BadRelocationTable));
             }
             let mut bit: usize = 0;
             while bit < 63
@@ -471,11 +1113,11 @@ fn decode_relr_entries(entries: &Vec<u64>) -> (r: Result<Vec<u64>, LoaderError>)
                 if (e & mask) != 0 {
                     let rel = (bit as u64).checked_mul(8);
                     if rel.is_none() {
-                        return Err(LoaderError {});
+                        return Err(parse_error(LoaderErrorReason::BadRelocationTable));
                     }
                     let off = where_next.checked_add(rel.unwrap());
                     if off.is_none() {
-                        return Err(LoaderError {});
+                        return Err(parse_error(LoaderErrorReason::BadRelocationTable));
                     }
                     out.push(off.unwrap());
                 }
@@ -483,7 +1125,7 @@ fn decode_relr_entries(entries: &Vec<u64>) -> (r: Result<Vec<u64>, LoaderError>)
             }
             let nxt = where_next.checked_add(63u64 * 8u64);
             if nxt.is_none() {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::BadRelocationTable));
             }
             where_next = nxt.unwrap();
         }
@@ -507,7 +1149,7 @@ fn read_u8_from_image(bytes: &Vec<u8>, phdrs: &Vec<ProgramHeader>, vaddr: u64) -
         if ph.p_type == PT_LOAD {
             let mem_end = ph.p_vaddr.checked_add(ph.p_memsz);
             if mem_end.is_none() {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::Truncated));
             }
             let mem_end = mem_end.unwrap();
             if vaddr >= ph.p_vaddr && vaddr < mem_end {
@@ -515,11 +1157,11 @@ fn read_u8_from_image(bytes: &Vec<u8>, phdrs: &Vec<ProgramHeader>, vaddr: u64) -
                 if delta < ph.p_filesz {
                     let file_off = ph.p_offset.checked_add(delta);
                     if file_off.is_none() {
-                        return Err(LoaderError {});
+                        return Err(parse_error(LoaderErrorReason::Truncated));
                     }
                     let file_off_r = u64_to_usize(file_off.unwrap());
                     if file_off_r.is_err() {
-                        return Err(LoaderError {});
+                        return Err(parse_error(LoaderErrorReason::Truncated));
                     }
                     return read_u8(bytes, file_off_r.unwrap());
                 } else {
@@ -529,7 +1171,7 @@ fn read_u8_from_image(bytes: &Vec<u8>, phdrs: &Vec<ProgramHeader>, vaddr: u64) -
         }
         i = i + 1;
     }
-    Err(LoaderError {})
+    Err(parse_error(LoaderErrorReason::UnmappedAddress { vaddr }))
 }
 
 fn read_u64_from_image(bytes: &Vec<u8>, phdrs: &Vec<ProgramHeader>, vaddr: u64) -> (r: Result<
@@ -545,7 +1187,7 @@ fn read_u64_from_image(bytes: &Vec<u8>, phdrs: &Vec<ProgramHeader>, vaddr: u64)
     {
         let cur_addr = vaddr.checked_add(i as u64);
         if cur_addr.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         let b_r = read_u8_from_image(bytes, phdrs, cur_addr.unwrap());
         if b_r.is_err() {
@@ -562,34 +1204,68 @@ fn relr_offsets_to_relas(
     bytes: &Vec<u8>,
     phdrs: &Vec<ProgramHeader>,
     relr_offsets: &Vec<u64>,
-) -> (r: Result<Vec<RelaEntry>, LoaderError>) {
+) -> (r: Result<Vec<RelaEntry>, LoaderError>)
+    ensures
+        r.is_ok() ==> forall|i: int|
+            0 <= i < r.unwrap()@.len() ==> r.unwrap()@[i].table == RelocTable::Relr && rela_type(r.unwrap()@[i])
+                == R_X86_64_RELATIVE,
+{
     let mut out: Vec<RelaEntry> = Vec::new();
     let mut i: usize = 0;
     while i < relr_offsets.len()
         invariant
             i <= relr_offsets.len(),
+            forall|k: int|
+                0 <= k < out@.len() ==> out@[k].table == RelocTable::Relr && rela_type(out@[k]) == R_X86_64_RELATIVE,
         decreases relr_offsets.len() - i,
     {
         let off = relr_offsets[i];
         if off % 8 != 0 {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::BadRelocationTable));
         }
         let addend_r = read_u64_from_image(bytes, phdrs, off);
         if addend_r.is_err() {
             return Err(addend_r.unwrap_err());
         }
         let addend_u = addend_r.unwrap();
-        let entry = RelaEntry {
-            offset: off,
-            info: R_X86_64_RELATIVE as u64,
-            addend: addend_u as i64,
-        };
+        let info = R_X86_64_RELATIVE as u64;
+        let entry = RelaEntry { offset: off, info, addend: addend_u as i64, table: RelocTable::Relr };
+        proof {
+            assert(info & 0xffff_ffff == info) by (bit_vector)
+                requires
+                    info == 8u64,
+            ;
+            assert(rela_type(entry) == R_X86_64_RELATIVE);
+        }
         out.push(entry);
         i = i + 1;
     }
     Ok(out)
 }
 
+fn relative_rela_only(entries: &Vec<RelaEntry>) -> (ok: bool)
+    ensures
+        ok == forall|k: int|
+            0 <= k < entries@.len() ==> entries@[k].table == RelocTable::Rela && rela_type(entries@[k])
+                == R_X86_64_RELATIVE,
+{
+    let mut i: usize = 0;
+    while i < entries.len()
+        invariant
+            i <= entries@.len(),
+            forall|k: int|
+                0 <= k < i ==> entries@[k].table == RelocTable::Rela && rela_type(entries@[k]) == R_X86_64_RELATIVE,
+        decreases entries.len() - i,
+    {
+        let e = &entries[i];
+        if !matches!(e.table, RelocTable::Rela) || (e.info & 0xffff_ffff) as u32 != R_X86_64_RELATIVE {
+            return false;
+        }
+        i = i + 1;
+    }
+    true
+}
+
 fn parse_init_array(
     bytes: &Vec<u8>,
     phdrs: &Vec<ProgramHeader>,
@@ -600,7 +1276,7 @@ fn parse_init_array(
         return Ok(Vec::new());
     }
     if size % 8 != 0 {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
 
     let file_off_r = vaddr_to_file_offset(phdrs, vaddr, size);
@@ -611,12 +1287,12 @@ fn parse_init_array(
     let file_off_r = u64_to_usize(file_off_u64);
     let size_r = u64_to_usize(size);
     if file_off_r.is_err() || size_r.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     let file_off = file_off_r.unwrap();
     let size_usize = size_r.unwrap();
     if ensure_range(bytes.len(), file_off, size_usize).is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
 
     let count = size_usize / 8;
@@ -630,16 +1306,16 @@ fn parse_init_array(
     {
         let step = i.checked_mul(8);
         if step.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         let base = file_off.checked_add(step.unwrap());
         if base.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         let base = base.unwrap();
         let val = read_u64_le(bytes, base);
         if val.is_err() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         out.push(val.unwrap());
         i = i + 1;
@@ -654,7 +1330,7 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
 {
     let bytes = &input.bytes;
     if bytes.len() < ELF64_EHDR_SIZE {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
 
     let m0 = read_u8(bytes, EI_MAG0);
@@ -662,28 +1338,28 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     let m2 = read_u8(bytes, EI_MAG2);
     let m3 = read_u8(bytes, EI_MAG3);
     if m0.is_err() || m1.is_err() || m2.is_err() || m3.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     if m0.unwrap() != ELFMAG0 || m1.unwrap() != ELFMAG1 || m2.unwrap() != ELFMAG2 || m3.unwrap()
         != ELFMAG3
     {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadElfHeader));
     }
 
     let cls = read_u8(bytes, EI_CLASS);
     let data = read_u8(bytes, EI_DATA);
     let ver = read_u8(bytes, EI_VERSION);
     if cls.is_err() || data.is_err() || ver.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     if cls.unwrap() != ELFCLASS64 {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadElfHeader));
     }
     if data.unwrap() != ELFDATA2LSB {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadElfHeader));
     }
     if ver.unwrap() != EV_CURRENT {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadElfHeader));
     }
 
     let e_type_r = read_u16_le(bytes, 16);
@@ -697,7 +1373,7 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     if e_type_r.is_err() || e_machine_r.is_err() || e_version_r.is_err() || e_entry_r.is_err()
         || e_phoff_r.is_err() || e_ehsize_r.is_err() || e_phentsize_r.is_err() || e_phnum_r.is_err()
     {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
 
     let e_type = e_type_r.unwrap();
@@ -710,39 +1386,39 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     let e_phnum = e_phnum_r.unwrap();
 
     if e_type != ET_EXEC && e_type != ET_DYN {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadElfHeader));
     }
     if e_machine != EM_X86_64 {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadElfHeader));
     }
     if e_version != EV_CURRENT as u32 {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadElfHeader));
     }
     if e_ehsize as usize != ELF64_EHDR_SIZE {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadElfHeader));
     }
     if e_phentsize as usize != ELF64_PHDR_SIZE {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadElfHeader));
     }
     if e_phnum == 0 {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadElfHeader));
     }
 
     let ph_table_size = (e_phnum as u64).checked_mul(e_phentsize as u64);
     if ph_table_size.is_none() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
     }
     let ph_end = e_phoff.checked_add(ph_table_size.unwrap());
     if ph_end.is_none() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
     }
     if ph_end.unwrap() > bytes.len() as u64 {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
     }
 
     let e_phoff_usize_r = u64_to_usize(e_phoff);
     if e_phoff_usize_r.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
     }
     let mut ph_off = e_phoff_usize_r.unwrap();
     let mut ph_i: usize = 0;
@@ -750,15 +1426,21 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
 
     let mut phdrs: Vec<ProgramHeader> = Vec::new();
     let mut saw_load = false;
+    let mut saw_interp = false;
     let mut dynamic_phdr: Option<ProgramHeader> = None;
+    let mut relro_phdr: Option<ProgramHeader> = None;
 
     while ph_i < ph_count
         invariant
             ph_i <= ph_count,
+            match relro_phdr {
+                Some(ph) => ph.p_type == PT_GNU_RELRO,
+                None => true,
+            },
         decreases ph_count - ph_i,
     {
         if ensure_range(bytes.len(), ph_off, ELF64_PHDR_SIZE).is_err() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
 
         let p_type_r = read_u32_le(bytes, ph_off);
@@ -767,10 +1449,11 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
         let p_vaddr_r = read_u64_le(bytes, ph_off + 16);
         let p_filesz_r = read_u64_le(bytes, ph_off + 32);
         let p_memsz_r = read_u64_le(bytes, ph_off + 40);
+        let p_align_r = read_u64_le(bytes, ph_off + 48);
         if p_type_r.is_err() || p_flags_r.is_err() || p_offset_r.is_err() || p_vaddr_r.is_err()
-            || p_filesz_r.is_err() || p_memsz_r.is_err()
+            || p_filesz_r.is_err() || p_memsz_r.is_err() || p_align_r.is_err()
         {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
 
         let p_type = p_type_r.unwrap();
@@ -779,52 +1462,61 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
         let p_vaddr = p_vaddr_r.unwrap();
         let p_filesz = p_filesz_r.unwrap();
         let p_memsz = p_memsz_r.unwrap();
+        let p_align = p_align_r.unwrap();
 
         if p_filesz > p_memsz {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
         }
 
-        let ph = ProgramHeader { p_type, p_flags, p_offset, p_vaddr, p_filesz, p_memsz };
+        let ph = ProgramHeader { p_type, p_flags, p_offset, p_vaddr, p_filesz, p_memsz, p_align };
 
         if p_type == PT_LOAD {
             saw_load = true;
         }
+        if p_type == PT_INTERP {
+            saw_interp = true;
+        }
 
-        if p_type == PT_LOAD || p_type == PT_DYNAMIC {
+        if p_type == PT_LOAD || p_type == PT_DYNAMIC || p_type == PT_TLS {
             let seg_off_r = u64_to_usize(p_offset);
             let seg_size_r = u64_to_usize(p_filesz);
             if seg_off_r.is_err() || seg_size_r.is_err() {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
             }
             if ensure_range(bytes.len(), seg_off_r.unwrap(), seg_size_r.unwrap()).is_err() {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
             }
             phdrs.push(ph.clone());
         }
 
         if p_type == PT_DYNAMIC {
             if dynamic_phdr.is_some() {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
             }
             dynamic_phdr = Some(ph);
         }
 
+        if p_type == PT_GNU_RELRO {
+            if relro_phdr.is_some() {
+                return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
+            }
+            relro_phdr = Some(ph);
+        }
+
         ph_i = ph_i + 1;
         ph_off = ph_off + ELF64_PHDR_SIZE;
     }
 
     if !saw_load {
-        return Err(LoaderError {});
-    }
-    if dynamic_phdr.is_none() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
     }
     if phdrs.len() == 0 {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
     }
 
     let mut has_load_phdr = false;
     let mut has_dynamic_phdr = false;
+    let mut tls: Option<ProgramHeader> = None;
     let mut chk_i: usize = 0;
     while chk_i < phdrs.len()
         invariant
@@ -832,14 +1524,52 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
             forall|k: int| 0 <= k < chk_i ==> valid_phdr(phdrs@[k]),
             has_load_phdr ==> exists|k: int| 0 <= k < chk_i && phdrs@[k].p_type == PT_LOAD,
             has_dynamic_phdr ==> exists|k: int| 0 <= k < chk_i && phdrs@[k].p_type == PT_DYNAMIC,
+            !has_dynamic_phdr ==> forall|k: int| 0 <= k < chk_i ==> phdrs@[k].p_type != PT_DYNAMIC,
+            tls_phdr_spec(phdrs@.subrange(0, chk_i as int), tls),
         decreases phdrs.len() - chk_i,
     {
         let p = &phdrs[chk_i];
-        if p.p_type != PT_LOAD && p.p_type != PT_DYNAMIC {
-            return Err(LoaderError {});
+        if p.p_type != PT_LOAD && p.p_type != PT_DYNAMIC && p.p_type != PT_TLS {
+            return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
+        }
+        let ghost prefix = phdrs@.subrange(0, chk_i as int);
+        let ghost next_prefix = phdrs@.subrange(0, chk_i + 1);
+        proof {
+            assert(next_prefix =~= prefix.push(phdrs@[chk_i as int]));
+        }
+        if p.p_type == PT_TLS {
+            if tls.is_some() {
+                return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
+            }
+            tls = Some(*p);
+            proof {
+                assert(next_prefix[chk_i as int] == phdrs@[chk_i as int]);
+                assert(tls_phdr_spec(next_prefix, tls));
+            }
+        } else {
+            proof {
+                match tls {
+                    Some(ph) => {
+                        let k = choose|k: int| 0 <= k < prefix.len() && prefix[k] == ph;
+                        assert(next_prefix[k] == ph);
+                    }
+                    None => {
+                        assert forall|k: int| 0 <= k < next_prefix.len() implies next_prefix[k].p_type
+                            != PT_TLS by {
+                            if k < prefix.len() {
+                                assert(next_prefix[k] == prefix[k]);
+                            }
+                        };
+                    }
+                }
+                assert(tls_phdr_spec(next_prefix, tls));
+            }
         }
         if p.p_filesz > p.p_memsz {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
+        }
+        if p.p_type == PT_LOAD && p.p_align > 1 && p.p_offset % p.p_align != p.p_vaddr % p.p_align {
+            return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
         }
         if p.p_type == PT_LOAD {
             has_load_phdr = true;
@@ -849,15 +1579,88 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
         }
         chk_i = chk_i + 1;
     }
-    if !has_load_phdr || !has_dynamic_phdr {
-        return Err(LoaderError {});
+    if !has_load_phdr {
+        return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
+    }
+    assert(phdrs@.subrange(0, phdrs@.len() as int) =~= phdrs@);
+
+    if !has_dynamic_phdr {
+        assert(e_type == ET_EXEC || e_type == ET_DYN);
+        let parsed = ParsedObject {
+            input_name: clone_u8_vec(&input.name),
+            file_bytes: clone_u8_vec(&input.bytes),
+            load_base: input.load_base,
+            elf_type: e_type,
+            entry: e_entry,
+            static_exec: !saw_interp,
+            phdrs,
+            tls,
+            relro: relro_phdr,
+            dynamic: DynamicInfo {
+                strtab_vaddr: 0,
+                strsz: 0,
+                symtab_vaddr: 0,
+                syment: 0,
+                hash_vaddr: 0,
+                gnu_hash_vaddr: 0,
+                rela_vaddr: 0,
+                relasz: 0,
+                relaent: 0,
+                rel_vaddr: 0,
+                relsz: 0,
+                relent: 0,
+                jmprel_vaddr: 0,
+                pltrelsz: 0,
+                pltrel: 0,
+                init_array_vaddr: 0,
+                init_array_sz: 0,
+                fini_array_vaddr: 0,
+                fini_array_sz: 0,
+                preinit_array_vaddr: 0,
+                preinit_array_sz: 0,
+            },
+            needed_offsets: Vec::new(),
+            soname_offset: None,
+            dynstr: Vec::new(),
+            dynsyms: Vec::new(),
+            dynsym_count_source: SymbolCountSource::SectionLayout,
+            hash_table: None,
+            versym: Vec::new(),
+            verdefs: Vec::new(),
+            verneeds: Vec::new(),
+            relas: Vec::new(),
+            jmprels: Vec::new(),
+            init: None,
+            fini: None,
+            preinit_array: Vec::new(),
+            init_array: Vec::new(),
+            fini_array: Vec::new(),
+        };
+        proof {
+            assert(!has_dynamic_segment(parsed.phdrs@));
+            assert(static_object_spec(parsed));
+            assert(static_exec_spec(parsed));
+            assert(dynsym_count_source_spec(
+                parsed.file_bytes@,
+                parsed.phdrs@,
+                parsed.dynamic,
+                parsed.dynsym_count_source,
+                parsed.dynsyms@.len() as int,
+            ));
+        }
+        return Ok(parsed);
     }
 
-    let dyn_ph = dynamic_phdr.unwrap();
+    let dyn_ph = match dynamic_phdr {
+        Some(ph) => ph,
+        None => {
+            return Err(parse_error(LoaderErrorReason::BadProgramHeaders));
+        },
+    };
     let dyn_off_r = u64_to_usize(dyn_ph.p_offset);
     let dyn_size_r = u64_to_usize(dyn_ph.p_filesz);
     if dyn_off_r.is_err() || dyn_size_r.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     let dyn_off = dyn_off_r.unwrap();
     let dyn_size = dyn_size_r.unwrap();
@@ -869,7 +1672,7 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     let scan = scan_r.unwrap();
 
     if scan.strtab.is_none() || scan.strsz.is_none() || scan.symtab.is_none() || scan.syment.is_none() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
 
     let strtab_vaddr = scan.strtab.unwrap();
@@ -878,39 +1681,52 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     let syment = scan.syment.unwrap();
 
     if strsz == 0 || syment != ELF64_SYM_SIZE as u64 {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
 
     if (scan.rela.is_some() && scan.relasz.is_none()) || (scan.rela.is_none() && scan.relasz.is_some()) {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
     if scan.relaent.is_some() && scan.relaent != Some(ELF64_RELA_SIZE as u64) {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
+    }
+    if (scan.rel.is_some() && scan.relsz.is_none()) || (scan.rel.is_none() && scan.relsz.is_some()) {
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
+    }
+    if scan.relent.is_some() && scan.relent != Some(ELF64_REL_SIZE as u64) {
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
 
     if (scan.jmprel.is_some() && scan.pltrelsz.is_none())
         || (scan.jmprel.is_none() && scan.pltrelsz.is_some())
     {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
-    if scan.pltrel.is_some() && scan.pltrel != Some(DT_RELA_TAG) {
-        return Err(LoaderError {});
+    if scan.pltrel.is_some() && scan.pltrel != Some(DT_RELA_TAG) && scan.pltrel != Some(DT_REL_TAG) {
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
     if (scan.relr.is_some() && scan.relrsz.is_none()) || (scan.relr.is_none() && scan.relrsz.is_some()) {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
     if scan.relrent.is_some() && scan.relrent != Some(8) {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
     if scan.relrsz.unwrap_or(0) > 0 && scan.relrent != Some(8) {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
+    }
+
+    if scan.verdef.is_some() != scan.verdefnum.is_some() || scan.verneed.is_some() != scan.verneednum.is_some() {
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
 
     if scan.init_arraysz.unwrap_or(0) > 0 && scan.init_array.is_none() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
     if scan.fini_arraysz.unwrap_or(0) > 0 && scan.fini_array.is_none() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
+    }
+    if scan.preinit_arraysz.unwrap_or(0) > 0 && scan.preinit_array.is_none() {
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
     }
 
     let dynstr_file_off_r = vaddr_to_file_offset(&phdrs, strtab_vaddr, strsz);
@@ -921,12 +1737,12 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     let dynstr_off_r = u64_to_usize(dynstr_file_off_u64);
     let dynstr_len_r = u64_to_usize(strsz);
     if dynstr_off_r.is_err() || dynstr_len_r.is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
     let dynstr_off = dynstr_off_r.unwrap();
     let dynstr_len = dynstr_len_r.unwrap();
     if ensure_range(bytes.len(), dynstr_off, dynstr_len).is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
 
     let mut dynstr: Vec<u8> = Vec::new();
@@ -940,11 +1756,11 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     {
         let idx = dynstr_off.checked_add(i);
         if idx.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         let b = read_u8(bytes, idx.unwrap());
         if b.is_err() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         dynstr.push(b.unwrap());
         i = i + 1;
@@ -966,7 +1782,7 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     {
         let off = scan.needed_offsets[n_i];
         if off as usize >= dynstr_len {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::BadDynamicSection));
         }
         let ghost before = needed_offsets@;
         needed_offsets.push(off);
@@ -994,43 +1810,57 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     let soname_offset = match scan.soname_offset {
         Some(off) => {
             if off as usize >= dynstr_len {
-                return Err(LoaderError {});
+                return Err(parse_error(LoaderErrorReason::BadDynamicSection));
             }
             Some(off)
         }
         None => None,
     };
 
-    let symtab_file_off_r = vaddr_to_file_offset(&phdrs, symtab_vaddr, 0);
-    if symtab_file_off_r.is_err() {
-        return Err(symtab_file_off_r.unwrap_err());
+    let hash_vaddr = scan.hash.unwrap_or(0);
+    let gnu_hash_vaddr = scan.gnu_hash.unwrap_or(0);
+    let gnu_count_r = if hash_vaddr == 0 && gnu_hash_vaddr != 0 {
+        dynsym_count_from_gnu_hash(bytes, &phdrs, gnu_hash_vaddr)
+    } else {
+        Ok(None)
+    };
+    if gnu_count_r.is_err() {
+        return Err(gnu_count_r.unwrap_err());
+    }
+    let gnu_count = gnu_count_r.unwrap();
+    // A DT_GNU_HASH table that hashes no symbols does not give the count.
+    let dynsym_count_source = if hash_vaddr != 0 {
+        SymbolCountSource::Hash
+    } else if gnu_count.is_some() {
+        SymbolCountSource::GnuHash
+    } else {
+        SymbolCountSource::SectionLayout
+    };
+    let count_r = match dynsym_count_source {
+        SymbolCountSource::Hash => dynsym_count_from_hash(bytes, &phdrs, hash_vaddr),
+        SymbolCountSource::GnuHash => Ok(gnu_count.unwrap()),
+        SymbolCountSource::SectionLayout => dynsym_count_from_layout(&phdrs, symtab_vaddr, dynstr_file_off_u64),
+    };
+    if count_r.is_err() {
+        return Err(count_r.unwrap_err());
     }
-    let symtab_file_off_u64 = symtab_file_off_r.unwrap();
-    if symtab_file_off_u64 > dynstr_file_off_u64 {
-        return Err(LoaderError {});
+    let dynsym_count = count_r.unwrap();
+    if dynsym_count == 0 {
+        return Err(parse_error(LoaderErrorReason::BadSymbolTable));
     }
 
-    let dynsym_span = dynstr_file_off_u64 - symtab_file_off_u64;
-    if dynsym_span % syment != 0 {
-        return Err(LoaderError {});
+    let span_r = dynsym_count.checked_mul(ELF64_SYM_SIZE);
+    if span_r.is_none() {
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
-    let dynsym_count_u64 = dynsym_span / syment;
-    if dynsym_count_u64 == 0 {
-        return Err(LoaderError {});
-    }
-
-    let symtab_off_r = u64_to_usize(symtab_file_off_u64);
-    let span_r = u64_to_usize(dynsym_span);
-    let count_r = u64_to_usize(dynsym_count_u64);
-    if symtab_off_r.is_err() || span_r.is_err() || count_r.is_err() {
-        return Err(LoaderError {});
+    let span = span_r.unwrap();
+    let symtab_off_r = table_file_offset(&phdrs, symtab_vaddr, span as u64);
+    if symtab_off_r.is_err() {
+        return Err(symtab_off_r.unwrap_err());
     }
-
     let symtab_off = symtab_off_r.unwrap();
-    let span = span_r.unwrap();
-    let dynsym_count = count_r.unwrap();
     if ensure_range(bytes.len(), symtab_off, span).is_err() {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
 
     let mut dynsyms: Vec<DynSymbol> = Vec::new();
@@ -1045,11 +1875,11 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     {
         let step = s_i.checked_mul(ELF64_SYM_SIZE);
         if step.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         let base = symtab_off.checked_add(step.unwrap());
         if base.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         let base = base.unwrap();
         let base4 = base.checked_add(4);
@@ -1058,7 +1888,7 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
         let base8 = base.checked_add(8);
         let base16 = base.checked_add(16);
         if base4.is_none() || base5.is_none() || base6.is_none() || base8.is_none() || base16.is_none() {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
         let st_name_r = read_u32_le(bytes, base);
         let st_info_r = read_u8(bytes, base4.unwrap());
@@ -1069,12 +1899,12 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
         if st_name_r.is_err() || st_info_r.is_err() || st_other_r.is_err() || st_shndx_r.is_err()
             || st_value_r.is_err() || st_size_r.is_err()
         {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::Truncated));
         }
 
         let st_name = st_name_r.unwrap();
         if st_name as usize >= dynstr_len {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::BadSymbolTable));
         }
 
         let sym = DynSymbol {
@@ -1108,12 +1938,62 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
         s_i = s_i + 1;
     }
 
+    let hash_table = if gnu_hash_vaddr != 0 {
+        let t = parse_gnu_hash_table(bytes, &phdrs, gnu_hash_vaddr, dynsym_count);
+        if t.is_err() {
+            return Err(t.unwrap_err());
+        }
+        Some(SymbolHashTable::Gnu(t.unwrap()))
+    } else if hash_vaddr != 0 {
+        let t = parse_sysv_hash_table(bytes, &phdrs, hash_vaddr);
+        if t.is_err() {
+            return Err(t.unwrap_err());
+        }
+        Some(SymbolHashTable::Sysv(t.unwrap()))
+    } else {
+        None
+    };
+
+    let versym = if scan.versym.unwrap_or(0) != 0 {
+        let v = parse_versym_table(bytes, &phdrs, scan.versym.unwrap_or(0), dynsym_count);
+        if v.is_err() {
+            return Err(v.unwrap_err());
+        }
+        v.unwrap()
+    } else {
+        Vec::new()
+    };
+    let verdefs_r = parse_verdefs(bytes, &phdrs, scan.verdef.unwrap_or(0), scan.verdefnum.unwrap_or(0), dynstr_len);
+    if verdefs_r.is_err() {
+        return Err(verdefs_r.unwrap_err());
+    }
+    let verdefs = verdefs_r.unwrap();
+    let verneeds_r = parse_verneeds(
+        bytes,
+        &phdrs,
+        scan.verneed.unwrap_or(0),
+        scan.verneednum.unwrap_or(0),
+        dynstr_len,
+    );
+    if verneeds_r.is_err() {
+        return Err(verneeds_r.unwrap_err());
+    }
+    let verneeds = verneeds_r.unwrap();
+
     let rela_vaddr = scan.rela.unwrap_or(0);
-    let relasz_input = scan.relasz.unwrap_or(0);
+    let relasz = scan.relasz.unwrap_or(0);
     let relaent = scan.relaent.unwrap_or(0);
+    let rel_vaddr = scan.rel.unwrap_or(0);
+    let relsz = scan.relsz.unwrap_or(0);
+    let relent = scan.relent.unwrap_or(0);
     let jmprel_vaddr = scan.jmprel.unwrap_or(0);
     let pltrelsz = scan.pltrelsz.unwrap_or(0);
     let pltrel = scan.pltrel.unwrap_or(0);
+    let jmprel_table = if pltrel == DT_REL_TAG {
+        RelocTable::Rel
+    } else {
+        RelocTable::Rela
+    };
     let relr_vaddr = scan.relr.unwrap_or(0);
     let relrsz = scan.relrsz.unwrap_or(0);
     let relrent = scan.relrent.unwrap_or(0);
@@ -1121,12 +2001,20 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     let init_array_sz = scan.init_arraysz.unwrap_or(0);
     let fini_array_vaddr = scan.fini_array.unwrap_or(0);
     let fini_array_sz = scan.fini_arraysz.unwrap_or(0);
+    let preinit_array_vaddr = scan.preinit_array.unwrap_or(0);
+    let preinit_array_sz = scan.preinit_arraysz.unwrap_or(0);
+    let init = scan.init;
+    let fini = scan.fini;
 
-    let relas_r = parse_rela_table(bytes, &phdrs, rela_vaddr, relasz_input);
+    let relas_r = parse_rela_table(bytes, &phdrs, rela_vaddr, relasz, RelocTable::Rela);
     if relas_r.is_err() {
         return Err(relas_r.unwrap_err());
     }
-    let jmprels_r = parse_rela_table(bytes, &phdrs, jmprel_vaddr, pltrelsz);
+    let rels_r = parse_rela_table(bytes, &phdrs, rel_vaddr, relsz, RelocTable::Rel);
+    if rels_r.is_err() {
+        return Err(rels_r.unwrap_err());
+    }
+    let jmprels_r = parse_rela_table(bytes, &phdrs, jmprel_vaddr, pltrelsz, jmprel_table);
     if jmprels_r.is_err() {
         return Err(jmprels_r.unwrap_err());
     }
@@ -1150,35 +2038,84 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
     if fini_array_r.is_err() {
         return Err(fini_array_r.unwrap_err());
     }
+    let preinit_array_r = parse_init_array(bytes, &phdrs, preinit_array_vaddr, preinit_array_sz);
+    if preinit_array_r.is_err() {
+        return Err(preinit_array_r.unwrap_err());
+    }
     let mut relas = relas_r.unwrap();
+    let rels = rels_r.unwrap();
     let relr_relas = relr_relas_r.unwrap();
     let jmprels = jmprels_r.unwrap();
     let init_array = init_array_r.unwrap();
     let fini_array = fini_array_r.unwrap();
+    let preinit_array = preinit_array_r.unwrap();
 
+    let jmprel_ent = if matches!(jmprel_table, RelocTable::Rel) {
+        ELF64_REL_SIZE
+    } else {
+        ELF64_RELA_SIZE
+    };
     let rela_input_bytes = (relas.len() as u64).checked_mul(ELF64_RELA_SIZE as u64);
-    let jmprel_bytes = (jmprels.len() as u64).checked_mul(ELF64_RELA_SIZE as u64);
+    let rel_bytes = (rels.len() as u64).checked_mul(ELF64_REL_SIZE as u64);
+    let jmprel_bytes = (jmprels.len() as u64).checked_mul(jmprel_ent as u64);
     let init_bytes = (init_array.len() as u64).checked_mul(8);
     let fini_bytes = (fini_array.len() as u64).checked_mul(8);
-    if rela_input_bytes.is_none() || jmprel_bytes.is_none() || init_bytes.is_none() || fini_bytes.is_none() {
-        return Err(LoaderError {});
+    let preinit_bytes = (preinit_array.len() as u64).checked_mul(8);
+    if rela_input_bytes.is_none() || rel_bytes.is_none() || jmprel_bytes.is_none() || init_bytes.is_none()
+        || fini_bytes.is_none() || preinit_bytes.is_none()
+    {
+        return Err(parse_error(LoaderErrorReason::Truncated));
     }
-    if rela_input_bytes.unwrap() != relasz_input || jmprel_bytes.unwrap() != pltrelsz || init_bytes.unwrap()
-        != init_array_sz || fini_bytes.unwrap() != fini_array_sz
+    if rela_input_bytes.unwrap() != relasz || rel_bytes.unwrap() != relsz || jmprel_bytes.unwrap()
+        != pltrelsz || init_bytes.unwrap() != init_array_sz || fini_bytes.unwrap() != fini_array_sz
+        || preinit_bytes.unwrap() != preinit_array_sz
     {
-        return Err(LoaderError {});
+        return Err(parse_error(LoaderErrorReason::BadDynamicSection));
+    }
+
+    let ghost n_rela = relas@.len() as int;
+    let ghost n_rel = rels@.len() as int;
+    let mut rel_i: usize = 0;
+    while rel_i < rels.len()
+        invariant
+            rel_i <= rels.len(),
+            n_rel == rels@.len(),
+            relas@.len() == n_rela + rel_i,
+            forall|k: int| 0 <= k < n_rela ==> relas@[k].table == RelocTable::Rela,
+            forall|k: int| 0 <= k < rels@.len() ==> rels@[k].table == RelocTable::Rel,
+            forall|k: int| n_rela <= k < relas@.len() ==> relas@[k].table == RelocTable::Rel,
+        decreases rels.len() - rel_i,
+    {
+        let r = &rels[rel_i];
+        relas.push(RelaEntry { offset: r.offset, info: r.info, addend: r.addend, table: r.table });
+        rel_i = rel_i + 1;
     }
 
     let mut rr_i: usize = 0;
     while rr_i < relr_relas.len()
         invariant
             rr_i <= relr_relas.len(),
+            relas@.len() == n_rela + n_rel + rr_i,
+            forall|k: int| 0 <= k < n_rela ==> relas@[k].table == RelocTable::Rela,
+            forall|k: int| n_rela <= k < n_rela + n_rel ==> relas@[k].table == RelocTable::Rel,
+            forall|k: int|
+                0 <= k < relr_relas@.len() ==> relr_relas@[k].table == RelocTable::Relr && rela_type(relr_relas@[k])
+                    == R_X86_64_RELATIVE,
+            forall|k: int|
+                n_rela + n_rel <= k < relas@.len() ==> relas@[k].table == RelocTable::Relr && rela_type(relas@[k])
+                    == R_X86_64_RELATIVE,
         decreases relr_relas.len() - rr_i,
     {
         let rr = &relr_relas[rr_i];
-        relas.push(RelaEntry { offset: rr.offset, info: R_X86_64_RELATIVE as u64, addend: rr.addend });
+        relas.push(RelaEntry { offset: rr.offset, info: rr.info, addend: rr.addend, table: rr.table });
         rr_i = rr_i + 1;
     }
+    proof {
+        lemma_div_multiples_vanish(n_rela, ELF64_RELA_SIZE as int);
+        lemma_div_multiples_vanish(n_rel, ELF64_REL_SIZE as int);
+        assert(relasz as int / ELF64_RELA_SIZE as int == n_rela);
+        assert(relsz as int / ELF64_REL_SIZE as int == n_rel);
+    }
 
     let mut chk_rela: usize = 0;
     while chk_rela < relas.len()
@@ -1190,9 +2127,13 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
         let info = relas[chk_rela].info;
         let t = (info & 0xffff_ffff) as u32;
         if t != R_X86_64_RELATIVE && t != R_X86_64_JUMP_SLOT && t != R_X86_64_GLOB_DAT
-            && t != R_X86_64_COPY && t != R_X86_64_64
+            && t != R_X86_64_COPY && t != R_X86_64_64 && t != R_X86_64_DTPMOD64
+            && t != R_X86_64_DTPOFF64 && t != R_X86_64_TPOFF64 && t != R_X86_64_IRELATIVE
         {
-            return Err(LoaderError {});
+            return Err(parse_error(LoaderErrorReason::UnsupportedRelocType {
+                reloc_type: t,
+                offset: relas[chk_rela].offset,
+            }));
         }
         proof {
             assert(relas@[chk_rela as int].info == info);
@@ -1206,12 +2147,6 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
         assert(forall|k: int| 0 <= k < relas@.len() ==> supported_reloc_type(rela_type(relas@[k])));
     }
 
-    let relasz_r = (relas.len() as u64).checked_mul(ELF64_RELA_SIZE as u64);
-    if relasz_r.is_none() {
-        return Err(LoaderError {});
-    }
-    let relasz = relasz_r.unwrap();
-
     proof {
         assert(input.bytes@.len() >= ELF64_EHDR_SIZE);
         assert(has_elf_magic(input.bytes@));
@@ -1219,21 +2154,29 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
         assert(phdrs@.len() > 0);
         assert(forall|i: int| 0 <= i < phdrs@.len() ==> valid_phdr(phdrs@[i]));
         assert(exists|i: int| 0 <= i < phdrs@.len() && phdrs@[i].p_type == PT_LOAD);
-        assert(exists|i: int| 0 <= i < phdrs@.len() && phdrs@[i].p_type == PT_DYNAMIC);
+        assert(has_dynamic_segment(phdrs@));
+        assert(tls_phdr_spec(phdrs@, tls));
         assert(strsz > 0);
         assert(syment == ELF64_SYM_SIZE as u64);
         assert(relaent == 0 || relaent == ELF64_RELA_SIZE as u64);
-        assert(pltrel == 0 || pltrel == DT_RELA_TAG);
+        assert(relent == 0 || relent == ELF64_REL_SIZE as u64);
+        assert(pltrel == 0 || pltrel == DT_RELA_TAG || pltrel == DT_REL_TAG);
+        assert(relsz % (ELF64_REL_SIZE as u64) == 0);
         assert(relasz % (ELF64_RELA_SIZE as u64) == 0);
-        assert(pltrelsz % (ELF64_RELA_SIZE as u64) == 0);
         assert(init_array_sz % 8 == 0);
         assert(fini_array_sz % 8 == 0);
+        assert(preinit_array_sz % 8 == 0);
         assert(dynstr@.len() as u64 == strsz);
         assert(dynsyms@.len() > 0);
-        assert(relas@.len() as u64 * (ELF64_RELA_SIZE as u64) == relasz);
-        assert(jmprels@.len() as u64 * (ELF64_RELA_SIZE as u64) == pltrelsz);
+        assert(versym@.len() == 0 || versym@.len() == dynsyms@.len());
+        assert(forall|i: int| 0 <= i < verdefs@.len() ==> offset_in_dynstr(verdefs@[i].name_offset, dynstr@));
+        assert(forall|i: int|
+            0 <= i < verneeds@.len() ==> offset_in_dynstr(verneeds@[i].name_offset, dynstr@)
+                && offset_in_dynstr(verneeds@[i].file_offset, dynstr@));
+        assert(jmprels@.len() as u64 * (jmprel_ent as u64) == pltrelsz);
         assert(init_array@.len() as u64 * 8 == init_array_sz);
         assert(fini_array@.len() as u64 * 8 == fini_array_sz);
+        assert(preinit_array@.len() as u64 * 8 == preinit_array_sz);
         assert(forall|i: int|
             0 <= i < needed_offsets@.len() ==> offset_in_dynstr(needed_offsets@[i], dynstr@));
         assert(match soname_offset {
@@ -1248,21 +2191,40 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
         )));
     }
 
+    let pie = match scan.flags_1 {
+        Some(f) => f & DF_1_PIE != 0,
+        None => false,
+    };
+    // With a dynamic section only a static-pie starts without an interpreter.
+    let static_exec = !saw_interp && e_type == ET_DYN && pie && needed_offsets.len() == 0;
+    if static_exec && (!relative_rela_only(&relas) || !relative_rela_only(&jmprels)) {
+        return Err(parse_error(LoaderErrorReason::BadRelocationTable));
+    }
+
     assert(e_type == ET_EXEC || e_type == ET_DYN);
     let parsed = ParsedObject {
         input_name: clone_u8_vec(&input.name),
         file_bytes: clone_u8_vec(&input.bytes),
+        load_base: input.load_base,
         elf_type: e_type,
         entry: e_entry,
+        static_exec,
         phdrs,
+        tls,
+        relro: relro_phdr,
         dynamic: DynamicInfo {
             strtab_vaddr,
             strsz,
             symtab_vaddr,
             syment,
+            hash_vaddr,
+            gnu_hash_vaddr,
             rela_vaddr,
             relasz,
             relaent,
+            rel_vaddr,
+            relsz,
+            relent,
             jmprel_vaddr,
             pltrelsz,
             pltrel,
@@ -1270,19 +2232,30 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
             init_array_sz,
             fini_array_vaddr,
             fini_array_sz,
+            preinit_array_vaddr,
+            preinit_array_sz,
         },
         needed_offsets,
         soname_offset,
         dynstr,
         dynsyms,
+        dynsym_count_source,
+        hash_table,
+        versym,
+        verdefs,
+        verneeds,
         relas,
         jmprels,
+        init,
+        fini,
+        preinit_array,
         init_array,
         fini_array,
     };
     proof {
         assert(parsed.input_name@ == input.name@);
         assert(parsed.file_bytes@ == input.bytes@);
+        assert(parsed.load_base == input.load_base);
         assert(input.bytes@.len() >= ELF64_EHDR_SIZE);
         assert(has_elf_magic(input.bytes@));
         assert(has_supported_ident(input.bytes@));
@@ -1290,21 +2263,43 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
         assert(parsed.phdrs@.len() > 0);
         assert(forall|i: int| 0 <= i < parsed.phdrs@.len() ==> valid_phdr(parsed.phdrs@[i]));
         assert(exists|i: int| 0 <= i < parsed.phdrs@.len() && parsed.phdrs@[i].p_type == PT_LOAD);
-        assert(exists|i: int| 0 <= i < parsed.phdrs@.len() && parsed.phdrs@[i].p_type == PT_DYNAMIC);
+        assert(has_dynamic_segment(parsed.phdrs@));
+        assert(tls_phdr_spec(parsed.phdrs@, parsed.tls));
         assert(parsed.dynamic.strsz > 0);
         assert(parsed.dynamic.syment == ELF64_SYM_SIZE as u64);
         assert(parsed.dynamic.relaent == 0 || parsed.dynamic.relaent == ELF64_RELA_SIZE as u64);
-        assert(parsed.dynamic.pltrel == 0 || parsed.dynamic.pltrel == DT_RELA_TAG);
+        assert(parsed.dynamic.relent == 0 || parsed.dynamic.relent == ELF64_REL_SIZE as u64);
+        assert(parsed.dynamic.pltrel == 0 || parsed.dynamic.pltrel == DT_RELA_TAG || parsed.dynamic.pltrel
+            == DT_REL_TAG);
+        assert(parsed.dynamic.relsz % (ELF64_REL_SIZE as u64) == 0);
         assert(parsed.dynamic.relasz % (ELF64_RELA_SIZE as u64) == 0);
-        assert(parsed.dynamic.pltrelsz % (ELF64_RELA_SIZE as u64) == 0);
         assert(parsed.dynamic.init_array_sz % 8 == 0);
         assert(parsed.dynamic.fini_array_sz % 8 == 0);
+        assert(parsed.dynamic.preinit_array_sz % 8 == 0);
         assert(parsed.dynstr@.len() as u64 == parsed.dynamic.strsz);
         assert(parsed.dynsyms@.len() > 0);
-        assert(parsed.relas@.len() as u64 * (ELF64_RELA_SIZE as u64) == parsed.dynamic.relasz);
-        assert(parsed.jmprels@.len() as u64 * (ELF64_RELA_SIZE as u64) == parsed.dynamic.pltrelsz);
+        assert(dynsym_count_source_spec(
+            parsed.file_bytes@,
+            parsed.phdrs@,
+            parsed.dynamic,
+            parsed.dynsym_count_source,
+            parsed.dynsyms@.len() as int,
+        ));
+        assert(parsed.versym@.len() == 0 || parsed.versym@.len() == parsed.dynsyms@.len());
+        assert(forall|i: int|
+            0 <= i < parsed.verdefs@.len() ==> offset_in_dynstr(parsed.verdefs@[i].name_offset, parsed.dynstr@));
+        assert(forall|i: int|
+            0 <= i < parsed.verneeds@.len() ==> offset_in_dynstr(parsed.verneeds@[i].name_offset, parsed.dynstr@)
+                && offset_in_dynstr(parsed.verneeds@[i].file_offset, parsed.dynstr@));
+        assert(reloc_tables_spec(parsed.relas@, parsed.dynamic));
+        assert(jmprel_table(parsed.dynamic) == jmprel_table);
+        assert(forall|i: int|
+            0 <= i < parsed.jmprels@.len() ==> parsed.jmprels@[i].table == jmprel_table(parsed.dynamic));
+        assert(parsed.jmprels@.len() as u64 * reloc_entry_size(jmprel_table(parsed.dynamic))
+            == parsed.dynamic.pltrelsz);
         assert(parsed.init_array@.len() as u64 * 8 == parsed.dynamic.init_array_sz);
         assert(parsed.fini_array@.len() as u64 * 8 == parsed.dynamic.fini_array_sz);
+        assert(parsed.preinit_array@.len() as u64 * 8 == parsed.dynamic.preinit_array_sz);
         assert(forall|i: int|
             0 <= i < parsed.needed_offsets@.len() ==> offset_in_dynstr(parsed.needed_offsets@[i], parsed.dynstr@));
         assert(match parsed.soname_offset {
@@ -1317,6 +2312,7 @@ fn parse_object_with_code(input: LoaderObject) -> (out: Result<ParsedObject, Loa
             0 <= i < parsed.relas@.len() ==> supported_reloc_type(rela_type(parsed.relas@[i])));
         assert(forall|i: int|
             0 <= i < parsed.jmprels@.len() ==> supported_reloc_type(rela_type(parsed.jmprels@[i])));
+        assert(static_exec_spec(parsed));
     }
     Ok(parsed)
 }
@@ -1325,10 +2321,11 @@ pub fn parse_object(input: LoaderObject) -> (out: Result<ParsedObject, LoaderErr
     ensures
         out.is_ok() ==> parse_object_spec(input, out.unwrap()),
 {
+    let name = clone_u8_vec(&input.name);
     let parsed = parse_object_with_code(input);
     match parsed {
         Ok(p) => Ok(p),
-        Err(e) => Err(e),
+        Err(e) => Err(LoaderError { stage: e.stage, object_name: name, reason: e.reason }),
     }
 }
 
@@ -1349,6 +2346,7 @@ pub fn parse_stage(input: LoaderInput) -> (out: Result<Vec<ParsedObject>, Loader
         let cur = LoaderObject {
             name: clone_u8_vec(&input.objects[i].name),
             bytes: clone_u8_vec(&input.objects[i].bytes),
+            load_base: input.objects[i].load_base,
         };
         let one = parse_object(cur);
         match one {
@@ -1356,6 +2354,7 @@ pub fn parse_stage(input: LoaderInput) -> (out: Result<Vec<ParsedObject>, Loader
                 proof {
                     assert(cur.name@ == input.objects@[i as int].name@);
                     assert(cur.bytes@ == input.objects@[i as int].bytes@);
+                    assert(cur.load_base == input.objects@[i as int].load_base);
                     assert(parse_object_spec(input.objects@[i as int], obj));
                     assert forall|k: int| 0 <= k < i + 1 implies parse_object_spec(input.objects@[k],
                         parsed@.push(obj)[k]) by {
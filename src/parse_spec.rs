@@ -6,7 +6,8 @@ verus! {
 
 pub open spec fn supported_reloc_type(t: u32) -> bool {
     t == R_X86_64_RELATIVE || t == R_X86_64_JUMP_SLOT || t == R_X86_64_GLOB_DAT
-        || t == R_X86_64_COPY || t == R_X86_64_64
+        || t == R_X86_64_COPY || t == R_X86_64_64 || t == R_X86_64_DTPMOD64
+        || t == R_X86_64_DTPOFF64 || t == R_X86_64_TPOFF64 || t == R_X86_64_IRELATIVE
 }
 
 pub open spec fn rela_type(r: RelaEntry) -> u32 {
@@ -32,27 +33,53 @@ pub open spec fn parse_object_spec(input: LoaderObject, parsed: ParsedObject) ->
     &&& input.bytes@.len() >= ELF64_EHDR_SIZE
     &&& parsed.input_name@ == input.name@
     &&& parsed.file_bytes@ == input.bytes@
+    &&& parsed.load_base == input.load_base
     &&& has_elf_magic(input.bytes@)
     &&& has_supported_ident(input.bytes@)
     &&& (parsed.elf_type == ET_EXEC || parsed.elf_type == ET_DYN)
     &&& parsed.phdrs@.len() > 0
     &&& forall|i: int| 0 <= i < parsed.phdrs@.len() ==> valid_phdr(parsed.phdrs@[i])
     &&& exists|i: int| 0 <= i < parsed.phdrs@.len() && parsed.phdrs@[i].p_type == PT_LOAD
-    &&& exists|i: int| 0 <= i < parsed.phdrs@.len() && parsed.phdrs@[i].p_type == PT_DYNAMIC
-    &&& parsed.dynamic.strsz > 0
-    &&& parsed.dynamic.syment == ELF64_SYM_SIZE as u64
+    &&& tls_phdr_spec(parsed.phdrs@, parsed.tls)
+    &&& match parsed.relro {
+        Some(ph) => ph.p_type == PT_GNU_RELRO,
+        None => true,
+    }
+    &&& if has_dynamic_segment(parsed.phdrs@) {
+        &&& parsed.dynamic.strsz > 0
+        &&& parsed.dynamic.syment == ELF64_SYM_SIZE as u64
+        &&& parsed.dynsyms@.len() > 0
+    } else {
+        static_object_spec(parsed)
+    }
     &&& parsed.dynamic.relaent == 0 || parsed.dynamic.relaent == ELF64_RELA_SIZE as u64
-    &&& parsed.dynamic.pltrel == 0 || parsed.dynamic.pltrel == DT_RELA_TAG
+    &&& parsed.dynamic.relent == 0 || parsed.dynamic.relent == ELF64_REL_SIZE as u64
+    &&& parsed.dynamic.pltrel == 0 || parsed.dynamic.pltrel == DT_RELA_TAG || parsed.dynamic.pltrel == DT_REL_TAG
     &&& parsed.dynamic.relasz % (ELF64_RELA_SIZE as u64) == 0
-    &&& parsed.dynamic.pltrelsz % (ELF64_RELA_SIZE as u64) == 0
+    &&& parsed.dynamic.relsz % (ELF64_REL_SIZE as u64) == 0
     &&& parsed.dynamic.init_array_sz % 8 == 0
     &&& parsed.dynamic.fini_array_sz % 8 == 0
+    &&& parsed.dynamic.preinit_array_sz % 8 == 0
     &&& parsed.dynstr@.len() as u64 == parsed.dynamic.strsz
-    &&& parsed.dynsyms@.len() > 0
-    &&& parsed.relas@.len() as u64 * (ELF64_RELA_SIZE as u64) == parsed.dynamic.relasz
-    &&& parsed.jmprels@.len() as u64 * (ELF64_RELA_SIZE as u64) == parsed.dynamic.pltrelsz
+    &&& dynsym_count_source_spec(
+        parsed.file_bytes@,
+        parsed.phdrs@,
+        parsed.dynamic,
+        parsed.dynsym_count_source,
+        parsed.dynsyms@.len() as int,
+    )
+    &&& (parsed.versym@.len() == 0 || parsed.versym@.len() == parsed.dynsyms@.len())
+    &&& forall|i: int|
+        0 <= i < parsed.verdefs@.len() ==> offset_in_dynstr(parsed.verdefs@[i].name_offset, parsed.dynstr@)
+    &&& forall|i: int|
+        0 <= i < parsed.verneeds@.len() ==> offset_in_dynstr(parsed.verneeds@[i].name_offset, parsed.dynstr@)
+            && offset_in_dynstr(parsed.verneeds@[i].file_offset, parsed.dynstr@)
+    &&& reloc_tables_spec(parsed.relas@, parsed.dynamic)
+    &&& forall|i: int| 0 <= i < parsed.jmprels@.len() ==> parsed.jmprels@[i].table == jmprel_table(parsed.dynamic)
+    &&& parsed.jmprels@.len() as u64 * reloc_entry_size(jmprel_table(parsed.dynamic)) == parsed.dynamic.pltrelsz
     &&& parsed.init_array@.len() as u64 * 8 == parsed.dynamic.init_array_sz
     &&& parsed.fini_array@.len() as u64 * 8 == parsed.dynamic.fini_array_sz
+    &&& parsed.preinit_array@.len() as u64 * 8 == parsed.dynamic.preinit_array_sz
     &&& forall|i: int|
         0 <= i < parsed.needed_offsets@.len() ==> offset_in_dynstr(parsed.needed_offsets@[i], parsed.dynstr@)
     &&& match parsed.soname_offset {
@@ -65,15 +92,179 @@ pub open spec fn parse_object_spec(input: LoaderObject, parsed: ParsedObject) ->
         0 <= i < parsed.relas@.len() ==> supported_reloc_type(rela_type(parsed.relas@[i]))
     &&& forall|i: int|
         0 <= i < parsed.jmprels@.len() ==> supported_reloc_type(rela_type(parsed.jmprels@[i]))
+    &&& static_exec_spec(parsed)
+}
+
+pub open spec fn reloc_entry_size(table: RelocTable) -> u64 {
+    if table == RelocTable::Rel {
+        ELF64_REL_SIZE as u64
+    } else {
+        ELF64_RELA_SIZE as u64
+    }
+}
+
+pub open spec fn jmprel_table(d: DynamicInfo) -> RelocTable {
+    if d.pltrel == DT_REL_TAG {
+        RelocTable::Rel
+    } else {
+        RelocTable::Rela
+    }
+}
+
+// `relas` holds the DT_RELA entries, then the DT_REL entries, then the
+// RELATIVE entries decoded from DT_RELR, each tagged with its table, so the
+// first two groups fill DT_RELASZ and DT_RELSZ exactly.
+pub open spec fn reloc_tables_spec(relas: Seq<RelaEntry>, d: DynamicInfo) -> bool {
+    let n_rela = d.relasz as int / ELF64_RELA_SIZE as int;
+    let n_rel = d.relsz as int / ELF64_REL_SIZE as int;
+    &&& n_rela + n_rel <= relas.len()
+    &&& forall|i: int| 0 <= i < n_rela ==> relas[i].table == RelocTable::Rela
+    &&& forall|i: int| n_rela <= i < n_rela + n_rel ==> relas[i].table == RelocTable::Rel
+    &&& forall|i: int|
+        n_rela + n_rel <= i < relas.len() ==> relas[i].table == RelocTable::Relr && rela_type(relas[i])
+            == R_X86_64_RELATIVE
+}
+
+pub open spec fn has_dynamic_segment(phdrs: Seq<ProgramHeader>) -> bool {
+    exists|i: int| 0 <= i < phdrs.len() && phdrs[i].p_type == PT_DYNAMIC
+}
+
+// A static executable has no dynamic section: it needs no libraries, exports
+// nothing, carries no relocations for the loader, and runs its own
+// constructors from its entry point.
+pub open spec fn static_object_spec(parsed: ParsedObject) -> bool {
+    &&& parsed.dynamic.strsz == 0
+    &&& parsed.dynamic.hash_vaddr == 0
+    &&& parsed.dynamic.gnu_hash_vaddr == 0
+    &&& parsed.needed_offsets@.len() == 0
+    &&& parsed.soname_offset.is_none()
+    &&& parsed.dynsyms@.len() == 0
+    &&& parsed.hash_table.is_none()
+    &&& parsed.relas@.len() == 0
+    &&& parsed.jmprels@.len() == 0
+    &&& parsed.init.is_none()
+    &&& parsed.fini.is_none()
+    &&& parsed.preinit_array@.len() == 0
+    &&& parsed.init_array@.len() == 0
+    &&& parsed.fini_array@.len() == 0
+}
+
+// A `static_exec` object with a dynamic section is a static-pie: it needs no
+// libraries, and its startup code stores `base + addend` for each RELA
+// `RELATIVE` entry, which rewrites the value the loader planned. REL and
+// RELR entries are added to in place and would be relocated twice.
+pub open spec fn static_exec_spec(parsed: ParsedObject) -> bool {
+    parsed.static_exec && has_dynamic_segment(parsed.phdrs@) ==> {
+        &&& parsed.elf_type == ET_DYN
+        &&& parsed.needed_offsets@.len() == 0
+        &&& forall|i: int|
+            0 <= i < parsed.relas@.len() ==> parsed.relas@[i].table == RelocTable::Rela && rela_type(
+                parsed.relas@[i],
+            ) == R_X86_64_RELATIVE
+        &&& forall|i: int|
+            0 <= i < parsed.jmprels@.len() ==> parsed.jmprels@[i].table == RelocTable::Rela && rela_type(
+                parsed.jmprels@[i],
+            ) == R_X86_64_RELATIVE
+    }
 }
 
 pub open spec fn offset_in_dynstr(off: u32, dynstr: Seq<u8>) -> bool {
     (off as int) < dynstr.len()
 }
 
+pub open spec fn u32_le(bytes: Seq<u8>, off: int) -> u32 {
+    (bytes[off] as u32) | ((bytes[off + 1] as u32) << 8) | ((bytes[off + 2] as u32) << 16) | ((bytes[off
+        + 3] as u32) << 24)
+}
+
+// `off` is where the `size` bytes at `vaddr` sit in the file, inside the file
+// image of one PT_LOAD segment.
+pub open spec fn vaddr_in_file(phdrs: Seq<ProgramHeader>, vaddr: u64, size: u64, off: int) -> bool {
+    exists|i: int|
+        0 <= i < phdrs.len() && phdrs[i].p_type == PT_LOAD && phdrs[i].p_vaddr <= vaddr && vaddr + size
+            <= phdrs[i].p_vaddr + phdrs[i].p_filesz && off == phdrs[i].p_offset + (vaddr - phdrs[i].p_vaddr)
+}
+
+// DT_GNU_HASH header at file offset `off`: nbuckets, symoffset, bloom_size,
+// bloom_shift, then the bloom words, the buckets and the chains.
+pub open spec fn gnu_nbuckets(bytes: Seq<u8>, off: int) -> int {
+    u32_le(bytes, off) as int
+}
+
+pub open spec fn gnu_symoffset(bytes: Seq<u8>, off: int) -> int {
+    u32_le(bytes, off + 4) as int
+}
+
+pub open spec fn gnu_buckets_off(bytes: Seq<u8>, off: int) -> int {
+    off + 16 + 8 * (u32_le(bytes, off + 8) as int)
+}
+
+pub open spec fn gnu_bucket(bytes: Seq<u8>, off: int, b: int) -> int {
+    u32_le(bytes, gnu_buckets_off(bytes, off) + 4 * b) as int
+}
+
+pub open spec fn gnu_chain_ends(bytes: Seq<u8>, off: int, idx: int) -> bool {
+    let chains_off = gnu_buckets_off(bytes, off) + 4 * gnu_nbuckets(bytes, off);
+    u32_le(bytes, chains_off + 4 * (idx - gnu_symoffset(bytes, off))) & 1 != 0
+}
+
+pub open spec fn gnu_max_bucket(bytes: Seq<u8>, off: int, m: int) -> bool {
+    &&& forall|b: int| 0 <= b < gnu_nbuckets(bytes, off) ==> gnu_bucket(bytes, off, b) <= m
+    &&& m == 0 || exists|b: int| 0 <= b < gnu_nbuckets(bytes, off) && gnu_bucket(bytes, off, b) == m
+}
+
+// The highest bucket starts the chain holding the highest symbol index; the
+// last symbol is the first entry from there with the end bit set.
+pub open spec fn gnu_hash_count(bytes: Seq<u8>, off: int, count: int) -> bool {
+    exists|m: int| {
+        &&& gnu_max_bucket(bytes, off, m)
+        &&& gnu_symoffset(bytes, off) <= m < count
+        &&& gnu_chain_ends(bytes, off, count - 1)
+        &&& forall|i: int| m <= i < count - 1 ==> !gnu_chain_ends(bytes, off, i)
+    }
+}
+
+// No bucket reaches `symoffset`: the table hashes no symbols, and `symoffset`
+// need not be the symbol count (ld.bfd leaves it at 1 with undefined imports
+// above it).
+pub open spec fn gnu_hash_empty(bytes: Seq<u8>, off: int) -> bool {
+    forall|b: int| 0 <= b < gnu_nbuckets(bytes, off) ==> gnu_bucket(bytes, off, b) < gnu_symoffset(bytes, off)
+}
+
+pub open spec fn dynsym_count_source_spec(
+    bytes: Seq<u8>,
+    phdrs: Seq<ProgramHeader>,
+    d: DynamicInfo,
+    src: SymbolCountSource,
+    count: int,
+) -> bool {
+    match src {
+        SymbolCountSource::Hash => d.hash_vaddr != 0 && exists|off: int|
+            vaddr_in_file(phdrs, d.hash_vaddr, 8, off) && count == u32_le(bytes, off + 4) as int,
+        SymbolCountSource::GnuHash => d.hash_vaddr == 0 && d.gnu_hash_vaddr != 0 && exists|off: int|
+            vaddr_in_file(phdrs, d.gnu_hash_vaddr, 16, off) && gnu_hash_count(bytes, off, count),
+        SymbolCountSource::SectionLayout => d.hash_vaddr == 0 && (d.gnu_hash_vaddr == 0 || exists|off: int|
+            vaddr_in_file(phdrs, d.gnu_hash_vaddr, 16, off) && gnu_hash_empty(bytes, off)),
+    }
+}
+
+// `p_align` of 0 or 1 means no constraint; otherwise a loadable segment's file
+// offset and address must agree modulo `p_align`, as the kernel maps it.
+pub open spec fn phdr_align_congruent(ph: ProgramHeader) -> bool {
+    ph.p_type == PT_LOAD && ph.p_align > 1 ==> ph.p_offset % ph.p_align == ph.p_vaddr % ph.p_align
+}
+
 pub open spec fn valid_phdr(ph: ProgramHeader) -> bool {
-    &&& (ph.p_type == PT_LOAD || ph.p_type == PT_DYNAMIC)
+    &&& (ph.p_type == PT_LOAD || ph.p_type == PT_DYNAMIC || ph.p_type == PT_TLS)
     &&& ph.p_filesz <= ph.p_memsz
+    &&& phdr_align_congruent(ph)
+}
+
+pub open spec fn tls_phdr_spec(phdrs: Seq<ProgramHeader>, tls: Option<ProgramHeader>) -> bool {
+    match tls {
+        Some(ph) => ph.p_type == PT_TLS && exists|i: int| 0 <= i < phdrs.len() && phdrs[i] == ph,
+        None => forall|i: int| 0 <= i < phdrs.len() ==> phdrs[i].p_type != PT_TLS,
+    }
 }
 
 pub open spec fn parse_stage_spec(input: LoaderInput, parsed: Seq<ParsedObject>) -> bool {
@@ -29,7 +29,7 @@ pub open spec fn rr_provider_value(parsed: Seq<ParsedObject>, order: Seq<usize>,
     match (rr.provider_object, rr.provider_symbol) {
         (Some(po), Some(ps)) => {
             if (po as int) < parsed.len() && (ps as int) < parsed[po as int].dynsyms@.len() {
-                add_u64_or_zero(object_base(parsed, order, po as int), parsed[po as int].dynsyms@[ps as int].st_value)
+                symbol_address(parsed, order, po as int, parsed[po as int].dynsyms@[ps as int])
             } else {
                 0
             }
@@ -48,7 +48,10 @@ pub open spec fn write_matches_r_x86_64_relative_entry(
     &&& 0 <= obj_idx < parsed.len()
     &&& rela_type_of(rela) == R_X86_64_RELATIVE
     &&& w.write_addr == add_u64_or_zero(object_base(parsed, order, obj_idx), rela.offset)
-    &&& w.value == add_i64_or_zero(object_base(parsed, order, obj_idx), rela.addend)
+    &&& w.value == add_i64_or_zero(
+        object_base(parsed, order, obj_idx),
+        rela_addend(parsed[obj_idx], rela),
+    )
     &&& w.reloc_type == R_X86_64_RELATIVE
 }
 
@@ -81,9 +84,78 @@ pub open spec fn write_matches_any_r_x86_64_relative(parsed: Seq<ParsedObject>,
     }
 }
 
+pub open spec fn write_matches_local_tls_entry(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    tls: TlsLayout,
+    obj_idx: int,
+    rela: RelaEntry,
+    w: RelocWrite,
+) -> bool {
+    &&& 0 <= obj_idx < parsed.len()
+    &&& is_tls_reloc_type(rela_type_of(rela))
+    &&& rela_sym_of(rela) == 0
+    &&& w == local_tls_write_for_entry(parsed, order, tls, obj_idx, rela)
+}
+
+pub open spec fn write_matches_any_local_tls(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    tls: TlsLayout,
+    w: RelocWrite,
+) -> bool {
+    exists|p: int| {
+        &&& 0 <= p < order.len()
+        &&& (order[p] as int) < parsed.len()
+        &&& (
+            exists|i: int| {
+                0 <= i < parsed[order[p] as int].relas@.len() && write_matches_local_tls_entry(
+                    parsed,
+                    order,
+                    tls,
+                    order[p] as int,
+                    parsed[order[p] as int].relas@[i],
+                    w,
+                )
+            }
+        )
+        || (
+            exists|i: int| {
+                0 <= i < parsed[order[p] as int].jmprels@.len() && write_matches_local_tls_entry(
+                    parsed,
+                    order,
+                    tls,
+                    order[p] as int,
+                    parsed[order[p] as int].jmprels@[i],
+                    w,
+                )
+            }
+        )
+    }
+}
+
+pub open spec fn write_matches_tls_from_rr(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    tls: TlsLayout,
+    rr: ResolvedReloc,
+    w: RelocWrite,
+) -> bool {
+    let req = rr.requester as int;
+    &&& 0 <= req < parsed.len()
+    &&& match rr_reloc_entry(parsed, rr) {
+        Some(rel) => {
+            &&& is_tls_reloc_type(rela_type_of(rel))
+            &&& w == tls_symbol_write_for_rr(parsed, order, tls, rr, rel)
+        }
+        None => false,
+    }
+}
+
 pub open spec fn write_matches_resolved_symbol_reloc(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    tls: TlsLayout,
     rr: ResolvedReloc,
     w: RelocWrite,
 ) -> bool {
@@ -91,6 +163,7 @@ pub open spec fn write_matches_resolved_symbol_reloc(
         || write_matches_r_x86_64_glob_dat_from_rr(parsed, order, rr, w)
         || write_matches_r_x86_64_64_from_rr(parsed, order, rr, w)
         || write_matches_r_x86_64_copy_from_rr(parsed, order, rr, w)
+        || write_matches_tls_from_rr(parsed, order, tls, rr, w)
 }
 
 pub open spec fn write_matches_r_x86_64_jump_slot_from_rr(
@@ -143,7 +216,10 @@ pub open spec fn write_matches_r_x86_64_64_from_rr(
         Some(rel) => {
             &&& rela_type_of(rel) == R_X86_64_64
             &&& w.write_addr == add_u64_or_zero(object_base(parsed, order, req), rel.offset)
-            &&& w.value == add_i64_or_zero(rr_provider_value(parsed, order, rr), rel.addend)
+            &&& w.value == add_i64_or_zero(
+                rr_provider_value(parsed, order, rr),
+                rela_addend(parsed[req], rel),
+            )
             &&& w.reloc_type == R_X86_64_64
         }
         None => false,
@@ -171,10 +247,12 @@ pub open spec fn write_matches_r_x86_64_copy_from_rr(
 pub open spec fn write_matches_supported_relocation(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    tls: TlsLayout,
     resolved: ResolutionResult,
     w: RelocWrite,
 ) -> bool {
     write_matches_any_r_x86_64_relative(parsed, order, w)
+        || write_matches_any_local_tls(parsed, order, tls, w)
         || exists|i: int|
             0 <= i < resolved.resolved_relocs@.len() && write_matches_r_x86_64_jump_slot_from_rr(
                 parsed,
@@ -203,17 +281,27 @@ pub open spec fn write_matches_supported_relocation(
                 resolved.resolved_relocs@[i],
                 w,
             )
+        || exists|i: int|
+            0 <= i < resolved.resolved_relocs@.len() && write_matches_tls_from_rr(
+                parsed,
+                order,
+                tls,
+                resolved.resolved_relocs@[i],
+                w,
+            )
 }
 
 pub open spec fn reloc_writes_sound(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    tls: TlsLayout,
     resolved: ResolutionResult,
     reloc_writes: Seq<RelocWrite>,
 ) -> bool {
     forall|i: int| 0 <= i < reloc_writes.len() ==> write_matches_supported_relocation(
         parsed,
         order,
+        tls,
         resolved,
         reloc_writes[i],
     )
@@ -227,95 +315,91 @@ pub open spec fn u64_le_byte(value: u64, idx: int) -> u8
     ((value >> shift) & 0xffu64) as u8
 }
 
-pub open spec fn patch_u64_le_bytes(bytes: Seq<u8>, off: nat, value: u64) -> Seq<u8> {
-    if off + 8 <= bytes.len() {
-        Seq::new(
-            bytes.len(),
-            |i: int|
-                if off <= i && i < off + 8 {
-                    u64_le_byte(value, i - off)
-                } else {
-                    bytes[i]
-                },
-        )
+// The patch a write leaves on `plan`, if the whole word lies inside it.
+pub open spec fn patch_for_write(plan: MmapPlan, write: RelocWrite) -> Option<PlanPatch> {
+    if write.write_addr >= plan.start && (write.write_addr - plan.start) as int + 8 <= plan.len as int {
+        Some(PlanPatch { offset: (write.write_addr - plan.start) as usize, value: write.value })
     } else {
-        bytes
+        None
     }
 }
 
-pub open spec fn apply_write_to_plan_bytes(
-    plan: MmapPlan,
-    cur_bytes: Seq<u8>,
-    write: RelocWrite,
-) -> Seq<u8> {
-    if write.write_addr >= plan.start
-        && write.write_addr - plan.start <= usize::MAX as u64
-    {
-        patch_u64_le_bytes(cur_bytes, (write.write_addr - plan.start) as nat, write.value)
+pub open spec fn plan_patches_prefix(plan: MmapPlan, writes: Seq<RelocWrite>, n: nat) -> Seq<PlanPatch>
+    decreases n,
+{
+    if n == 0 {
+        Seq::empty()
     } else {
-        cur_bytes
+        let prev = plan_patches_prefix(plan, writes, (n - 1) as nat);
+        match patch_for_write(plan, writes[(n - 1) as int]) {
+            Some(p) => prev.push(p),
+            None => prev,
+        }
     }
 }
 
-pub open spec fn initial_plan_bytes(plans: Seq<MmapPlan>) -> Seq<Seq<u8>> {
-    Seq::new(plans.len(), |i: int| plans[i].bytes@)
+pub open spec fn plan_patches(plan: MmapPlan, writes: Seq<RelocWrite>) -> Seq<PlanPatch> {
+    plan_patches_prefix(plan, writes, writes.len() as nat)
 }
 
-pub open spec fn apply_write_to_all_plan_bytes(
-    plans: Seq<MmapPlan>,
-    cur: Seq<Seq<u8>>,
-    write: RelocWrite,
-) -> Seq<Seq<u8>>
-    recommends
-        cur.len() == plans.len(),
+// Byte `k` of a region after its first `n` patches; later patches win.
+pub open spec fn patched_byte(plan: MmapPlan, file: Seq<u8>, n: nat, k: int) -> u8
+    decreases n,
 {
-    Seq::new(
-        plans.len(),
-        |i: int| apply_write_to_plan_bytes(plans[i], cur[i], write),
-    )
+    if n == 0 || n > plan.patches@.len() {
+        plan_initial_byte(plan, file, k)
+    } else {
+        let p = plan.patches@[(n - 1) as int];
+        if p.offset <= k < p.offset + 8 {
+            u64_le_byte(p.value, k - p.offset)
+        } else {
+            patched_byte(plan, file, (n - 1) as nat, k)
+        }
+    }
 }
 
-pub open spec fn apply_write_to_plans_current_bytes(
-    plans: Seq<MmapPlan>,
-    write: RelocWrite,
-) -> Seq<Seq<u8>> {
-    apply_write_to_all_plan_bytes(plans, initial_plan_bytes(plans), write)
+pub open spec fn plan_byte(plan: MmapPlan, file: Seq<u8>, k: int) -> u8 {
+    patched_byte(plan, file, plan.patches@.len(), k)
 }
 
-pub open spec fn apply_reloc_writes_prefix_bytes(
-    plans: Seq<MmapPlan>,
+// Contents of a region once mapped: file bytes, zero fill, overlay, then patches.
+pub open spec fn plan_image(plan: MmapPlan, file: Seq<u8>) -> Seq<u8> {
+    Seq::new(plan.len as nat, |k: int| plan_byte(plan, file, k))
+}
+
+// Which writes land in a region only depends on where it starts and how long
+// it is, so the patches are fixed before any protection (including the
+// read-only RELRO pieces) takes effect at runtime.
+pub proof fn lemma_plan_patches_independent_of_prot(
+    plan: MmapPlan,
+    other: MmapPlan,
     writes: Seq<RelocWrite>,
     n: nat,
-) -> Seq<Seq<u8>>
+)
+    requires
+        plan.start == other.start,
+        plan.len == other.len,
+    ensures
+        plan_patches_prefix(plan, writes, n) == plan_patches_prefix(other, writes, n),
     decreases n,
 {
-    if n == 0 {
-        initial_plan_bytes(plans)
-    } else {
-        apply_write_to_all_plan_bytes(
-            plans,
-            apply_reloc_writes_prefix_bytes(plans, writes, (n - 1) as nat),
-            writes[(n - 1) as int],
-        )
+    if n > 0 {
+        lemma_plan_patches_independent_of_prot(plan, other, writes, (n - 1) as nat);
     }
 }
 
-pub open spec fn apply_reloc_writes_bytes(plans: Seq<MmapPlan>, writes: Seq<RelocWrite>) -> Seq<Seq<u8>> {
-    apply_reloc_writes_prefix_bytes(plans, writes, writes.len() as nat)
-}
-
 pub open spec fn relocate_apply_stage_spec(
     in_plan: RelocatePlanOutput,
     out_plan: RelocateApplyOutput,
 ) -> bool {
     &&& out_plan.mmap_plans@.len() == in_plan.mmap_plans@.len()
     &&& forall|i: int|
-        0 <= i < out_plan.mmap_plans@.len() ==> out_plan.mmap_plans@[i].bytes@ == apply_reloc_writes_bytes(
-            in_plan.mmap_plans@,
-            in_plan.reloc_plan@,
-        )[i]
+        0 <= i < out_plan.mmap_plans@.len() ==> out_plan.mmap_plans@[i].patches@ == in_plan.mmap_plans@[i].patches@
+            + plan_patches(in_plan.mmap_plans@[i], in_plan.reloc_plan@)
     &&& same_mmap_layout(in_plan.mmap_plans@, out_plan.mmap_plans@)
     &&& out_plan.reloc_writes@ == in_plan.reloc_plan@
+    &&& out_plan.resolver_writes@ == in_plan.resolver_plan@
+    &&& out_plan.tls == in_plan.tls
     &&& out_plan.parsed@ == in_plan.parsed@
     &&& out_plan.discovered == in_plan.discovered
     &&& out_plan.resolved == in_plan.resolved
@@ -329,6 +413,7 @@ pub open spec fn relocate_apply_stage_spec(
     &&& reloc_writes_sound(
         out_plan.parsed@,
         out_plan.discovered.order@,
+        out_plan.tls,
         out_plan.resolved,
         out_plan.reloc_writes@,
     )
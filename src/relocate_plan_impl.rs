@@ -1,6 +1,10 @@
 use crate::consts::*;
+use crate::mmap_plan_impl::object_base_exec;
 use crate::mmap_plan_spec::*;
 use crate::relocate_plan_spec::*;
+use crate::resolve_impl::symbol_match_exec;
+use crate::resolve_spec::symbol_match;
+use crate::tls_layout_spec::*;
 use crate::types::*;
 use vstd::prelude::*;
 
@@ -21,33 +25,46 @@ fn add_i64_or_zero_exec(base: u64, addend: i64) -> u64 {
     }
 }
 
-fn dyn_base_for_pos_exec(pos: usize) -> u64 {
-    let mul = (pos as i128).checked_mul(DYN_BASE_STRIDE as i128);
-    if mul.is_none() {
-        return 0;
-    }
-    let raw_opt = (DYN_BASE_START as i128).checked_add(mul.unwrap());
-    if raw_opt.is_none() {
-        return 0;
+fn is_tls_reloc_type(rel_type: u32) -> bool {
+    rel_type == R_X86_64_DTPMOD64 || rel_type == R_X86_64_DTPOFF64 || rel_type == R_X86_64_TPOFF64
+}
+
+fn provider_is_ifunc(parsed: &[ParsedObject], rr: &ResolvedReloc) -> bool {
+    match (rr.provider_object, rr.provider_symbol) {
+        (Some(po), Some(ps)) => parsed
+            .get(po)
+            .and_then(|o| o.dynsyms.get(ps))
+            .is_some_and(|sym| sym.st_info & 0xf == STT_GNU_IFUNC),
+        _ => false,
     }
-    let raw = raw_opt.unwrap();
-    if raw >= 0 && raw <= u64::MAX as i128 {
-        raw as u64
+}
+
+fn symbol_address_exec(base: u64, sym: &DynSymbol) -> u64 {
+    if sym.st_shndx == SHN_ABS {
+        sym.st_value
     } else {
-        0
+        add_u64_or_zero_exec(base, sym.st_value)
     }
 }
 
-fn object_base_exec(parsed: &[ParsedObject], order: &[usize], obj_idx: usize) -> u64 {
-    for (pos, idx) in order.iter().enumerate() {
-        if *idx == obj_idx && *idx < parsed.len() {
-            if parsed[*idx].elf_type == ET_EXEC {
-                return 0;
-            }
-            return dyn_base_for_pos_exec(pos);
-        }
+fn symbol_supported_for(rel_type: u32, sym: &DynSymbol) -> bool {
+    (sym.st_shndx < SHN_LORESERVE || sym.st_shndx == SHN_ABS)
+        && is_tls_reloc_type(rel_type) == (sym.st_info & 0xf == STT_TLS)
+}
+
+fn plan_error(parsed: &[ParsedObject], obj_idx: usize, reason: LoaderErrorReason) -> LoaderError {
+    LoaderError {
+        stage: LoaderStage::RelocatePlan,
+        object_name: parsed.get(obj_idx).map_or_else(Vec::new, |o| o.input_name.clone()),
+        reason,
+    }
+}
+
+fn symbol_name_exec(obj: &ParsedObject, sym_idx: usize) -> Vec<u8> {
+    match obj.dynsyms.get(sym_idx) {
+        Some(sym) => cstr_at(&obj.dynstr, sym.name_offset),
+        None => Vec::new(),
     }
-    0
 }
 
 fn rr_reloc_entry_exec<'a>(parsed: &'a [ParsedObject], rr: &ResolvedReloc) -> Option<&'a RelaEntry> {
@@ -69,79 +86,56 @@ fn symbol_relocation_requires_provider(rel_type: u32, sym: &DynSymbol) -> bool {
     if rel_type == R_X86_64_COPY {
         true
     } else {
-        (rel_type == R_X86_64_JUMP_SLOT || rel_type == R_X86_64_GLOB_DAT || rel_type == R_X86_64_64)
+        (rel_type == R_X86_64_JUMP_SLOT
+            || rel_type == R_X86_64_GLOB_DAT
+            || rel_type == R_X86_64_64
+            || is_tls_reloc_type(rel_type))
             && !symbol_is_weak_undef(sym)
     }
 }
 
-fn dynstr_cstr<'a>(obj: &'a ParsedObject, off: u32) -> Option<&'a [u8]> {
-    let start = off as usize;
-    if start >= obj.dynstr.len() {
-        return None;
-    }
-    let rel_end = obj.dynstr[start..].iter().position(|&b| b == 0)?;
-    Some(&obj.dynstr[start..start + rel_end])
-}
-
-fn find_copy_provider(
-    parsed: &[ParsedObject],
-    order: &[usize],
-    req_idx: usize,
-    req_sym_idx: usize,
-) -> Option<(usize, usize)> {
-    let req_obj = parsed.get(req_idx)?;
-    let req_sym = req_obj.dynsyms.get(req_sym_idx)?;
-    let req_name = dynstr_cstr(req_obj, req_sym.name_offset)?;
-
-    for obj_idx in order {
-        if *obj_idx == req_idx || *obj_idx >= parsed.len() {
-            continue;
-        }
-        let obj = &parsed[*obj_idx];
-        for (sym_idx, sym) in obj.dynsyms.iter().enumerate() {
-            if sym.st_shndx == 0 {
-                continue;
-            }
-            if let Some(name) = dynstr_cstr(obj, sym.name_offset) {
-                if name == req_name {
-                    return Some((*obj_idx, sym_idx));
-                }
+fn apply_write_to_temp_plans(plans: &mut [MmapPlan], write_addr: u64, value: u64) {
+    for plan in plans {
+        if write_addr >= plan.start && write_addr - plan.start <= usize::MAX as u64 {
+            let delta = (write_addr - plan.start) as usize;
+            if delta <= plan.len && plan.len - delta >= 8 {
+                plan.patches.push(PlanPatch { offset: delta, value });
             }
         }
     }
-    None
 }
 
-fn patch_u64_le(bytes: &mut [u8], off: usize, value: u64) {
-    if off > bytes.len() || bytes.len() - off < 8 {
-        return;
-    }
-
-    let mut k = 0usize;
-    while k < 8 {
-        let shift = 8 * k;
-        bytes[off + k] = ((value >> shift) & 0xff) as u8;
-        k += 1;
+fn plan_image_byte(parsed: &[ParsedObject], plan: &MmapPlan, idx: usize) -> u8 {
+    for p in plan.patches.iter().rev() {
+        if idx >= p.offset && idx - p.offset < 8 {
+            return ((p.value >> (8 * (idx - p.offset))) & 0xff) as u8;
+        }
     }
-}
-
-fn apply_write_to_temp_plans(plans: &mut [MmapPlan], write_addr: u64, value: u64) {
-    for plan in plans {
-        if write_addr >= plan.start && write_addr - plan.start <= usize::MAX as u64 {
-            let delta = (write_addr - plan.start) as usize;
-            patch_u64_le(&mut plan.bytes, delta, value);
+    let (file_offset, file_len, j) = match plan.overlay {
+        Some(fill) if idx >= fill.offset && idx - fill.offset < fill.len => {
+            (fill.file_offset, fill.file_len, idx - fill.offset)
+        }
+        _ => (plan.file_offset, plan.file_len, idx),
+    };
+    if j < file_len {
+        if let Some(obj) = parsed.get(plan.object_index) {
+            let off = file_offset as u128 + j as u128;
+            if off < obj.file_bytes.len() as u128 {
+                return obj.file_bytes[off as usize];
+            }
         }
     }
+    0
 }
 
-fn read_plan_byte(plans: &[MmapPlan], addr: u64) -> Option<u8> {
+fn read_plan_byte(parsed: &[ParsedObject], plans: &[MmapPlan], addr: u64) -> Option<u8> {
     for plan in plans {
         if addr >= plan.start {
             let delta = addr - plan.start;
             if delta <= usize::MAX as u64 {
                 let idx = delta as usize;
-                if idx < plan.bytes.len() {
-                    return Some(plan.bytes[idx]);
+                if idx < plan.len {
+                    return Some(plan_image_byte(parsed, plan, idx));
                 }
             }
         }
@@ -150,6 +144,7 @@ fn read_plan_byte(plans: &[MmapPlan], addr: u64) -> Option<u8> {
 }
 
 fn copy_chunk_value(
+    parsed: &[ParsedObject],
     plans: &[MmapPlan],
     src_addr: u64,
     dst_addr: u64,
@@ -159,9 +154,9 @@ fn copy_chunk_value(
     let mut i = 0usize;
     while i < 8 {
         let b = if i < chunk_len {
-            read_plan_byte(plans, add_u64_or_zero_exec(src_addr, i as u64))?
+            read_plan_byte(parsed, plans, add_u64_or_zero_exec(src_addr, i as u64))?
         } else {
-            read_plan_byte(plans, add_u64_or_zero_exec(dst_addr, i as u64))?
+            read_plan_byte(parsed, plans, add_u64_or_zero_exec(dst_addr, i as u64))?
         };
         value |= (b as u64) << (8 * i);
         i += 1;
@@ -171,54 +166,236 @@ fn copy_chunk_value(
 
 verus! {
 
+fn image_byte_exec(obj: &ParsedObject, vaddr: u128) -> (b: u8)
+    ensures
+        b == image_byte_from(*obj, vaddr as int, 0),
+{
+    let mut i: usize = 0;
+    while i < obj.phdrs.len()
+        invariant
+            i <= obj.phdrs@.len(),
+            image_byte_from(*obj, vaddr as int, 0) == image_byte_from(*obj, vaddr as int, i as nat),
+        decreases obj.phdrs.len() - i,
+    {
+        let ph = &obj.phdrs[i];
+        let start = ph.p_vaddr as u128;
+        if ph.p_type == PT_LOAD && vaddr >= start && vaddr - start < (ph.p_memsz as u128) {
+            let delta = vaddr - start;
+            let off = ph.p_offset as u128 + delta;
+            if delta < (ph.p_filesz as u128) && off < (obj.file_bytes.len() as u128) {
+                return obj.file_bytes[off as usize];
+            }
+            return 0;
+        }
+        i += 1;
+    }
+    0
+}
+
+// The planner's addend reads, checked against `rela_addend`.
+fn rela_addend_exec(obj: &ParsedObject, rel: &RelaEntry) -> (a: i64)
+    ensures
+        a == rela_addend(*obj, *rel),
+{
+    if !matches!(rel.table, RelocTable::Rel) {
+        return rel.addend;
+    }
+    let v = rel.offset as u128;
+    let b0 = image_byte_exec(obj, v) as u64;
+    let b1 = image_byte_exec(obj, v + 1) as u64;
+    let b2 = image_byte_exec(obj, v + 2) as u64;
+    let b3 = image_byte_exec(obj, v + 3) as u64;
+    let b4 = image_byte_exec(obj, v + 4) as u64;
+    let b5 = image_byte_exec(obj, v + 5) as u64;
+    let b6 = image_byte_exec(obj, v + 6) as u64;
+    let b7 = image_byte_exec(obj, v + 7) as u64;
+    let value = b0 | (b1 << 8u64) | (b2 << 16u64) | (b3 << 24u64) | (b4 << 32u64) | (b5 << 40u64) | (b6 << 48u64) | (b7
+        << 56u64);
+    value as i64
+}
+
+// TLS relocation value against the block the TLS layout gave `obj_idx`.
+fn tls_value_exec(tls: &TlsLayout, obj_idx: usize, rel_type: u32, sym_value: u64, addend: i64) -> (v: u64)
+    ensures
+        v == tls_value(rel_type, tls_module_of(*tls, obj_idx as int), sym_value, addend),
+{
+    let mut i: usize = 0;
+    while i < tls.modules.len()
+        invariant
+            i <= tls.modules@.len(),
+            tls_module_of(*tls, obj_idx as int) == tls_module_from(tls.modules@, obj_idx as int, i as nat),
+        decreases tls.modules.len() - i,
+    {
+        let m = &tls.modules[i];
+        if m.object_index == obj_idx {
+            if rel_type == R_X86_64_DTPMOD64 {
+                return m.module_id;
+            }
+            if rel_type == R_X86_64_DTPOFF64 {
+                let sum = (sym_value as i128) + (addend as i128);
+                return if 0 <= sum && sum <= u64::MAX as i128 {
+                    sum as u64
+                } else {
+                    0
+                };
+            }
+            let raw = (sym_value as i128) + (addend as i128) - (m.tp_offset as i128);
+            return if 0 <= raw && raw <= u64::MAX as i128 {
+                raw as u64
+            } else if -(u64::MAX as i128) <= raw && raw < 0 {
+                (raw + (u64::MAX as i128) + 1) as u64
+            } else {
+                0
+            };
+        }
+        i += 1;
+    }
+    0
+}
+
+// A `COPY` source is never the requester itself, even when its reference binds
+// locally; take the first matching data definition in load order.
+fn find_copy_provider(
+    parsed: &Vec<ParsedObject>,
+    order: &Vec<usize>,
+    req_idx: usize,
+    req_sym_idx: usize,
+) -> (r: Option<(usize, usize)>)
+    requires
+        req_idx < parsed@.len(),
+        req_sym_idx < parsed@[req_idx as int].dynsyms@.len(),
+    ensures
+        match r {
+            Some((o, s)) => {
+                &&& o != req_idx
+                &&& o < parsed@.len()
+                &&& s < parsed@[o as int].dynsyms@.len()
+                &&& symbol_match(parsed@, req_idx as int, req_sym_idx as int, o as int, s as int)
+            },
+            None => true,
+        },
+{
+    let mut pos: usize = 0;
+    while pos < order.len()
+        invariant
+            req_idx < parsed@.len(),
+            req_sym_idx < parsed@[req_idx as int].dynsyms@.len(),
+        decreases order.len() - pos,
+    {
+        let obj_idx = order[pos];
+        if obj_idx != req_idx && obj_idx < parsed.len() {
+            let mut s: usize = 0;
+            while s < parsed[obj_idx].dynsyms.len()
+                invariant
+                    req_idx < parsed@.len(),
+                    req_sym_idx < parsed@[req_idx as int].dynsyms@.len(),
+                    obj_idx != req_idx,
+                    obj_idx < parsed@.len(),
+                decreases parsed@[obj_idx as int].dynsyms@.len() - s,
+            {
+                let sym = &parsed[obj_idx].dynsyms[s];
+                let supported = (sym.st_shndx < SHN_LORESERVE || sym.st_shndx == SHN_ABS) && sym.st_info & 0xf
+                    != STT_TLS;
+                if supported && symbol_match_exec(parsed, req_idx, req_sym_idx, obj_idx, s) {
+                    return Some((obj_idx, s));
+                }
+                s = s + 1;
+            }
+        }
+        pos = pos + 1;
+    }
+    None
+}
+
+// Trusted: the body is not verified, so `plan_relocate_stage_spec` (symbol
+// values by section index and type, the `UnsupportedSymbol` rejections, and
+// REL addends beyond what `rela_addend_exec` proves for one entry) is
+// assumed for its result rather than proved.
 #[verifier::external_body]
 pub fn plan_relocate_stage(
     parsed: Vec<ParsedObject>,
     discovered: DiscoveryResult,
     resolved: ResolutionResult,
     mmap_plans: Vec<MmapPlan>,
+    tls: TlsLayout,
 ) -> (out: Result<RelocatePlanOutput, LoaderError>)
     requires
         mmap_plan_stage_spec(parsed@, discovered, mmap_plans@),
+        tls_layout_stage_spec(parsed@, discovered, tls),
     ensures
-        out.is_ok() ==> plan_relocate_stage_spec(parsed@, discovered, resolved, mmap_plans@, out.unwrap()),
+        out.is_ok() ==> plan_relocate_stage_spec(parsed@, discovered, resolved, mmap_plans@, tls, out.unwrap()),
 {
     let mut reloc_writes: Vec<RelocWrite> = Vec::new();
+    let mut resolver_writes: Vec<ResolverWrite> = Vec::new();
     let mut temp_plans = mmap_plans.clone();
 
     for obj_idx in &discovered.order {
         if *obj_idx >= parsed.len() {
-            return Err(LoaderError {});
+            return Err(plan_error(
+                &parsed,
+                *obj_idx,
+                LoaderErrorReason::InvalidObjectIndex { index: *obj_idx },
+            ));
         }
 
         let base = object_base_exec(&parsed, &discovered.order, *obj_idx);
 
         for rel in &parsed[*obj_idx].relas {
-            if rel.reloc_type() != R_X86_64_RELATIVE {
+            let rel_type = rel.reloc_type();
+            let addend = rela_addend_exec(&parsed[*obj_idx], rel);
+            if rel_type == R_X86_64_IRELATIVE {
+                resolver_writes.push(ResolverWrite {
+                    object_name: parsed[*obj_idx].input_name.clone(),
+                    write_addr: add_u64_or_zero_exec(base, rel.offset),
+                    resolver_pc: add_i64_or_zero_exec(base, addend),
+                    addend: 0,
+                    reloc_type: R_X86_64_IRELATIVE,
+                });
                 continue;
             }
+            let value = if rel_type == R_X86_64_RELATIVE {
+                add_i64_or_zero_exec(base, addend)
+            } else if is_tls_reloc_type(rel_type) && rel.sym_index() == 0 {
+                tls_value_exec(&tls, *obj_idx, rel_type, 0, addend)
+            } else {
+                continue;
+            };
             let write_addr = add_u64_or_zero_exec(base, rel.offset);
-            let value = add_i64_or_zero_exec(base, rel.addend);
             reloc_writes.push(RelocWrite {
                 object_name: parsed[*obj_idx].input_name.clone(),
                 write_addr,
                 value,
-                reloc_type: R_X86_64_RELATIVE,
+                reloc_type: rel_type,
             });
             apply_write_to_temp_plans(&mut temp_plans, write_addr, value);
         }
 
         for rel in &parsed[*obj_idx].jmprels {
-            if rel.reloc_type() != R_X86_64_RELATIVE {
+            let rel_type = rel.reloc_type();
+            let addend = rela_addend_exec(&parsed[*obj_idx], rel);
+            if rel_type == R_X86_64_IRELATIVE {
+                resolver_writes.push(ResolverWrite {
+                    object_name: parsed[*obj_idx].input_name.clone(),
+                    write_addr: add_u64_or_zero_exec(base, rel.offset),
+                    resolver_pc: add_i64_or_zero_exec(base, addend),
+                    addend: 0,
+                    reloc_type: R_X86_64_IRELATIVE,
+                });
                 continue;
             }
+            let value = if rel_type == R_X86_64_RELATIVE {
+                add_i64_or_zero_exec(base, addend)
+            } else if is_tls_reloc_type(rel_type) && rel.sym_index() == 0 {
+                tls_value_exec(&tls, *obj_idx, rel_type, 0, addend)
+            } else {
+                continue;
+            };
             let write_addr = add_u64_or_zero_exec(base, rel.offset);
-            let value = add_i64_or_zero_exec(base, rel.addend);
             reloc_writes.push(RelocWrite {
                 object_name: parsed[*obj_idx].input_name.clone(),
                 write_addr,
                 value,
-                reloc_type: R_X86_64_RELATIVE,
+                reloc_type: rel_type,
             });
             apply_write_to_temp_plans(&mut temp_plans, write_addr, value);
         }
@@ -229,7 +406,9 @@ pub fn plan_relocate_stage(
     for rr in &resolved.resolved_relocs {
         let rel = match rr_reloc_entry_exec(&parsed, rr) {
             Some(v) => v,
-            None => return Err(LoaderError {}),
+            None => {
+                return Err(plan_error(&parsed, rr.requester, LoaderErrorReason::BadRelocationTable))
+            }
         };
 
         let rel_type = rel.reloc_type();
@@ -237,16 +416,25 @@ pub fn plan_relocate_stage(
             && rel_type != R_X86_64_GLOB_DAT
             && rel_type != R_X86_64_64
             && rel_type != R_X86_64_COPY
+            && !is_tls_reloc_type(rel_type)
         {
             continue;
         }
 
         let req_idx = rr.requester;
         if req_idx >= parsed.len() {
-            return Err(LoaderError {});
+            return Err(plan_error(
+                &parsed,
+                req_idx,
+                LoaderErrorReason::InvalidObjectIndex { index: req_idx },
+            ));
         }
         if rr.sym_index == 0 || rr.sym_index >= parsed[req_idx].dynsyms.len() {
-            return Err(LoaderError {});
+            return Err(plan_error(
+                &parsed,
+                req_idx,
+                LoaderErrorReason::BadSymbolIndex { sym_index: rr.sym_index },
+            ));
         }
 
         let provider_required =
@@ -255,12 +443,33 @@ pub fn plan_relocate_stage(
         match (rr.provider_object, rr.provider_symbol) {
             (Some(po), Some(ps)) => {
                 if po >= parsed.len() || ps >= parsed[po].dynsyms.len() {
-                    return Err(LoaderError {});
+                    return Err(plan_error(
+                        &parsed,
+                        req_idx,
+                        LoaderErrorReason::InvalidObjectIndex { index: po },
+                    ));
+                }
+                let sym = &parsed[po].dynsyms[ps];
+                if !symbol_supported_for(rel_type, sym) {
+                    return Err(plan_error(
+                        &parsed,
+                        po,
+                        LoaderErrorReason::UnsupportedSymbol {
+                            name: symbol_name_exec(&parsed[po], ps),
+                            shndx: sym.st_shndx,
+                            sym_type: sym.st_info & 0xf,
+                        },
+                    ));
                 }
             }
             _ => {
                 if provider_required {
-                    return Err(LoaderError {});
+                    let name = symbol_name_exec(&parsed[req_idx], rr.sym_index);
+                    return Err(plan_error(
+                        &parsed,
+                        req_idx,
+                        LoaderErrorReason::UndefinedSymbol { name },
+                    ));
                 }
             }
         }
@@ -271,21 +480,52 @@ pub fn plan_relocate_stage(
         }
 
         let req_base = object_base_exec(&parsed, &discovered.order, req_idx);
+        if is_tls_reloc_type(rel_type) {
+            let value = match (rr.provider_object, rr.provider_symbol) {
+                (Some(po), Some(ps)) => tls_value_exec(
+                    &tls,
+                    po,
+                    rel_type,
+                    parsed[po].dynsyms[ps].st_value,
+                    rela_addend_exec(&parsed[req_idx], rel),
+                ),
+                _ => 0,
+            };
+            let write_addr = add_u64_or_zero_exec(req_base, rel.offset);
+            reloc_writes.push(RelocWrite {
+                object_name: parsed[req_idx].input_name.clone(),
+                write_addr,
+                value,
+                reloc_type: rel_type,
+            });
+            apply_write_to_temp_plans(&mut temp_plans, write_addr, value);
+            continue;
+        }
+
         let provider_value = match (rr.provider_object, rr.provider_symbol) {
             (Some(po), Some(ps)) => {
                 let prov_base = object_base_exec(&parsed, &discovered.order, po);
-                add_u64_or_zero_exec(prov_base, parsed[po].dynsyms[ps].st_value)
+                symbol_address_exec(prov_base, &parsed[po].dynsyms[ps])
             }
             _ => 0,
         };
 
         let value = if rel_type == R_X86_64_64 {
-            add_i64_or_zero_exec(provider_value, rel.addend)
+            add_i64_or_zero_exec(provider_value, rela_addend_exec(&parsed[req_idx], rel))
         } else {
             provider_value
         };
 
         let write_addr = add_u64_or_zero_exec(req_base, rel.offset);
+        if provider_is_ifunc(&parsed, rr) {
+            resolver_writes.push(ResolverWrite {
+                object_name: parsed[req_idx].input_name.clone(),
+                write_addr,
+                resolver_pc: provider_value,
+                addend: if rel_type == R_X86_64_64 { rela_addend_exec(&parsed[req_idx], rel) } else { 0 },
+                reloc_type: rel_type,
+            });
+        }
         reloc_writes.push(RelocWrite {
             object_name: parsed[req_idx].input_name.clone(),
             write_addr,
@@ -298,7 +538,11 @@ pub fn plan_relocate_stage(
     for (rr, rel) in pending_copy {
         let req_idx = rr.requester;
         if req_idx >= parsed.len() || rr.sym_index >= parsed[req_idx].dynsyms.len() {
-            return Err(LoaderError {});
+            return Err(plan_error(
+                &parsed,
+                req_idx,
+                LoaderErrorReason::BadSymbolIndex { sym_index: rr.sym_index },
+            ));
         }
 
         let provider = match (rr.provider_object, rr.provider_symbol) {
@@ -315,7 +559,14 @@ pub fn plan_relocate_stage(
 
         let (prov_idx, prov_sym_idx) = match provider {
             Some(v) => v,
-            None => return Err(LoaderError {}),
+            None => {
+                let name = symbol_name_exec(&parsed[req_idx], rr.sym_index);
+                return Err(plan_error(
+                    &parsed,
+                    req_idx,
+                    LoaderErrorReason::MissingCopySource { name },
+                ));
+            }
         };
 
         let req_sym = &parsed[req_idx].dynsyms[rr.sym_index];
@@ -332,7 +583,7 @@ pub fn plan_relocate_stage(
         let req_base = object_base_exec(&parsed, &discovered.order, req_idx);
         let prov_base = object_base_exec(&parsed, &discovered.order, prov_idx);
         let dst_start = add_u64_or_zero_exec(req_base, rel.offset);
-        let src_start = add_u64_or_zero_exec(prov_base, prov_sym.st_value);
+        let src_start = symbol_address_exec(prov_base, prov_sym);
 
         let mut copied = 0usize;
         while copied < copy_size {
@@ -340,9 +591,15 @@ pub fn plan_relocate_stage(
             let src_addr = add_u64_or_zero_exec(src_start, copied as u64);
             let dst_addr = add_u64_or_zero_exec(dst_start, copied as u64);
 
-            let value = match copy_chunk_value(&temp_plans, src_addr, dst_addr, chunk_len) {
+            let value = match copy_chunk_value(&parsed, &temp_plans, src_addr, dst_addr, chunk_len) {
                 Some(v) => v,
-                None => return Err(LoaderError {}),
+                None => {
+                    return Err(plan_error(
+                        &parsed,
+                        req_idx,
+                        LoaderErrorReason::UnmappedAddress { vaddr: src_addr },
+                    ))
+                }
             };
 
             reloc_writes.push(RelocWrite {
@@ -358,7 +615,9 @@ pub fn plan_relocate_stage(
 
     Ok(RelocatePlanOutput {
         mmap_plans,
+        tls,
         reloc_plan: reloc_writes,
+        resolver_plan: resolver_writes,
         parsed,
         discovered,
         resolved,
@@ -1,5 +1,7 @@
 use crate::consts::*;
+use crate::mmap_plan_spec::*;
 use crate::relocate_apply_spec::*;
+use crate::tls_layout_spec::*;
 use crate::types::*;
 use vstd::prelude::*;
 
@@ -26,19 +28,186 @@ pub open spec fn rela_type_of(r: RelaEntry) -> u32 {
     (r.info & 0xffff_ffff) as u32
 }
 
-pub open spec fn dyn_base_for_pos(pos: int) -> u64 {
-    if pos < 0 {
+pub open spec fn rela_sym_of(r: RelaEntry) -> usize {
+    (r.info >> 32) as usize
+}
+
+// Byte at link-time address `vaddr` of the object's file image: file contents
+// inside the first PT_LOAD covering it, zero past its `p_filesz`.
+pub open spec fn image_byte_from(obj: ParsedObject, vaddr: int, i: nat) -> u8
+    decreases obj.phdrs@.len() - i,
+{
+    if i >= obj.phdrs@.len() {
         0
     } else {
-        let raw = (DYN_BASE_START as i128) + (pos as i128) * (DYN_BASE_STRIDE as i128);
-        if 0 <= raw && raw <= u64::MAX as i128 {
-            raw as u64
+        let ph = obj.phdrs@[i as int];
+        let delta = vaddr - ph.p_vaddr;
+        if ph.p_type == PT_LOAD && 0 <= delta < ph.p_memsz {
+            let off = ph.p_offset + delta;
+            if delta < ph.p_filesz && off < obj.file_bytes@.len() {
+                obj.file_bytes@[off]
+            } else {
+                0
+            }
         } else {
-            0
+            image_byte_from(obj, vaddr, (i + 1) as nat)
+        }
+    }
+}
+
+pub open spec fn image_byte_at(obj: ParsedObject, vaddr: int) -> u64 {
+    image_byte_from(obj, vaddr, 0) as u64
+}
+
+pub open spec fn image_u64_at(obj: ParsedObject, vaddr: u64) -> u64 {
+    image_byte_at(obj, vaddr as int) | (image_byte_at(obj, vaddr + 1) << 8u64) | (image_byte_at(obj, vaddr + 2)
+        << 16u64) | (image_byte_at(obj, vaddr + 3) << 24u64) | (image_byte_at(obj, vaddr + 4) << 32u64)
+        | (image_byte_at(obj, vaddr + 5) << 40u64) | (image_byte_at(obj, vaddr + 6) << 48u64)
+        | (image_byte_at(obj, vaddr + 7) << 56u64)
+}
+
+// The addend of a relocation entry: explicit for RELA and RELR entries, and
+// for REL entries the word the target already holds in the file image.
+pub open spec fn rela_addend(obj: ParsedObject, rela: RelaEntry) -> i64 {
+    if rela.table == RelocTable::Rel {
+        image_u64_at(obj, rela.offset) as i64
+    } else {
+        rela.addend
+    }
+}
+
+pub open spec fn is_tls_reloc_type(t: u32) -> bool {
+    t == R_X86_64_DTPMOD64 || t == R_X86_64_DTPOFF64 || t == R_X86_64_TPOFF64
+}
+
+pub open spec fn tpoff_value(tp_offset: u64, sym_value: u64, addend: i64) -> u64 {
+    let raw = (sym_value as int) + (addend as int) - (tp_offset as int);
+    if 0 <= raw && raw <= u64::MAX as int {
+        raw as u64
+    } else if -(u64::MAX as int) <= raw && raw < 0 {
+        (raw + (u64::MAX as int) + 1) as u64
+    } else {
+        0
+    }
+}
+
+// `DTPMOD64` takes the module id the TLS layout gave the defining object and
+// `DTPOFF64` the offset inside that module's block; `TPOFF64` is relative to
+// the thread pointer. An object without a TLS block yields 0.
+pub open spec fn tls_value(reloc_type: u32, module: Option<TlsModule>, sym_value: u64, addend: i64) -> u64 {
+    match module {
+        Some(m) => {
+            if reloc_type == R_X86_64_DTPMOD64 {
+                m.module_id
+            } else if reloc_type == R_X86_64_DTPOFF64 {
+                add_i64_or_zero(sym_value, addend)
+            } else {
+                tpoff_value(m.tp_offset, sym_value, addend)
+            }
+        },
+        None => 0,
+    }
+}
+
+// Page-rounded lowest start of an object's PT_LOAD segments, from program
+// header `i` on; `u64::MAX` when there are none.
+pub open spec fn load_span_lo_from(phdrs: Seq<ProgramHeader>, i: nat) -> u64
+    decreases phdrs.len() - i,
+{
+    if i >= phdrs.len() {
+        u64::MAX
+    } else {
+        let rest = load_span_lo_from(phdrs, (i + 1) as nat);
+        let ph = phdrs[i as int];
+        let lo = page_floor_u64(ph.p_vaddr);
+        if ph.p_type == PT_LOAD && lo < rest {
+            lo
+        } else {
+            rest
+        }
+    }
+}
+
+// Page-rounded highest end of an object's PT_LOAD segments; `0` when there are none.
+pub open spec fn load_span_hi_from(phdrs: Seq<ProgramHeader>, i: nat) -> u64
+    decreases phdrs.len() - i,
+{
+    if i >= phdrs.len() {
+        0
+    } else {
+        let rest = load_span_hi_from(phdrs, (i + 1) as nat);
+        let ph = phdrs[i as int];
+        let hi = page_ceil_u64(seg_end_or_zero(ph.p_vaddr, ph.p_memsz));
+        if ph.p_type == PT_LOAD && hi > rest {
+            hi
+        } else {
+            rest
         }
     }
 }
 
+// Largest PT_LOAD `p_align` that is a whole number of pages, at least one page.
+pub open spec fn load_align_from(phdrs: Seq<ProgramHeader>, i: nat) -> u64
+    decreases phdrs.len() - i,
+{
+    if i >= phdrs.len() {
+        PAGE_SIZE
+    } else {
+        let rest = load_align_from(phdrs, (i + 1) as nat);
+        let ph = phdrs[i as int];
+        if ph.p_type == PT_LOAD && ph.p_align % PAGE_SIZE == 0 && ph.p_align > rest {
+            ph.p_align
+        } else {
+            rest
+        }
+    }
+}
+
+// Smallest multiple of the object's largest `p_align` that puts its lowest
+// segment at or above `free`. Aligning the base rather than the lowest segment
+// keeps every segment at its linked address modulo its `p_align`.
+pub open spec fn dyn_base_at(obj: ParsedObject, free: u64) -> u64 {
+    let lo = load_span_lo_from(obj.phdrs@, 0);
+    if lo <= free {
+        align_up_or_zero((free - lo) as u64, load_align_from(obj.phdrs@, 0))
+    } else {
+        0
+    }
+}
+
+// First address above every packed `ET_DYN` object at load positions before
+// `pos`. Objects with a requested `load_base` do not take part in the packing.
+pub open spec fn dyn_next_free(parsed: Seq<ParsedObject>, order: Seq<usize>, pos: nat) -> u64
+    decreases pos,
+{
+    if pos == 0 {
+        DYN_BASE_START
+    } else {
+        let prev = (pos - 1) as nat;
+        let free = dyn_next_free(parsed, order, prev);
+        let obj_idx = order[prev as int] as int;
+        if 0 <= obj_idx < parsed.len() && parsed[obj_idx].elf_type != ET_EXEC && parsed[obj_idx].load_base.is_none() {
+            let obj = parsed[obj_idx];
+            let end = add_u64_or_zero(dyn_base_at(obj, free), load_span_hi_from(obj.phdrs@, 0));
+            if end > free {
+                end
+            } else {
+                free
+            }
+        } else {
+            free
+        }
+    }
+}
+
+pub open spec fn dyn_base_for_pos(parsed: Seq<ParsedObject>, order: Seq<usize>, pos: int) -> u64 {
+    if pos < 0 {
+        0
+    } else {
+        dyn_base_at(parsed[order[pos] as int], dyn_next_free(parsed, order, pos as nat))
+    }
+}
+
 pub open spec fn pos_to_i128_or_zero(pos: int) -> i128 {
     if pos < 0 || pos > usize::MAX as int {
         0
@@ -56,7 +225,10 @@ pub open spec fn base_for_load_pos(parsed: Seq<ParsedObject>, order: Seq<usize>,
     if parsed[obj_idx].elf_type == ET_EXEC {
         0
     } else {
-        dyn_base_for_pos(pos)
+        match parsed[obj_idx].load_base {
+            Some(base) => base,
+            None => dyn_base_for_pos(parsed, order, pos),
+        }
     }
 }
 
@@ -84,6 +256,44 @@ pub open spec fn object_base(parsed: Seq<ParsedObject>, order: Seq<usize>, obj_i
     object_base_from(parsed, order, obj_idx, 0)
 }
 
+pub open spec fn symbol_type_of(sym: DynSymbol) -> u8 {
+    sym.st_info & 0xf
+}
+
+// Run-time address of a non-TLS definition. Absolute symbols are not rebased.
+pub open spec fn symbol_address(parsed: Seq<ParsedObject>, order: Seq<usize>, obj_idx: int, sym: DynSymbol) -> u64 {
+    if sym.st_shndx == SHN_ABS {
+        sym.st_value
+    } else {
+        add_u64_or_zero(object_base(parsed, order, obj_idx), sym.st_value)
+    }
+}
+
+// Definitions the planner can turn into a value for `rel_type`: the section
+// index is an ordinary one or `SHN_ABS` (`SHN_COMMON` and the other reserved
+// indices only occur in relocatable objects), and a symbol is `STT_TLS`
+// exactly when the relocation is a TLS one, since a TLS symbol's value is an
+// offset inside its module's block rather than an address.
+pub open spec fn symbol_supported_for(rel_type: u32, sym: DynSymbol) -> bool {
+    &&& sym.st_shndx < SHN_LORESERVE || sym.st_shndx == SHN_ABS
+    &&& is_tls_reloc_type(rel_type) == (symbol_type_of(sym) == STT_TLS)
+}
+
+pub open spec fn rr_provider_supported(parsed: Seq<ParsedObject>, rr: ResolvedReloc) -> bool {
+    match (rr_reloc_entry(parsed, rr), rr.provider_object, rr.provider_symbol) {
+        (Some(rel), Some(po), Some(ps)) => {
+            let rel_type = rela_type_of(rel);
+            (rel_type == R_X86_64_JUMP_SLOT || rel_type == R_X86_64_GLOB_DAT || rel_type == R_X86_64_64
+                || rel_type == R_X86_64_COPY || is_tls_reloc_type(rel_type)) ==> {
+                &&& (po as int) < parsed.len()
+                &&& (ps as int) < parsed[po as int].dynsyms@.len()
+                &&& symbol_supported_for(rel_type, parsed[po as int].dynsyms@[ps as int])
+            }
+        },
+        _ => true,
+    }
+}
+
 pub open spec fn relative_write_for_entry(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
@@ -97,11 +307,77 @@ pub open spec fn relative_write_for_entry(
     RelocWrite {
         object_name: parsed[obj_idx].input_name,
         write_addr: add_u64_or_zero(object_base(parsed, order, obj_idx), rela.offset),
-        value: add_i64_or_zero(object_base(parsed, order, obj_idx), rela.addend),
+        value: add_i64_or_zero(
+            object_base(parsed, order, obj_idx),
+            rela_addend(parsed[obj_idx], rela),
+        ),
         reloc_type: R_X86_64_RELATIVE,
     }
 }
 
+pub open spec fn local_tls_write_for_entry(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    tls: TlsLayout,
+    obj_idx: int,
+    rela: RelaEntry,
+) -> RelocWrite
+    recommends
+        0 <= obj_idx < parsed.len(),
+        is_tls_reloc_type(rela_type_of(rela)),
+{
+    RelocWrite {
+        object_name: parsed[obj_idx].input_name,
+        write_addr: add_u64_or_zero(object_base(parsed, order, obj_idx), rela.offset),
+        value: tls_value(rela_type_of(rela), tls_module_of(tls, obj_idx), 0, rela_addend(parsed[obj_idx], rela)),
+        reloc_type: rela_type_of(rela),
+    }
+}
+
+pub open spec fn rr_provider_tls_value(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    tls: TlsLayout,
+    rr: ResolvedReloc,
+    rela: RelaEntry,
+) -> u64 {
+    match (rr.provider_object, rr.provider_symbol) {
+        (Some(po), Some(ps)) => {
+            if (po as int) < parsed.len() && (ps as int) < parsed[po as int].dynsyms@.len() {
+                tls_value(
+                    rela_type_of(rela),
+                    tls_module_of(tls, po as int),
+                    parsed[po as int].dynsyms@[ps as int].st_value,
+                    rela_addend(parsed[rr.requester as int], rela),
+                )
+            } else {
+                0
+            }
+        }
+        _ => 0,
+    }
+}
+
+pub open spec fn tls_symbol_write_for_rr(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    tls: TlsLayout,
+    rr: ResolvedReloc,
+    rela: RelaEntry,
+) -> RelocWrite
+    recommends
+        0 <= (rr.requester as int) && (rr.requester as int) < parsed.len(),
+        is_tls_reloc_type(rela_type_of(rela)),
+{
+    let req = rr.requester as int;
+    RelocWrite {
+        object_name: parsed[req].input_name,
+        write_addr: add_u64_or_zero(object_base(parsed, order, req), rela.offset),
+        value: rr_provider_tls_value(parsed, order, tls, rr, rela),
+        reloc_type: rela_type_of(rela),
+    }
+}
+
 pub open spec fn symbol_write_for_rr(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
@@ -118,7 +394,7 @@ pub open spec fn symbol_write_for_rr(
         object_name: parsed[req].input_name,
         write_addr: add_u64_or_zero(object_base(parsed, order, req), rela.offset),
         value: if rela_type_of(rela) == R_X86_64_64 {
-            add_i64_or_zero(rr_provider_value(parsed, order, rr), rela.addend)
+            add_i64_or_zero(rr_provider_value(parsed, order, rr), rela_addend(parsed[req], rela))
         } else {
             rr_provider_value(parsed, order, rr)
         },
@@ -129,6 +405,7 @@ pub open spec fn symbol_write_for_rr(
 pub open spec fn expected_relative_rela_writes_from(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    tls: TlsLayout,
     obj_idx: int,
     i: nat,
 ) -> Seq<RelocWrite>
@@ -144,9 +421,11 @@ pub open spec fn expected_relative_rela_writes_from(
         Seq::empty()
     } else {
         let rela = parsed[obj_idx].relas@[i as int];
-        let tail = expected_relative_rela_writes_from(parsed, order, obj_idx, (i + 1) as nat);
+        let tail = expected_relative_rela_writes_from(parsed, order, tls, obj_idx, (i + 1) as nat);
         if rela_type_of(rela) == R_X86_64_RELATIVE {
             seq![relative_write_for_entry(parsed, order, obj_idx, rela)] + tail
+        } else if is_tls_reloc_type(rela_type_of(rela)) && rela_sym_of(rela) == 0 {
+            seq![local_tls_write_for_entry(parsed, order, tls, obj_idx, rela)] + tail
         } else {
             tail
         }
@@ -156,6 +435,7 @@ pub open spec fn expected_relative_rela_writes_from(
 pub open spec fn expected_relative_jmprel_writes_from(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    tls: TlsLayout,
     obj_idx: int,
     i: nat,
 ) -> Seq<RelocWrite>
@@ -171,9 +451,11 @@ pub open spec fn expected_relative_jmprel_writes_from(
         Seq::empty()
     } else {
         let rela = parsed[obj_idx].jmprels@[i as int];
-        let tail = expected_relative_jmprel_writes_from(parsed, order, obj_idx, (i + 1) as nat);
+        let tail = expected_relative_jmprel_writes_from(parsed, order, tls, obj_idx, (i + 1) as nat);
         if rela_type_of(rela) == R_X86_64_RELATIVE {
             seq![relative_write_for_entry(parsed, order, obj_idx, rela)] + tail
+        } else if is_tls_reloc_type(rela_type_of(rela)) && rela_sym_of(rela) == 0 {
+            seq![local_tls_write_for_entry(parsed, order, tls, obj_idx, rela)] + tail
         } else {
             tail
         }
@@ -183,15 +465,17 @@ pub open spec fn expected_relative_jmprel_writes_from(
 pub open spec fn expected_relative_writes_for_obj(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    tls: TlsLayout,
     obj_idx: int,
 ) -> Seq<RelocWrite> {
-    expected_relative_rela_writes_from(parsed, order, obj_idx, 0)
-        + expected_relative_jmprel_writes_from(parsed, order, obj_idx, 0)
+    expected_relative_rela_writes_from(parsed, order, tls, obj_idx, 0)
+        + expected_relative_jmprel_writes_from(parsed, order, tls, obj_idx, 0)
 }
 
 pub open spec fn expected_relative_writes_from_order(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    tls: TlsLayout,
     pos: nat,
 ) -> Seq<RelocWrite>
     decreases order.len() - pos,
@@ -200,14 +484,15 @@ pub open spec fn expected_relative_writes_from_order(
         Seq::empty()
     } else {
         let obj_idx = order[pos as int] as int;
-        expected_relative_writes_for_obj(parsed, order, obj_idx)
-            + expected_relative_writes_from_order(parsed, order, (pos + 1) as nat)
+        expected_relative_writes_for_obj(parsed, order, tls, obj_idx)
+            + expected_relative_writes_from_order(parsed, order, tls, (pos + 1) as nat)
     }
 }
 
 pub open spec fn expected_symbol_writes_from(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    tls: TlsLayout,
     resolved: ResolutionResult,
     i: nat,
 ) -> Seq<RelocWrite>
@@ -217,7 +502,7 @@ pub open spec fn expected_symbol_writes_from(
         Seq::empty()
     } else {
         let rr = resolved.resolved_relocs@[i as int];
-        let tail = expected_symbol_writes_from(parsed, order, resolved, (i + 1) as nat);
+        let tail = expected_symbol_writes_from(parsed, order, tls, resolved, (i + 1) as nat);
         match rr_reloc_entry(parsed, rr) {
             Some(rel) => {
                 let rel_type = rela_type_of(rel);
@@ -231,6 +516,8 @@ pub open spec fn expected_symbol_writes_from(
                     && 0 <= req < parsed.len()
                 {
                     seq![symbol_write_for_rr(parsed, order, rr, rel)] + tail
+                } else if is_tls_reloc_type(rel_type) && 0 <= req < parsed.len() {
+                    seq![tls_symbol_write_for_rr(parsed, order, tls, rr, rel)] + tail
                 } else {
                     tail
                 }
@@ -243,10 +530,150 @@ pub open spec fn expected_symbol_writes_from(
 pub open spec fn expected_reloc_writes(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    tls: TlsLayout,
     resolved: ResolutionResult,
 ) -> Seq<RelocWrite> {
-    expected_relative_writes_from_order(parsed, order, 0)
-        + expected_symbol_writes_from(parsed, order, resolved, 0)
+    expected_relative_writes_from_order(parsed, order, tls, 0)
+        + expected_symbol_writes_from(parsed, order, tls, resolved, 0)
+}
+
+pub open spec fn irelative_write_for_entry(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    obj_idx: int,
+    rela: RelaEntry,
+) -> ResolverWrite
+    recommends
+        0 <= obj_idx < parsed.len(),
+        rela_type_of(rela) == R_X86_64_IRELATIVE,
+{
+    ResolverWrite {
+        object_name: parsed[obj_idx].input_name,
+        write_addr: add_u64_or_zero(object_base(parsed, order, obj_idx), rela.offset),
+        resolver_pc: add_i64_or_zero(
+            object_base(parsed, order, obj_idx),
+            rela_addend(parsed[obj_idx], rela),
+        ),
+        addend: 0,
+        reloc_type: R_X86_64_IRELATIVE,
+    }
+}
+
+pub open spec fn irelative_writes_in(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    obj_idx: int,
+    relas: Seq<RelaEntry>,
+    i: nat,
+) -> Seq<ResolverWrite>
+    decreases relas.len() - i,
+{
+    if i >= relas.len() {
+        Seq::empty()
+    } else {
+        let tail = irelative_writes_in(parsed, order, obj_idx, relas, (i + 1) as nat);
+        if rela_type_of(relas[i as int]) == R_X86_64_IRELATIVE {
+            seq![irelative_write_for_entry(parsed, order, obj_idx, relas[i as int])] + tail
+        } else {
+            tail
+        }
+    }
+}
+
+pub open spec fn expected_irelative_writes_from_order(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    pos: nat,
+) -> Seq<ResolverWrite>
+    decreases order.len() - pos,
+{
+    if pos >= order.len() {
+        Seq::empty()
+    } else {
+        let obj_idx = order[pos as int] as int;
+        let tail = expected_irelative_writes_from_order(parsed, order, (pos + 1) as nat);
+        if 0 <= obj_idx < parsed.len() {
+            irelative_writes_in(parsed, order, obj_idx, parsed[obj_idx].relas@, 0)
+                + irelative_writes_in(parsed, order, obj_idx, parsed[obj_idx].jmprels@, 0) + tail
+        } else {
+            tail
+        }
+    }
+}
+
+pub open spec fn rr_provider_is_ifunc(parsed: Seq<ParsedObject>, rr: ResolvedReloc) -> bool {
+    match (rr.provider_object, rr.provider_symbol) {
+        (Some(po), Some(ps)) => {
+            &&& (po as int) < parsed.len()
+            &&& (ps as int) < parsed[po as int].dynsyms@.len()
+            &&& parsed[po as int].dynsyms@[ps as int].st_info & 0xf == STT_GNU_IFUNC
+        }
+        _ => false,
+    }
+}
+
+pub open spec fn ifunc_write_for_rr(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    rr: ResolvedReloc,
+    rela: RelaEntry,
+) -> ResolverWrite
+    recommends
+        0 <= (rr.requester as int) && (rr.requester as int) < parsed.len(),
+        rr_provider_is_ifunc(parsed, rr),
+{
+    let req = rr.requester as int;
+    ResolverWrite {
+        object_name: parsed[req].input_name,
+        write_addr: add_u64_or_zero(object_base(parsed, order, req), rela.offset),
+        resolver_pc: rr_provider_value(parsed, order, rr),
+        addend: if rela_type_of(rela) == R_X86_64_64 {
+            rela_addend(parsed[req], rela)
+        } else {
+            0
+        },
+        reloc_type: rela_type_of(rela),
+    }
+}
+
+pub open spec fn expected_ifunc_writes_from(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    resolved: ResolutionResult,
+    i: nat,
+) -> Seq<ResolverWrite>
+    decreases resolved.resolved_relocs@.len() - i,
+{
+    if i >= resolved.resolved_relocs@.len() {
+        Seq::empty()
+    } else {
+        let rr = resolved.resolved_relocs@[i as int];
+        let tail = expected_ifunc_writes_from(parsed, order, resolved, (i + 1) as nat);
+        match rr_reloc_entry(parsed, rr) {
+            Some(rel) => {
+                let rel_type = rela_type_of(rel);
+                let req = rr.requester as int;
+                if (rel_type == R_X86_64_JUMP_SLOT || rel_type == R_X86_64_GLOB_DAT
+                    || rel_type == R_X86_64_64) && rr_provider_is_ifunc(parsed, rr)
+                    && 0 <= req < parsed.len()
+                {
+                    seq![ifunc_write_for_rr(parsed, order, rr, rel)] + tail
+                } else {
+                    tail
+                }
+            }
+            None => tail,
+        }
+    }
+}
+
+pub open spec fn expected_resolver_writes(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    resolved: ResolutionResult,
+) -> Seq<ResolverWrite> {
+    expected_irelative_writes_from_order(parsed, order, 0)
+        + expected_ifunc_writes_from(parsed, order, resolved, 0)
 }
 
 pub open spec fn plan_relocate_stage_spec(
@@ -254,14 +681,22 @@ pub open spec fn plan_relocate_stage_spec(
     discovered: DiscoveryResult,
     resolved: ResolutionResult,
     mmap_plans: Seq<MmapPlan>,
+    tls: TlsLayout,
     out: RelocatePlanOutput,
 ) -> bool {
     &&& out.mmap_plans@ == mmap_plans
+    &&& out.tls == tls
     &&& out.parsed@ == parsed
     &&& out.discovered == discovered
     &&& out.resolved == resolved
-    &&& out.reloc_plan@ == expected_reloc_writes(parsed, discovered.order@, resolved)
-    &&& reloc_writes_sound(parsed, discovered.order@, resolved, out.reloc_plan@)
+    &&& out.reloc_plan@ == expected_reloc_writes(parsed, discovered.order@, tls, resolved)
+    &&& out.resolver_plan@ == expected_resolver_writes(parsed, discovered.order@, resolved)
+    &&& reloc_writes_sound(parsed, discovered.order@, tls, resolved, out.reloc_plan@)
+    &&& forall|i: int|
+        0 <= i < resolved.resolved_relocs@.len() ==> rr_provider_supported(
+            parsed,
+            resolved.resolved_relocs@[i],
+        )
 }
 
 } // verus!
@@ -26,7 +26,124 @@ fn cstr_eq_from_exec(a: &Vec<u8>, ai: usize, b: &Vec<u8>, bi: usize) -> (r: bool
     }
 }
 
-fn symbol_match_exec(
+fn verneed_name_exec(needs: &Vec<VersionNeed>, index: u16) -> (r: Option<u32>)
+    ensures
+        r == verneed_name_from(needs@, index, 0),
+{
+    let mut i: usize = 0;
+    while i < needs.len()
+        invariant
+            i <= needs@.len(),
+            verneed_name_from(needs@, index, i as nat) == verneed_name_from(needs@, index, 0),
+        decreases needs.len() - i,
+    {
+        if needs[i].index == index {
+            return Some(needs[i].name_offset);
+        }
+        i = i + 1;
+    }
+    None
+}
+
+fn verdef_name_exec(defs: &Vec<VersionDef>, index: u16) -> (r: Option<u32>)
+    ensures
+        r == verdef_name_from(defs@, index, 0),
+{
+    let mut i: usize = 0;
+    while i < defs.len()
+        invariant
+            i <= defs@.len(),
+            verdef_name_from(defs@, index, i as nat) == verdef_name_from(defs@, index, 0),
+        decreases defs.len() - i,
+    {
+        if defs[i].index == index {
+            return Some(defs[i].name_offset);
+        }
+        i = i + 1;
+    }
+    None
+}
+
+fn required_version_exec(obj: &ParsedObject, s: usize) -> (r: Option<u32>)
+    ensures
+        r == required_version(*obj, s as int),
+{
+    if s >= obj.versym.len() {
+        return None;
+    }
+    let v = obj.versym[s] & VERSYM_VERSION;
+    if v < 2 {
+        return None;
+    }
+    match verneed_name_exec(&obj.verneeds, v) {
+        Some(name) => Some(name),
+        None => verdef_name_exec(&obj.verdefs, v),
+    }
+}
+
+fn provided_version_exec(obj: &ParsedObject, s: usize) -> (r: Option<u32>)
+    ensures
+        r == provided_version(*obj, s as int),
+{
+    if s >= obj.versym.len() {
+        return None;
+    }
+    let v = obj.versym[s] & VERSYM_VERSION;
+    if v < 2 {
+        return None;
+    }
+    verdef_name_exec(&obj.verdefs, v)
+}
+
+fn version_match_exec(
+    parsed: &Vec<ParsedObject>,
+    req_obj: usize,
+    req_sym: usize,
+    prov_obj: usize,
+    prov_sym: usize,
+) -> (r: bool)
+    requires
+        req_obj < parsed@.len(),
+        prov_obj < parsed@.len(),
+    ensures
+        r == version_match(parsed@[req_obj as int], req_sym as int, parsed@[prov_obj as int], prov_sym as int),
+{
+    match required_version_exec(&parsed[req_obj], req_sym) {
+        Some(want) => match provided_version_exec(&parsed[prov_obj], prov_sym) {
+            Some(have) => cstr_eq_from_exec(
+                &parsed[req_obj].dynstr,
+                want as usize,
+                &parsed[prov_obj].dynstr,
+                have as usize,
+            ),
+            None => true,
+        },
+        None => prov_sym >= parsed[prov_obj].versym.len() || parsed[prov_obj].versym[prov_sym] & VERSYM_HIDDEN
+            == 0,
+    }
+}
+
+fn symbol_exported_exec(sym: &DynSymbol) -> (r: bool)
+    ensures
+        r == symbol_exported(*sym),
+{
+    let bind = sym.st_info >> 4;
+    let vis = sym.st_other & 3;
+    sym.st_shndx != 0 && bind != STB_LOCAL && (vis == STV_DEFAULT || vis == STV_PROTECTED)
+}
+
+fn binds_locally_exec(parsed: &Vec<ParsedObject>, req_obj: usize, req_sym: usize) -> (r: bool)
+    requires
+        req_obj < parsed@.len(),
+        req_sym < parsed@[req_obj as int].dynsyms@.len(),
+    ensures
+        r == binds_locally(parsed@, req_obj as int, req_sym as int),
+{
+    let sym = &parsed[req_obj].dynsyms[req_sym];
+    sym.st_shndx != 0 && (sym.st_other & 3 != STV_DEFAULT || sym.st_info >> 4 == STB_LOCAL)
+}
+
+pub fn symbol_match_exec(
     parsed: &Vec<ParsedObject>,
     req_obj: usize,
     req_sym: usize,
@@ -50,7 +167,7 @@ fn symbol_match_exec(
     let req_name = parsed[req_obj].dynsyms[req_sym].name_offset;
     let prov_sym_rec = &parsed[prov_obj].dynsyms[prov_sym];
     let prov_name = prov_sym_rec.name_offset;
-    if prov_sym_rec.st_shndx == 0 {
+    if !symbol_exported_exec(prov_sym_rec) {
         false
     } else {
         cstr_eq_from_exec(
@@ -58,137 +175,792 @@ fn symbol_match_exec(
             req_name as usize,
             &parsed[prov_obj].dynstr,
             prov_name as usize,
-        )
+        ) && version_match_exec(parsed, req_obj, req_sym, prov_obj, prov_sym)
     }
 }
 
-fn find_provider(
+fn candidate_match_exec(
     parsed: &Vec<ParsedObject>,
-    order: &Vec<usize>,
     req_obj: usize,
     req_sym: usize,
-) -> (r: Option<(usize, usize)>)
+    prov_obj: usize,
+    prov_sym: usize,
+    strong_only: bool,
+) -> (r: bool)
+    requires
+        req_obj < parsed@.len(),
+        req_sym < parsed@[req_obj as int].dynsyms@.len(),
+        prov_obj < parsed@.len(),
+        prov_sym < parsed@[prov_obj as int].dynsyms@.len(),
+    ensures
+        r == candidate_match(
+            parsed@,
+            req_obj as int,
+            req_sym as int,
+            prov_obj as int,
+            prov_sym as int,
+            strong_only,
+        ),
+{
+    if strong_only && parsed[prov_obj].dynsyms[prov_sym].st_info >> 4 == STB_WEAK {
+        false
+    } else {
+        symbol_match_exec(parsed, req_obj, req_sym, prov_obj, prov_sym)
+    }
+}
+
+proof fn lemma_cstr_eq_gnu_hash(a: Seq<u8>, ai: nat, b: Seq<u8>, bi: nat, h: u32)
+    requires
+        cstr_eq_from(a, ai, b, bi),
+    ensures
+        gnu_hash_from(a, ai, h) == gnu_hash_from(b, bi, h),
+    decreases a.len() - ai,
+{
+    if a[ai as int] != 0 {
+        lemma_cstr_eq_gnu_hash(
+            a,
+            (ai + 1) as nat,
+            b,
+            (bi + 1) as nat,
+            ((h as int * 33 + a[ai as int] as int) % 0x1_0000_0000) as u32,
+        );
+    }
+}
+
+proof fn lemma_cstr_eq_sysv_hash(a: Seq<u8>, ai: nat, b: Seq<u8>, bi: nat, h: u32)
+    requires
+        cstr_eq_from(a, ai, b, bi),
+    ensures
+        sysv_hash_from(a, ai, h) == sysv_hash_from(b, bi, h),
+    decreases a.len() - ai,
+{
+    if a[ai as int] != 0 {
+        lemma_cstr_eq_sysv_hash(a, (ai + 1) as nat, b, (bi + 1) as nat, sysv_hash_step(h, a[ai as int]));
+    }
+}
+
+proof fn lemma_match_same_hashes(
+    parsed: Seq<ParsedObject>,
+    req_obj: int,
+    req_sym: int,
+    prov_obj: int,
+    prov_sym: int,
+)
+    requires
+        symbol_match(parsed, req_obj, req_sym, prov_obj, prov_sym),
+    ensures
+        exported_symbol(parsed[prov_obj], prov_sym),
+        sym_gnu_hash(parsed[prov_obj], prov_sym) == gnu_hash_from(
+            parsed[req_obj].dynstr@,
+            parsed[req_obj].dynsyms@[req_sym].name_offset as nat,
+            5381,
+        ),
+        sym_sysv_hash(parsed[prov_obj], prov_sym) == sysv_hash_from(
+            parsed[req_obj].dynstr@,
+            parsed[req_obj].dynsyms@[req_sym].name_offset as nat,
+            0,
+        ),
+{
+    let a = parsed[req_obj].dynstr@;
+    let ai = parsed[req_obj].dynsyms@[req_sym].name_offset as nat;
+    let b = parsed[prov_obj].dynstr@;
+    let bi = parsed[prov_obj].dynsyms@[prov_sym].name_offset as nat;
+    lemma_cstr_eq_gnu_hash(a, ai, b, bi, 5381);
+    lemma_cstr_eq_sysv_hash(a, ai, b, bi, 0);
+}
+
+proof fn lemma_sysv_chain_stays_zero(t: SysvHashTable, start: int, k: nat, k2: nat)
+    requires
+        k <= k2,
+        sysv_chain_at(t, start, k) == 0,
+    ensures
+        sysv_chain_at(t, start, k2) == 0,
+    decreases k2 - k,
+{
+    if k < k2 {
+        lemma_sysv_chain_stays_zero(t, start, k, (k2 - 1) as nat);
+    }
+}
+
+fn gnu_hash_exec(bytes: &Vec<u8>, off: usize) -> (r: u32)
+    ensures
+        r == gnu_hash_from(bytes@, off as nat, 5381),
+{
+    let mut h: u32 = 5381;
+    let mut i = off;
+    while i < bytes.len() && bytes[i] != 0
+        invariant
+            gnu_hash_from(bytes@, i as nat, h) == gnu_hash_from(bytes@, off as nat, 5381),
+        decreases bytes.len() - i,
+    {
+        h = ((h as u64 * 33 + bytes[i] as u64) % 0x1_0000_0000) as u32;
+        i = i + 1;
+    }
+    h
+}
+
+fn sysv_hash_step_exec(h: u32, c: u8) -> (r: u32)
+    ensures
+        r == sysv_hash_step(h, c),
+{
+    let h1 = ((h as u64 * 16 + c as u64) % 0x1_0000_0000) as u32;
+    let g = h1 & 0xf000_0000u32;
+    (h1 ^ (g >> 24u32)) & !g
+}
+
+fn sysv_hash_exec(bytes: &Vec<u8>, off: usize) -> (r: u32)
+    ensures
+        r == sysv_hash_from(bytes@, off as nat, 0),
+{
+    let mut h: u32 = 0;
+    let mut i = off;
+    while i < bytes.len() && bytes[i] != 0
+        invariant
+            sysv_hash_from(bytes@, i as nat, h) == sysv_hash_from(bytes@, off as nat, 0),
+        decreases bytes.len() - i,
+    {
+        h = sysv_hash_step_exec(h, bytes[i]);
+        i = i + 1;
+    }
+    h
+}
+
+fn gnu_bloom_hit_exec(t: &GnuHashTable, h: u32) -> (r: bool)
+    requires
+        t.bloom@.len() > 0,
+        t.bloom_shift < 32,
+    ensures
+        r == gnu_bloom_hit(*t, h),
+{
+    let word = t.bloom[(h / 64) as usize % t.bloom.len()];
+    (word >> ((h % 64) as u64)) & 1 == 1 && (word >> (((h >> t.bloom_shift) % 64) as u64)) & 1 == 1
+}
+
+fn gnu_symbol_indexed_exec(obj: &ParsedObject, t: &GnuHashTable, s: usize) -> (r: bool)
+    requires
+        gnu_table_wf(*t, obj.dynsyms@.len()),
+        s < obj.dynsyms@.len(),
+    ensures
+        r ==> gnu_symbol_indexed(*obj, *t, s as int),
+{
+    let h = gnu_hash_exec(&obj.dynstr, obj.dynsyms[s].name_offset as usize);
+    if !gnu_bloom_hit_exec(t, h) {
+        return false;
+    }
+    let start = t.buckets[h as usize % t.buckets.len()] as usize;
+    let symoffset = t.symoffset as usize;
+    if start == 0 || start < symoffset || start > s {
+        return false;
+    }
+    if t.chains[s - symoffset] | 1 != h | 1 {
+        return false;
+    }
+    let mut j = start;
+    while j < s
+        invariant
+            gnu_table_wf(*t, obj.dynsyms@.len()),
+            s < obj.dynsyms@.len(),
+            symoffset == t.symoffset,
+            symoffset <= start <= j <= s,
+            gnu_chain_open(*t, start as int, j as int),
+        decreases s - j,
+    {
+        if t.chains[j - symoffset] & 1 != 0 {
+            return false;
+        }
+        j = j + 1;
+    }
+    true
+}
+
+fn sysv_symbol_indexed_exec(obj: &ParsedObject, t: &SysvHashTable, s: usize) -> (r: bool)
+    requires
+        sysv_table_wf(*t, obj.dynsyms@.len()),
+        s < obj.dynsyms@.len(),
+    ensures
+        r ==> sysv_symbol_indexed(*obj, *t, s as int),
+{
+    if s == 0 {
+        return false;
+    }
+    let h = sysv_hash_exec(&obj.dynstr, obj.dynsyms[s].name_offset as usize);
+    let ghost start = sysv_bucket_start(*t, h);
+    let mut cur = t.buckets[h as usize % t.buckets.len()] as usize;
+    let mut k: usize = 0;
+    while k < t.chains.len()
+        invariant
+            s > 0,
+            start == sysv_bucket_start(*t, sym_sysv_hash(*obj, s as int)),
+            cur as int == sysv_chain_at(*t, start, k as nat),
+        decreases t.chains.len() - k,
+    {
+        if cur == s {
+            proof {
+                assert(k < t.chains@.len() && sysv_chain_at(*t, start, k as nat) == s as int);
+            }
+            return true;
+        }
+        cur = if cur > 0 && cur < t.chains.len() {
+            t.chains[cur] as usize
+        } else {
+            0
+        };
+        k = k + 1;
+    }
+    false
+}
+
+fn symbol_index_complete_exec(obj: &ParsedObject) -> (r: bool)
+    ensures
+        r ==> symbol_index_complete(*obj),
+{
+    match &obj.hash_table {
+        Some(SymbolHashTable::Gnu(t)) => {
+            if t.buckets.len() == 0 || t.bloom.len() == 0 || t.bloom_shift >= 32 || t.symoffset as usize
+                > obj.dynsyms.len() || t.chains.len() != obj.dynsyms.len() - t.symoffset as usize
+            {
+                return false;
+            }
+            let mut s: usize = 0;
+            while s < obj.dynsyms.len()
+                invariant
+                    obj.hash_table == Some(SymbolHashTable::Gnu(*t)),
+                    gnu_table_wf(*t, obj.dynsyms@.len()),
+                    s <= obj.dynsyms@.len(),
+                    forall|s0: int|
+                        0 <= s0 < s && exported_symbol(*obj, s0) ==> gnu_symbol_indexed(*obj, *t, s0),
+                decreases obj.dynsyms.len() - s,
+            {
+                if symbol_exported_exec(&obj.dynsyms[s]) && !gnu_symbol_indexed_exec(obj, t, s) {
+                    return false;
+                }
+                s = s + 1;
+            }
+            true
+        },
+        Some(SymbolHashTable::Sysv(t)) => {
+            if t.buckets.len() == 0 || t.chains.len() != obj.dynsyms.len() {
+                return false;
+            }
+            let mut s: usize = 0;
+            while s < obj.dynsyms.len()
+                invariant
+                    obj.hash_table == Some(SymbolHashTable::Sysv(*t)),
+                    sysv_table_wf(*t, obj.dynsyms@.len()),
+                    s <= obj.dynsyms@.len(),
+                    forall|s0: int|
+                        0 <= s0 < s && exported_symbol(*obj, s0) ==> sysv_symbol_indexed(*obj, *t, s0),
+                decreases obj.dynsyms.len() - s,
+            {
+                if symbol_exported_exec(&obj.dynsyms[s]) && !sysv_symbol_indexed_exec(obj, t, s) {
+                    return false;
+                }
+                s = s + 1;
+            }
+            true
+        },
+        None => false,
+    }
+}
+
+fn find_in_symbols(
+    parsed: &Vec<ParsedObject>,
+    req_obj: usize,
+    req_sym: usize,
+    cand_obj: usize,
+    strong_only: bool,
+) -> (r: Option<usize>)
     requires
         req_obj < parsed@.len(),
         req_sym < parsed@[req_obj as int].dynsyms@.len(),
+        cand_obj < parsed@.len(),
     ensures
-        provider_result_spec(parsed@, order@, req_obj as int, req_sym as int, r),
+        match r {
+            Some(s) => s < parsed@[cand_obj as int].dynsyms@.len() && candidate_match(
+                parsed@,
+                req_obj as int,
+                req_sym as int,
+                cand_obj as int,
+                s as int,
+                strong_only,
+            ),
+            None => !obj_has_candidate(parsed@, req_obj as int, req_sym as int, cand_obj as int, strong_only),
+        },
 {
+    let mut s: usize = 0;
+    while s < parsed[cand_obj].dynsyms.len()
+        invariant
+            s <= parsed@[cand_obj as int].dynsyms@.len(),
+            req_obj < parsed.len(),
+            req_sym < parsed@[req_obj as int].dynsyms@.len(),
+            cand_obj < parsed.len(),
+            forall|s0: int|
+                0 <= s0 < s ==> !candidate_match(
+                    parsed@,
+                    req_obj as int,
+                    req_sym as int,
+                    cand_obj as int,
+                    s0,
+                    strong_only,
+                ),
+        decreases parsed@[cand_obj as int].dynsyms@.len() - s,
+    {
+        if candidate_match_exec(parsed, req_obj, req_sym, cand_obj, s, strong_only) {
+            return Some(s);
+        }
+        s = s + 1;
+    }
+    proof {
+        assert(s == parsed@[cand_obj as int].dynsyms@.len());
+        assert(!obj_has_candidate(parsed@, req_obj as int, req_sym as int, cand_obj as int, strong_only)) by {
+            if obj_has_candidate(parsed@, req_obj as int, req_sym as int, cand_obj as int, strong_only) {
+                let s0 = choose|s0: int|
+                    candidate_match(
+                        parsed@,
+                        req_obj as int,
+                        req_sym as int,
+                        cand_obj as int,
+                        s0,
+                        strong_only,
+                    );
+                assert(s0 < s as int);
+                assert(false);
+            }
+        };
+    }
+    None
+}
+
+fn find_in_gnu_table(
+    parsed: &Vec<ParsedObject>,
+    req_obj: usize,
+    req_sym: usize,
+    cand_obj: usize,
+    t: &GnuHashTable,
+    h: u32,
+    strong_only: bool,
+) -> (r: Option<usize>)
+    requires
+        req_obj < parsed@.len(),
+        req_sym < parsed@[req_obj as int].dynsyms@.len(),
+        cand_obj < parsed@.len(),
+        parsed@[cand_obj as int].hash_table == Some(SymbolHashTable::Gnu(*t)),
+        symbol_index_complete(parsed@[cand_obj as int]),
+        h == gnu_hash_from(
+            parsed@[req_obj as int].dynstr@,
+            parsed@[req_obj as int].dynsyms@[req_sym as int].name_offset as nat,
+            5381,
+        ),
+    ensures
+        match r {
+            Some(s) => s < parsed@[cand_obj as int].dynsyms@.len() && candidate_match(
+                parsed@,
+                req_obj as int,
+                req_sym as int,
+                cand_obj as int,
+                s as int,
+                strong_only,
+            ),
+            None => !obj_has_candidate(parsed@, req_obj as int, req_sym as int, cand_obj as int, strong_only),
+        },
+{
+    let ghost obj = parsed@[cand_obj as int];
+    if !gnu_bloom_hit_exec(t, h) {
+        proof {
+            if obj_has_candidate(parsed@, req_obj as int, req_sym as int, cand_obj as int, strong_only) {
+                let s0 = choose|s0: int|
+                    candidate_match(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0, strong_only);
+                lemma_match_same_hashes(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0);
+                assert(gnu_symbol_indexed(obj, *t, s0));
+            }
+        }
+        return None;
+    }
+    let start = t.buckets[h as usize % t.buckets.len()] as usize;
+    let symoffset = t.symoffset as usize;
+    if start == 0 || start < symoffset {
+        proof {
+            if obj_has_candidate(parsed@, req_obj as int, req_sym as int, cand_obj as int, strong_only) {
+                let s0 = choose|s0: int|
+                    candidate_match(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0, strong_only);
+                lemma_match_same_hashes(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0);
+                assert(gnu_symbol_indexed(obj, *t, s0));
+            }
+        }
+        return None;
+    }
+    let mut i = start;
+    while i < parsed[cand_obj].dynsyms.len()
+        invariant
+            req_obj < parsed@.len(),
+            req_sym < parsed@[req_obj as int].dynsyms@.len(),
+            cand_obj < parsed@.len(),
+            obj == parsed@[cand_obj as int],
+            obj.hash_table == Some(SymbolHashTable::Gnu(*t)),
+            symbol_index_complete(obj),
+            h == gnu_hash_from(
+                parsed@[req_obj as int].dynstr@,
+                parsed@[req_obj as int].dynsyms@[req_sym as int].name_offset as nat,
+                5381,
+            ),
+            start as int == gnu_bucket_start(*t, h),
+            symoffset == t.symoffset,
+            0 < start,
+            symoffset <= start <= i,
+            gnu_chain_open(*t, start as int, i as int),
+            forall|s0: int|
+                start <= s0 < i ==> !candidate_match(
+                    parsed@,
+                    req_obj as int,
+                    req_sym as int,
+                    cand_obj as int,
+                    s0,
+                    strong_only,
+                ),
+        decreases parsed@[cand_obj as int].dynsyms@.len() - i,
+    {
+        let ch = t.chains[i - symoffset];
+        if ch | 1 == h | 1 && candidate_match_exec(parsed, req_obj, req_sym, cand_obj, i, strong_only) {
+            return Some(i);
+        }
+        proof {
+            if candidate_match(parsed@, req_obj as int, req_sym as int, cand_obj as int, i as int, strong_only) {
+                lemma_match_same_hashes(parsed@, req_obj as int, req_sym as int, cand_obj as int, i as int);
+                assert(gnu_symbol_indexed(obj, *t, i as int));
+            }
+        }
+        if ch & 1 != 0 {
+            proof {
+                if obj_has_candidate(parsed@, req_obj as int, req_sym as int, cand_obj as int, strong_only) {
+                    let s0 = choose|s0: int|
+                        candidate_match(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0, strong_only);
+                    lemma_match_same_hashes(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0);
+                    assert(gnu_symbol_indexed(obj, *t, s0));
+                    if s0 > i as int {
+                        assert(t.chains@[i - symoffset] & 1 == 0);
+                    }
+                    assert(s0 < i as int);
+                }
+            }
+            return None;
+        }
+        i = i + 1;
+    }
     proof {
-        assert(req_obj < parsed.len());
+        if obj_has_candidate(parsed@, req_obj as int, req_sym as int, cand_obj as int, strong_only) {
+            let s0 = choose|s0: int|
+                candidate_match(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0, strong_only);
+            lemma_match_same_hashes(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0);
+            assert(gnu_symbol_indexed(obj, *t, s0));
+            assert(s0 < i as int);
+        }
     }
+    None
+}
 
+fn find_in_sysv_table(
+    parsed: &Vec<ParsedObject>,
+    req_obj: usize,
+    req_sym: usize,
+    cand_obj: usize,
+    t: &SysvHashTable,
+    h: u32,
+    strong_only: bool,
+) -> (r: Option<usize>)
+    requires
+        req_obj < parsed@.len(),
+        req_sym < parsed@[req_obj as int].dynsyms@.len(),
+        cand_obj < parsed@.len(),
+        parsed@[cand_obj as int].hash_table == Some(SymbolHashTable::Sysv(*t)),
+        symbol_index_complete(parsed@[cand_obj as int]),
+        h == sysv_hash_from(
+            parsed@[req_obj as int].dynstr@,
+            parsed@[req_obj as int].dynsyms@[req_sym as int].name_offset as nat,
+            0,
+        ),
+    ensures
+        match r {
+            Some(s) => s < parsed@[cand_obj as int].dynsyms@.len() && candidate_match(
+                parsed@,
+                req_obj as int,
+                req_sym as int,
+                cand_obj as int,
+                s as int,
+                strong_only,
+            ),
+            None => !obj_has_candidate(parsed@, req_obj as int, req_sym as int, cand_obj as int, strong_only),
+        },
+{
+    let ghost obj = parsed@[cand_obj as int];
+    let ghost start = sysv_bucket_start(*t, h);
+    let mut cur = t.buckets[h as usize % t.buckets.len()] as usize;
+    let mut k: usize = 0;
+    while k < t.chains.len()
+        invariant
+            req_obj < parsed@.len(),
+            req_sym < parsed@[req_obj as int].dynsyms@.len(),
+            cand_obj < parsed@.len(),
+            obj == parsed@[cand_obj as int],
+            obj.hash_table == Some(SymbolHashTable::Sysv(*t)),
+            symbol_index_complete(obj),
+            h == sysv_hash_from(
+                parsed@[req_obj as int].dynstr@,
+                parsed@[req_obj as int].dynsyms@[req_sym as int].name_offset as nat,
+                0,
+            ),
+            start == sysv_bucket_start(*t, h),
+            cur as int == sysv_chain_at(*t, start, k as nat),
+            forall|k0: nat|
+                k0 < k ==> !candidate_match(
+                    parsed@,
+                    req_obj as int,
+                    req_sym as int,
+                    cand_obj as int,
+                    #[trigger] sysv_chain_at(*t, start, k0),
+                    strong_only,
+                ),
+        decreases t.chains.len() - k,
+    {
+        if cur == 0 {
+            proof {
+                if obj_has_candidate(parsed@, req_obj as int, req_sym as int, cand_obj as int, strong_only) {
+                    let s0 = choose|s0: int|
+                        candidate_match(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0, strong_only);
+                    lemma_match_same_hashes(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0);
+                    assert(sysv_symbol_indexed(obj, *t, s0));
+                    let ks = choose|ks: nat| ks < t.chains@.len() && sysv_chain_at(*t, start, ks) == s0;
+                    if ks >= k {
+                        lemma_sysv_chain_stays_zero(*t, start, k as nat, ks);
+                    }
+                    assert(!candidate_match(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0, strong_only));
+                }
+            }
+            return None;
+        }
+        if cur < parsed[cand_obj].dynsyms.len() && candidate_match_exec(parsed, req_obj, req_sym, cand_obj, cur, strong_only) {
+            return Some(cur);
+        }
+        cur = if cur < t.chains.len() {
+            t.chains[cur] as usize
+        } else {
+            0
+        };
+        k = k + 1;
+    }
+    proof {
+        if obj_has_candidate(parsed@, req_obj as int, req_sym as int, cand_obj as int, strong_only) {
+            let s0 = choose|s0: int|
+                candidate_match(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0, strong_only);
+            lemma_match_same_hashes(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0);
+            assert(sysv_symbol_indexed(obj, *t, s0));
+            let ks = choose|ks: nat| ks < t.chains@.len() && sysv_chain_at(*t, start, ks) == s0;
+            assert(!candidate_match(parsed@, req_obj as int, req_sym as int, cand_obj as int, s0, strong_only));
+        }
+    }
+    None
+}
+
+fn find_in_scope(
+    parsed: &Vec<ParsedObject>,
+    indexed: &Vec<bool>,
+    order: &Vec<usize>,
+    req_obj: usize,
+    req_sym: usize,
+    gnu_h: u32,
+    sysv_h: u32,
+    strong_only: bool,
+) -> (r: Option<(usize, usize)>)
+    requires
+        req_obj < parsed@.len(),
+        req_sym < parsed@[req_obj as int].dynsyms@.len(),
+        indexed@.len() == parsed@.len(),
+        forall|i: int| 0 <= i < indexed@.len() && indexed@[i] ==> symbol_index_complete(parsed@[i]),
+        gnu_h == gnu_hash_from(
+            parsed@[req_obj as int].dynstr@,
+            parsed@[req_obj as int].dynsyms@[req_sym as int].name_offset as nat,
+            5381,
+        ),
+        sysv_h == sysv_hash_from(
+            parsed@[req_obj as int].dynstr@,
+            parsed@[req_obj as int].dynsyms@[req_sym as int].name_offset as nat,
+            0,
+        ),
+    ensures
+        match r {
+            Some((prov_obj, prov_sym)) => {
+                &&& candidate_match(
+                    parsed@,
+                    req_obj as int,
+                    req_sym as int,
+                    prov_obj as int,
+                    prov_sym as int,
+                    strong_only,
+                )
+                &&& first_in_order(parsed@, order@, req_obj as int, req_sym as int, prov_obj as int, strong_only)
+            },
+            None => forall|p: int|
+                0 <= p < order@.len() ==> !obj_has_candidate(
+                    parsed@,
+                    req_obj as int,
+                    req_sym as int,
+                    order@[p] as int,
+                    strong_only,
+                ),
+        },
+{
     let mut pos: usize = 0;
     while pos < order.len()
         invariant
             pos <= order.len(),
             req_obj < parsed.len(),
             req_sym < parsed@[req_obj as int].dynsyms@.len(),
+            indexed@.len() == parsed@.len(),
+            forall|i: int| 0 <= i < indexed@.len() && indexed@[i] ==> symbol_index_complete(parsed@[i]),
+            gnu_h == gnu_hash_from(
+                parsed@[req_obj as int].dynstr@,
+                parsed@[req_obj as int].dynsyms@[req_sym as int].name_offset as nat,
+                5381,
+            ),
+            sysv_h == sysv_hash_from(
+                parsed@[req_obj as int].dynstr@,
+                parsed@[req_obj as int].dynsyms@[req_sym as int].name_offset as nat,
+                0,
+            ),
             forall|p: int|
-                0 <= p < pos ==> !obj_has_match(
+                0 <= p < pos ==> !obj_has_candidate(
                     parsed@,
                     req_obj as int,
                     req_sym as int,
                     order@[p] as int,
+                    strong_only,
                 ),
         decreases order.len() - pos,
     {
         let cand_obj = order[pos];
         if cand_obj < parsed.len() {
-            let mut s: usize = 0;
-            while s < parsed[cand_obj].dynsyms.len()
-                invariant
-                    s <= parsed@[cand_obj as int].dynsyms@.len(),
-                    req_obj < parsed.len(),
-                    req_sym < parsed@[req_obj as int].dynsyms@.len(),
-                    cand_obj < parsed.len(),
-                    forall|p: int|
-                        0 <= p < pos ==> !obj_has_match(
-                            parsed@,
-                            req_obj as int,
-                            req_sym as int,
-                            order@[p] as int,
-                        ),
-                    forall|s0: int|
-                        0 <= s0 < s ==> !symbol_match(
-                            parsed@,
-                            req_obj as int,
-                            req_sym as int,
-                            cand_obj as int,
-                            s0,
-                        ),
-                decreases parsed@[cand_obj as int].dynsyms@.len() - s,
-            {
-                let m = symbol_match_exec(parsed, req_obj, req_sym, cand_obj, s);
-                if m {
+            let found = if indexed[cand_obj] {
+                match &parsed[cand_obj].hash_table {
+                    Some(SymbolHashTable::Gnu(t)) => find_in_gnu_table(
+                        parsed,
+                        req_obj,
+                        req_sym,
+                        cand_obj,
+                        t,
+                        gnu_h,
+                        strong_only,
+                    ),
+                    Some(SymbolHashTable::Sysv(t)) => find_in_sysv_table(
+                        parsed,
+                        req_obj,
+                        req_sym,
+                        cand_obj,
+                        t,
+                        sysv_h,
+                        strong_only,
+                    ),
+                    None => find_in_symbols(parsed, req_obj, req_sym, cand_obj, strong_only),
+                }
+            } else {
+                find_in_symbols(parsed, req_obj, req_sym, cand_obj, strong_only)
+            };
+            match found {
+                Some(s) => {
                     proof {
-                        assert(symbol_match(
+                        assert(no_candidate_before(
                             parsed@,
+                            order@,
                             req_obj as int,
                             req_sym as int,
-                            cand_obj as int,
-                            s as int,
+                            pos as int,
+                            strong_only,
                         ));
-                        assert(provider_result_spec(
+                        assert(order@[pos as int] as int == cand_obj as int);
+                    }
+                    return Some((cand_obj, s));
+                },
+                None => {},
+            }
+        }
+        pos = pos + 1;
+    }
+    None
+}
+
+fn find_provider(
+    parsed: &Vec<ParsedObject>,
+    indexed: &Vec<bool>,
+    order: &Vec<usize>,
+    strong_first: bool,
+    req_obj: usize,
+    req_sym: usize,
+) -> (r: Option<(usize, usize)>)
+    requires
+        req_obj < parsed@.len(),
+        req_sym < parsed@[req_obj as int].dynsyms@.len(),
+        indexed@.len() == parsed@.len(),
+        forall|i: int| 0 <= i < indexed@.len() && indexed@[i] ==> symbol_index_complete(parsed@[i]),
+    ensures
+        provider_result_spec(parsed@, order@, strong_first, req_obj as int, req_sym as int, r),
+{
+    if binds_locally_exec(parsed, req_obj, req_sym) {
+        return Some((req_obj, req_sym));
+    }
+    let name_off = parsed[req_obj].dynsyms[req_sym].name_offset as usize;
+    let gnu_h = gnu_hash_exec(&parsed[req_obj].dynstr, name_off);
+    let sysv_h = sysv_hash_exec(&parsed[req_obj].dynstr, name_off);
+
+    if strong_first {
+        match find_in_scope(parsed, indexed, order, req_obj, req_sym, gnu_h, sysv_h, true) {
+            Some(found) => {
+                return Some(found);
+            },
+            None => {},
+        }
+    }
+    let r = find_in_scope(parsed, indexed, order, req_obj, req_sym, gnu_h, sysv_h, false);
+    proof {
+        match r {
+            Some((po, ps)) => {
+                if strong_first && symbol_binding(parsed@[po as int].dynsyms@[ps as int]) != STB_WEAK {
+                    let p = choose|p: int|
+                        0 <= p < order@.len() && order@[p] as int == po as int && no_candidate_before(
                             parsed@,
                             order@,
                             req_obj as int,
                             req_sym as int,
-                            Some((cand_obj, s)),
-                        ));
-                    }
-                    return Some((cand_obj, s));
+                            p,
+                            false,
+                        );
+                    assert(candidate_match(parsed@, req_obj as int, req_sym as int, po as int, ps as int, true));
+                    assert(obj_has_candidate(parsed@, req_obj as int, req_sym as int, order@[p] as int, true));
+                    assert(false);
                 }
-                proof {
-                    assert(!symbol_match(
+            },
+            None => {},
+        }
+        if r.is_none() {
+            assert forall|p: int| 0 <= p < order@.len() implies !obj_has_match(
+                parsed@,
+                req_obj as int,
+                req_sym as int,
+                order@[p] as int,
+            ) by {
+                if obj_has_match(parsed@, req_obj as int, req_sym as int, order@[p] as int) {
+                    let s0 = choose|s0: int|
+                        symbol_match(parsed@, req_obj as int, req_sym as int, order@[p] as int, s0);
+                    assert(candidate_match(
                         parsed@,
                         req_obj as int,
                         req_sym as int,
-                        cand_obj as int,
-                        s as int,
+                        order@[p] as int,
+                        s0,
+                        false,
                     ));
                 }
-                s = s + 1;
-            }
-            proof {
-                assert(s == parsed@[cand_obj as int].dynsyms@.len());
-                assert(!obj_has_match(parsed@, req_obj as int, req_sym as int, cand_obj as int)) by {
-                    if obj_has_match(parsed@, req_obj as int, req_sym as int, cand_obj as int) {
-                        let s0 = choose|s0: int|
-                            symbol_match(
-                                parsed@,
-                                req_obj as int,
-                                req_sym as int,
-                                cand_obj as int,
-                                s0,
-                            );
-                        assert(s0 < s as int);
-                        assert(!symbol_match(
-                            parsed@,
-                            req_obj as int,
-                            req_sym as int,
-                            cand_obj as int,
-                            s0,
-                        ));
-                        assert(false);
-                    }
-                };
-            }
+            };
         }
-        pos = pos + 1;
-    }
-
-    proof {
-        assert(pos == order.len());
-        assert(provider_result_spec(
-            parsed@,
-            order@,
-            req_obj as int,
-            req_sym as int,
-            None,
-        ));
     }
-    None
+    r
 }
 
 fn symbol_is_weak_undef(sym: &DynSymbol) -> bool {
@@ -201,16 +973,18 @@ fn symbol_relocation_requires_provider(rel_type: u32, sym: &DynSymbol) -> bool {
         true
     } else {
         (rel_type == R_X86_64_JUMP_SLOT || rel_type == R_X86_64_GLOB_DAT || rel_type
-            == R_X86_64_64) && !symbol_is_weak_undef(sym)
+            == R_X86_64_64 || rel_type == R_X86_64_DTPMOD64 || rel_type == R_X86_64_DTPOFF64
+            || rel_type == R_X86_64_TPOFF64) && !symbol_is_weak_undef(sym)
     }
 }
 
 pub fn resolve_stage_ref(
     parsed: &Vec<ParsedObject>,
     discovered: &DiscoveryResult,
+    strong_first: bool,
 ) -> (out: Result<ResolutionResult, LoaderError>)
     ensures
-        out.is_ok() ==> resolve_stage_spec(parsed@, *discovered, out.unwrap()),
+        out.is_ok() ==> resolve_stage_spec(parsed@, *discovered, strong_first, out.unwrap()),
 {
     let mut planned: Vec<PlannedObject> = Vec::new();
     let mut pi: usize = 0;
@@ -225,21 +999,41 @@ pub fn resolve_stage_ref(
     {
         let idx = discovered.order[pi];
         if idx >= parsed.len() {
-            return Err(LoaderError {});
+            return Err(loader_error(
+                LoaderStage::Resolve,
+                &Vec::new(),
+                LoaderErrorReason::InvalidObjectIndex { index: idx },
+            ));
         }
         planned.push(PlannedObject { index: idx, base: 0 });
         pi = pi + 1;
     }
 
+    let mut indexed: Vec<bool> = Vec::new();
+    let mut xi: usize = 0;
+    while xi < parsed.len()
+        invariant
+            xi <= parsed.len(),
+            indexed@.len() == xi,
+            forall|i: int| 0 <= i < indexed@.len() && indexed@[i] ==> symbol_index_complete(parsed@[i]),
+        decreases parsed.len() - xi,
+    {
+        indexed.push(symbol_index_complete_exec(&parsed[xi]));
+        xi = xi + 1;
+    }
+
     let mut resolved_relocs: Vec<ResolvedReloc> = Vec::new();
     let mut oi: usize = 0;
     while oi < discovered.order.len()
         invariant
             oi <= discovered.order.len(),
+            indexed@.len() == parsed@.len(),
+            forall|i: int| 0 <= i < indexed@.len() && indexed@[i] ==> symbol_index_complete(parsed@[i]),
             forall|k: int|
                 0 <= k < resolved_relocs@.len() ==> resolved_reloc_spec(
                     parsed@,
                     discovered.order@,
+                    strong_first,
                     resolved_relocs@[k],
                 ),
         decreases discovered.order.len() - oi,
@@ -253,10 +1047,13 @@ pub fn resolve_stage_ref(
                     oi < discovered.order.len(),
                     obj_idx == discovered.order@[oi as int],
                     obj_idx < parsed.len(),
+                    indexed@.len() == parsed@.len(),
+                    forall|i: int| 0 <= i < indexed@.len() && indexed@[i] ==> symbol_index_complete(parsed@[i]),
                     forall|k: int|
                         0 <= k < resolved_relocs@.len() ==> resolved_reloc_spec(
                             parsed@,
                             discovered.order@,
+                            strong_first,
                             resolved_relocs@[k],
                         ),
                 decreases parsed@[obj_idx as int].relas@.len() - ri,
@@ -268,7 +1065,11 @@ pub fn resolve_stage_ref(
                     || rel_type == R_X86_64_COPY || rel_type == R_X86_64_64
                 {
                     if sym_idx == 0 || sym_idx >= parsed[obj_idx].dynsyms.len() {
-                        return Err(LoaderError {});
+                        return Err(loader_error(
+                            LoaderStage::Resolve,
+                            &parsed[obj_idx].input_name,
+                            LoaderErrorReason::BadSymbolIndex { sym_index: sym_idx },
+                        ));
                     }
                 }
                 if sym_idx > 0 {
@@ -279,10 +1080,18 @@ pub fn resolve_stage_ref(
                             assert(sym_idx < parsed@[obj_idx as int].dynsyms@.len());
                         }
                         provider_required = symbol_relocation_requires_provider(rel_type, &parsed[obj_idx].dynsyms[sym_idx]);
-                        prov = find_provider(parsed, &discovered.order, obj_idx, sym_idx);
+                        prov = find_provider(parsed, &indexed, &discovered.order, strong_first, obj_idx, sym_idx);
                     }
                     if provider_required && prov.is_none() {
-                        return Err(LoaderError {});
+                        let sym_name = cstr_at(
+                            parsed[obj_idx].dynstr.as_slice(),
+                            parsed[obj_idx].dynsyms[sym_idx].name_offset,
+                        );
+                        return Err(loader_error(
+                            LoaderStage::Resolve,
+                            &parsed[obj_idx].input_name,
+                            LoaderErrorReason::UndefinedSymbol { name: sym_name },
+                        ));
                     }
 
                     let new_rr = match prov {
@@ -311,6 +1120,7 @@ pub fn resolve_stage_ref(
                             0 <= k < resolved_relocs@.len() implies resolved_reloc_spec(
                                 parsed@,
                                 discovered.order@,
+                                strong_first,
                                 resolved_relocs@[k],
                             ) by {
                             if k < old_rrs.len() {
@@ -320,6 +1130,7 @@ pub fn resolve_stage_ref(
                                     assert(provider_result_spec(
                                         parsed@,
                                         discovered.order@,
+                                        strong_first,
                                         obj_idx as int,
                                         sym_idx as int,
                                         prov,
@@ -331,6 +1142,7 @@ pub fn resolve_stage_ref(
                                 assert(resolved_reloc_spec(
                                     parsed@,
                                     discovered.order@,
+                                    strong_first,
                                     new_rr,
                                 ));
                             }
@@ -347,10 +1159,13 @@ pub fn resolve_stage_ref(
                     oi < discovered.order.len(),
                     obj_idx == discovered.order@[oi as int],
                     obj_idx < parsed.len(),
+                    indexed@.len() == parsed@.len(),
+                    forall|i: int| 0 <= i < indexed@.len() && indexed@[i] ==> symbol_index_complete(parsed@[i]),
                     forall|k: int|
                         0 <= k < resolved_relocs@.len() ==> resolved_reloc_spec(
                             parsed@,
                             discovered.order@,
+                            strong_first,
                             resolved_relocs@[k],
                         ),
                 decreases parsed@[obj_idx as int].jmprels@.len() - ji,
@@ -362,7 +1177,11 @@ pub fn resolve_stage_ref(
                     || rel_type == R_X86_64_COPY || rel_type == R_X86_64_64
                 {
                     if sym_idx == 0 || sym_idx >= parsed[obj_idx].dynsyms.len() {
-                        return Err(LoaderError {});
+                        return Err(loader_error(
+                            LoaderStage::Resolve,
+                            &parsed[obj_idx].input_name,
+                            LoaderErrorReason::BadSymbolIndex { sym_index: sym_idx },
+                        ));
                     }
                 }
                 if sym_idx > 0 {
@@ -373,10 +1192,18 @@ pub fn resolve_stage_ref(
                             assert(sym_idx < parsed@[obj_idx as int].dynsyms@.len());
                         }
                         provider_required = symbol_relocation_requires_provider(rel_type, &parsed[obj_idx].dynsyms[sym_idx]);
-                        prov = find_provider(parsed, &discovered.order, obj_idx, sym_idx);
+                        prov = find_provider(parsed, &indexed, &discovered.order, strong_first, obj_idx, sym_idx);
                     }
                     if provider_required && prov.is_none() {
-                        return Err(LoaderError {});
+                        let sym_name = cstr_at(
+                            parsed[obj_idx].dynstr.as_slice(),
+                            parsed[obj_idx].dynsyms[sym_idx].name_offset,
+                        );
+                        return Err(loader_error(
+                            LoaderStage::Resolve,
+                            &parsed[obj_idx].input_name,
+                            LoaderErrorReason::UndefinedSymbol { name: sym_name },
+                        ));
                     }
 
                     let new_rr = match prov {
@@ -405,6 +1232,7 @@ pub fn resolve_stage_ref(
                             0 <= k < resolved_relocs@.len() implies resolved_reloc_spec(
                                 parsed@,
                                 discovered.order@,
+                                strong_first,
                                 resolved_relocs@[k],
                             ) by {
                             if k < old_rrs.len() {
@@ -414,6 +1242,7 @@ pub fn resolve_stage_ref(
                                     assert(provider_result_spec(
                                         parsed@,
                                         discovered.order@,
+                                        strong_first,
                                         obj_idx as int,
                                         sym_idx as int,
                                         prov,
@@ -425,6 +1254,7 @@ pub fn resolve_stage_ref(
                                 assert(resolved_reloc_spec(
                                     parsed@,
                                     discovered.order@,
+                                    strong_first,
                                     new_rr,
                                 ));
                             }
@@ -434,7 +1264,11 @@ pub fn resolve_stage_ref(
                 ji = ji + 1;
             }
         } else {
-            return Err(LoaderError {});
+            return Err(loader_error(
+                LoaderStage::Resolve,
+                &Vec::new(),
+                LoaderErrorReason::InvalidObjectIndex { index: obj_idx },
+            ));
         }
         oi = oi + 1;
     }
@@ -445,6 +1279,7 @@ pub fn resolve_stage_ref(
             0 <= k < resolved_relocs@.len() ==> resolved_reloc_spec(
                 parsed@,
                 discovered.order@,
+                strong_first,
                 resolved_relocs@[k],
             ));
     }
@@ -455,11 +1290,12 @@ pub fn resolve_stage_ref(
 pub fn resolve_stage(
     parsed: Vec<ParsedObject>,
     discovered: DiscoveryResult,
+    strong_first: bool,
 ) -> (out: Result<ResolutionResult, LoaderError>)
     ensures
-        out.is_ok() ==> resolve_stage_spec(parsed@, discovered, out.unwrap()),
+        out.is_ok() ==> resolve_stage_spec(parsed@, discovered, strong_first, out.unwrap()),
 {
-    resolve_stage_ref(&parsed, &discovered)
+    resolve_stage_ref(&parsed, &discovered, strong_first)
 }
 
 } // verus!
@@ -1,3 +1,4 @@
+use crate::consts::*;
 use crate::discover_spec::cstr_eq_from;
 use crate::types::*;
 use vstd::prelude::*;
@@ -11,6 +12,22 @@ pub open spec fn provider_pair(r: ResolvedReloc) -> Option<(usize, usize)> {
     }
 }
 
+pub open spec fn symbol_binding(sym: DynSymbol) -> u8 {
+    sym.st_info >> 4u8
+}
+
+pub open spec fn symbol_visibility(sym: DynSymbol) -> u8 {
+    sym.st_other & 3u8
+}
+
+// Only global or weak definitions with default or protected visibility are
+// visible to other objects.
+pub open spec fn symbol_exported(sym: DynSymbol) -> bool {
+    &&& sym.st_shndx != 0
+    &&& symbol_binding(sym) != STB_LOCAL
+    &&& symbol_visibility(sym) == STV_DEFAULT || symbol_visibility(sym) == STV_PROTECTED
+}
+
 pub open spec fn symbol_match(
     parsed: Seq<ParsedObject>,
     req_obj: int,
@@ -22,13 +39,116 @@ pub open spec fn symbol_match(
     &&& 0 <= req_sym < parsed[req_obj].dynsyms@.len()
     &&& 0 <= prov_obj < parsed.len()
     &&& 0 <= prov_sym < parsed[prov_obj].dynsyms@.len()
-    &&& parsed[prov_obj].dynsyms@[prov_sym].st_shndx != 0
+    &&& symbol_exported(parsed[prov_obj].dynsyms@[prov_sym])
     &&& cstr_eq_from(
         parsed[req_obj].dynstr@,
         parsed[req_obj].dynsyms@[req_sym].name_offset as nat,
         parsed[prov_obj].dynstr@,
         parsed[prov_obj].dynsyms@[prov_sym].name_offset as nat,
     )
+    &&& version_match(parsed[req_obj], req_sym, parsed[prov_obj], prov_sym)
+}
+
+pub open spec fn versym_of(obj: ParsedObject, s: int) -> Option<u16> {
+    if 0 <= s < obj.versym@.len() {
+        Some(obj.versym@[s])
+    } else {
+        None
+    }
+}
+
+pub open spec fn verneed_name_from(needs: Seq<VersionNeed>, index: u16, i: nat) -> Option<u32>
+    decreases needs.len() - i,
+{
+    if i >= needs.len() {
+        None
+    } else if needs[i as int].index == index {
+        Some(needs[i as int].name_offset)
+    } else {
+        verneed_name_from(needs, index, (i + 1) as nat)
+    }
+}
+
+pub open spec fn verdef_name_from(defs: Seq<VersionDef>, index: u16, i: nat) -> Option<u32>
+    decreases defs.len() - i,
+{
+    if i >= defs.len() {
+        None
+    } else if defs[i as int].index == index {
+        Some(defs[i as int].name_offset)
+    } else {
+        verdef_name_from(defs, index, (i + 1) as nat)
+    }
+}
+
+// Version a symbol reference asks for: its versym index names a
+// `.gnu.version_r` entry (or, for self-references, a `.gnu.version_d` entry).
+// Indices 0 and 1 are unversioned.
+pub open spec fn required_version(obj: ParsedObject, s: int) -> Option<u32> {
+    match versym_of(obj, s) {
+        Some(v) => if v & VERSYM_VERSION >= 2 {
+            match verneed_name_from(obj.verneeds@, v & VERSYM_VERSION, 0) {
+                Some(name) => Some(name),
+                None => verdef_name_from(obj.verdefs@, v & VERSYM_VERSION, 0),
+            }
+        } else {
+            None
+        },
+        None => None,
+    }
+}
+
+pub open spec fn provided_version(obj: ParsedObject, s: int) -> Option<u32> {
+    match versym_of(obj, s) {
+        Some(v) => if v & VERSYM_VERSION >= 2 {
+            verdef_name_from(obj.verdefs@, v & VERSYM_VERSION, 0)
+        } else {
+            None
+        },
+        None => None,
+    }
+}
+
+pub open spec fn version_hidden(obj: ParsedObject, s: int) -> bool {
+    match versym_of(obj, s) {
+        Some(v) => v & VERSYM_HIDDEN != 0,
+        None => false,
+    }
+}
+
+// A versioned reference binds to a definition of that version or to an
+// unversioned one; an unversioned reference binds to anything but a hidden
+// (non-default, `name@VER`) definition.
+pub open spec fn version_match(req: ParsedObject, req_sym: int, prov: ParsedObject, prov_sym: int) -> bool {
+    match required_version(req, req_sym) {
+        Some(want) => match provided_version(prov, prov_sym) {
+            Some(have) => cstr_eq_from(req.dynstr@, want as nat, prov.dynstr@, have as nat),
+            None => true,
+        },
+        None => !version_hidden(prov, prov_sym),
+    }
+}
+
+pub open spec fn candidate_match(
+    parsed: Seq<ParsedObject>,
+    req_obj: int,
+    req_sym: int,
+    prov_obj: int,
+    prov_sym: int,
+    strong_only: bool,
+) -> bool {
+    &&& symbol_match(parsed, req_obj, req_sym, prov_obj, prov_sym)
+    &&& strong_only ==> symbol_binding(parsed[prov_obj].dynsyms@[prov_sym]) != STB_WEAK
+}
+
+pub open spec fn obj_has_candidate(
+    parsed: Seq<ParsedObject>,
+    req_obj: int,
+    req_sym: int,
+    prov_obj: int,
+    strong_only: bool,
+) -> bool {
+    exists|s: int| candidate_match(parsed, req_obj, req_sym, prov_obj, s, strong_only)
 }
 
 pub open spec fn obj_has_match(
@@ -40,29 +160,192 @@ pub open spec fn obj_has_match(
     exists|s: int| symbol_match(parsed, req_obj, req_sym, prov_obj, s)
 }
 
-pub open spec fn provider_result_spec(
+// A reference to a symbol the requester itself defines with non-default
+// visibility (or local binding) never leaves the requester.
+pub open spec fn binds_locally(parsed: Seq<ParsedObject>, req_obj: int, req_sym: int) -> bool {
+    let sym = parsed[req_obj].dynsyms@[req_sym];
+    &&& sym.st_shndx != 0
+    &&& symbol_visibility(sym) != STV_DEFAULT || symbol_binding(sym) == STB_LOCAL
+}
+
+pub open spec fn no_candidate_before(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
     req_obj: int,
     req_sym: int,
-    out: Option<(usize, usize)>,
+    end: int,
+    strong_only: bool,
 ) -> bool {
-    match out {
-        Some((prov_obj, prov_sym)) => symbol_match(
+    forall|q: int| 0 <= q < end ==> !obj_has_candidate(parsed, req_obj, req_sym, order[q] as int, strong_only)
+}
+
+// `prov_obj` is the first object in `order` with a candidate definition.
+pub open spec fn first_in_order(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    req_obj: int,
+    req_sym: int,
+    prov_obj: int,
+    strong_only: bool,
+) -> bool {
+    exists|p: int|
+        0 <= p < order.len() && order[p] as int == prov_obj && no_candidate_before(
             parsed,
+            order,
             req_obj,
             req_sym,
-            prov_obj as int,
-            prov_sym as int,
-        ),
-        None => forall|p: int| 0 <= p < order.len() ==> !obj_has_match(parsed, req_obj, req_sym, order[p]
-            as int),
+            p,
+            strong_only,
+        )
+}
+
+// Outside local binding, the provider is the first object in load order that
+// defines the symbol; with `strong_first`, a strong definition anywhere in
+// the order beats every weak one.
+pub open spec fn provider_result_spec(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    strong_first: bool,
+    req_obj: int,
+    req_sym: int,
+    out: Option<(usize, usize)>,
+) -> bool {
+    if binds_locally(parsed, req_obj, req_sym) {
+        out == Some((req_obj as usize, req_sym as usize))
+    } else {
+        match out {
+            Some((prov_obj, prov_sym)) => {
+                let weak = symbol_binding(parsed[prov_obj as int].dynsyms@[prov_sym as int]) == STB_WEAK;
+                &&& symbol_match(parsed, req_obj, req_sym, prov_obj as int, prov_sym as int)
+                &&& first_in_order(parsed, order, req_obj, req_sym, prov_obj as int, strong_first && !weak)
+                &&& strong_first && weak ==> forall|p: int|
+                    0 <= p < order.len() ==> !obj_has_candidate(parsed, req_obj, req_sym, order[p] as int, true)
+            },
+            None => forall|p: int|
+                0 <= p < order.len() ==> !obj_has_match(parsed, req_obj, req_sym, order[p] as int),
+        }
+    }
+}
+
+pub open spec fn gnu_hash_from(bytes: Seq<u8>, i: nat, h: u32) -> u32
+    decreases bytes.len() - i,
+{
+    if i >= bytes.len() || bytes[i as int] == 0 {
+        h
+    } else {
+        gnu_hash_from(bytes, (i + 1) as nat, ((h as int * 33 + bytes[i as int] as int) % 0x1_0000_0000) as u32)
+    }
+}
+
+pub open spec fn sysv_hash_step(h: u32, c: u8) -> u32 {
+    let h1 = ((h as int * 16 + c as int) % 0x1_0000_0000) as u32;
+    let g = h1 & 0xf000_0000u32;
+    (h1 ^ (g >> 24u32)) & !g
+}
+
+pub open spec fn sysv_hash_from(bytes: Seq<u8>, i: nat, h: u32) -> u32
+    decreases bytes.len() - i,
+{
+    if i >= bytes.len() || bytes[i as int] == 0 {
+        h
+    } else {
+        sysv_hash_from(bytes, (i + 1) as nat, sysv_hash_step(h, bytes[i as int]))
+    }
+}
+
+pub open spec fn sym_gnu_hash(obj: ParsedObject, s: int) -> u32 {
+    gnu_hash_from(obj.dynstr@, obj.dynsyms@[s].name_offset as nat, 5381)
+}
+
+pub open spec fn sym_sysv_hash(obj: ParsedObject, s: int) -> u32 {
+    sysv_hash_from(obj.dynstr@, obj.dynsyms@[s].name_offset as nat, 0)
+}
+
+pub open spec fn gnu_table_wf(t: GnuHashTable, nsyms: nat) -> bool {
+    &&& t.buckets@.len() > 0
+    &&& t.bloom@.len() > 0
+    &&& t.bloom_shift < 32
+    &&& t.symoffset as int + t.chains@.len() == nsyms
+}
+
+pub open spec fn gnu_bloom_hit(t: GnuHashTable, h: u32) -> bool {
+    let word = t.bloom@[(h / 64) as int % t.bloom@.len() as int];
+    &&& (word >> ((h % 64) as u64)) & 1 == 1
+    &&& (word >> (((h >> t.bloom_shift) % 64) as u64)) & 1 == 1
+}
+
+pub open spec fn gnu_bucket_start(t: GnuHashTable, h: u32) -> int {
+    t.buckets@[h as int % t.buckets@.len() as int] as int
+}
+
+pub open spec fn gnu_chain_open(t: GnuHashTable, from: int, to: int) -> bool {
+    forall|j: int| from <= j < to ==> t.chains@[j - t.symoffset as int] & 1 == 0
+}
+
+pub open spec fn gnu_symbol_indexed(obj: ParsedObject, t: GnuHashTable, s: int) -> bool {
+    let h = sym_gnu_hash(obj, s);
+    let start = gnu_bucket_start(t, h);
+    &&& gnu_bloom_hit(t, h)
+    &&& 0 < start
+    &&& t.symoffset as int <= start <= s
+    &&& t.chains@[s - t.symoffset as int] | 1 == h | 1
+    &&& gnu_chain_open(t, start, s)
+}
+
+pub open spec fn sysv_table_wf(t: SysvHashTable, nsyms: nat) -> bool {
+    &&& t.buckets@.len() > 0
+    &&& t.chains@.len() == nsyms
+}
+
+pub open spec fn sysv_bucket_start(t: SysvHashTable, h: u32) -> int {
+    t.buckets@[h as int % t.buckets@.len() as int] as int
+}
+
+pub open spec fn sysv_chain_at(t: SysvHashTable, start: int, k: nat) -> int
+    decreases k,
+{
+    if k == 0 {
+        start
+    } else {
+        let p = sysv_chain_at(t, start, (k - 1) as nat);
+        if 0 < p < t.chains@.len() {
+            t.chains@[p] as int
+        } else {
+            0
+        }
+    }
+}
+
+pub open spec fn sysv_symbol_indexed(obj: ParsedObject, t: SysvHashTable, s: int) -> bool {
+    let start = sysv_bucket_start(t, sym_sysv_hash(obj, s));
+    &&& s != 0
+    &&& exists|k: nat| k < t.chains@.len() && sysv_chain_at(t, start, k) == s
+}
+
+pub open spec fn exported_symbol(obj: ParsedObject, s: int) -> bool {
+    0 <= s < obj.dynsyms@.len() && symbol_exported(obj.dynsyms@[s])
+}
+
+// Holds when every exported symbol of `obj` is reachable through its hash table,
+// so a failed table lookup is as good as a failed linear scan.
+pub open spec fn symbol_index_complete(obj: ParsedObject) -> bool {
+    match obj.hash_table {
+        Some(SymbolHashTable::Gnu(t)) => {
+            &&& gnu_table_wf(t, obj.dynsyms@.len())
+            &&& forall|s: int| exported_symbol(obj, s) ==> gnu_symbol_indexed(obj, t, s)
+        },
+        Some(SymbolHashTable::Sysv(t)) => {
+            &&& sysv_table_wf(t, obj.dynsyms@.len())
+            &&& forall|s: int| exported_symbol(obj, s) ==> sysv_symbol_indexed(obj, t, s)
+        },
+        None => false,
     }
 }
 
 pub open spec fn resolved_reloc_spec(
     parsed: Seq<ParsedObject>,
     order: Seq<usize>,
+    strong_first: bool,
     rr: ResolvedReloc,
 ) -> bool {
     let req_obj = rr.requester as int;
@@ -74,7 +357,7 @@ pub open spec fn resolved_reloc_spec(
         _ => true,
     }
     &&& if 0 <= req_obj < parsed.len() && 0 <= req_sym < parsed[req_obj].dynsyms@.len() {
-        provider_result_spec(parsed, order, req_obj, req_sym, provider_pair(rr))
+        provider_result_spec(parsed, order, strong_first, req_obj, req_sym, provider_pair(rr))
     } else {
         rr.provider_object.is_none() && rr.provider_symbol.is_none()
     }
@@ -89,6 +372,7 @@ pub open spec fn planned_scope_spec(load_order: Seq<usize>, planned: Seq<Planned
 pub open spec fn resolve_stage_spec(
     parsed: Seq<ParsedObject>,
     discovered: DiscoveryResult,
+    strong_first: bool,
     out: ResolutionResult,
 ) -> bool {
     &&& planned_scope_spec(discovered.order@, out.planned@)
@@ -96,6 +380,7 @@ pub open spec fn resolve_stage_spec(
         0 <= i < out.resolved_relocs@.len() ==> resolved_reloc_spec(
             parsed,
             discovered.order@,
+            strong_first,
             out.resolved_relocs@[i],
         )
 }
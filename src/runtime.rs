@@ -1,8 +1,16 @@
-use crate::types::{LoaderError, LoaderOutput, MmapPlan, ProtFlags};
+use crate::mmap_plan_impl::{load_align_exec, load_span_hi_exec, load_span_lo_exec};
+use crate::types::{
+    LoaderError, LoaderErrorReason, LoaderOutput, LoaderStage, MmapPlan, ParsedObject, ProtFlags, TlsLayout,
+};
 use core::arch::asm;
 use std::ffi::c_void;
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
 use std::ptr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 
+const PROT_NONE: i32 = 0x0;
 const PROT_READ: i32 = 0x1;
 const PROT_WRITE: i32 = 0x2;
 const PROT_EXEC: i32 = 0x4;
@@ -10,13 +18,26 @@ const PROT_EXEC: i32 = 0x4;
 const MAP_PRIVATE: i32 = 0x02;
 const MAP_FIXED: i32 = 0x10;
 const MAP_ANONYMOUS: i32 = 0x20;
+const MAP_NORESERVE: i32 = 0x4000;
 const MAP_STACK: i32 = 0x20000;
+const MAP_FIXED_NOREPLACE: i32 = 0x100000;
+const EEXIST: i32 = 17;
 
 const STACK_SIZE: usize = 8 * 1024 * 1024;
 const RANDOM_LEN: usize = 16;
+const TCB_SIZE: usize = 4096;
+
+const SYS_ARCH_PRCTL: usize = 158;
+const ARCH_SET_FS: usize = 0x1002;
 
 const ET_EXEC: u16 = 2;
+const PT_LOAD: u32 = 1;
 const PT_PHDR: u32 = 6;
+const STB_GLOBAL: u8 = 1;
+const STB_WEAK: u8 = 2;
+const STT_FUNC: u8 = 2;
+const STV_PROTECTED: u8 = 3;
+const PAGE_SIZE: u64 = 4096;
 const ELF64_PHDR_SIZE: usize = 56;
 
 const AT_NULL: usize = 0;
@@ -40,6 +61,9 @@ const AT_EXECFN: usize = 31;
 const AT_SYSINFO: usize = 32;
 const AT_SYSINFO_EHDR: usize = 33;
 
+static DESTRUCTOR_PCS: OnceLock<Vec<usize>> = OnceLock::new();
+static DESTRUCTORS_RAN: AtomicBool = AtomicBool::new(false);
+
 unsafe extern "C" {
     fn mmap(
         addr: *mut c_void,
@@ -50,6 +74,7 @@ unsafe extern "C" {
         offset: i64,
     ) -> *mut c_void;
     fn mprotect(addr: *mut c_void, len: usize, prot: i32) -> i32;
+    fn munmap(addr: *mut c_void, len: usize) -> i32;
     fn getauxval(t: usize) -> usize;
     fn getuid() -> u32;
     fn geteuid() -> u32;
@@ -71,71 +96,332 @@ fn prot_bits(p: ProtFlags) -> i32 {
     out
 }
 
-fn map_segment(plan: &MmapPlan) -> Result<(), LoaderError> {
-    if plan.bytes.is_empty() {
-        return Ok(());
+fn runtime_error(object_name: &[u8], reason: LoaderErrorReason) -> LoaderError {
+    LoaderError {
+        stage: LoaderStage::Runtime,
+        object_name: object_name.to_vec(),
+        reason,
     }
+}
 
-    let addr = plan.start as usize as *mut c_void;
-    let len = plan.bytes.len();
+fn last_errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap_or(0)
+}
+
+// Page-rounded PT_LOAD span and largest page-multiple alignment of an object,
+// from the functions the planner packs bases with.
+fn load_extent(obj: &ParsedObject) -> Option<(u64, u64, u64)> {
+    let lo = load_span_lo_exec(&obj.phdrs);
+    let hi = load_span_hi_exec(&obj.phdrs);
+    if lo < hi {
+        Some((lo, hi, load_align_exec(&obj.phdrs)))
+    } else {
+        None
+    }
+}
+
+fn reserve_fixed(object_name: &[u8], start: u64, len: u64) -> Result<(), LoaderError> {
+    let addr = start as usize as *mut c_void;
     let mapped = unsafe {
         mmap(
             addr,
-            len,
-            PROT_READ | PROT_WRITE,
-            MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED,
+            len as usize,
+            PROT_NONE,
+            MAP_PRIVATE | MAP_ANONYMOUS | MAP_NORESERVE | MAP_FIXED_NOREPLACE,
             -1,
             0,
         )
     };
     if mapped as isize == -1 {
-        return Err(LoaderError {});
+        let errno = last_errno();
+        let reason = if errno == EEXIST {
+            LoaderErrorReason::AddressInUse { start, len }
+        } else {
+            LoaderErrorReason::Mmap { start, errno }
+        };
+        return Err(runtime_error(object_name, reason));
     }
-    if mapped as usize != plan.start as usize {
-        return Err(LoaderError {});
+    if mapped != addr {
+        // Kernels before 4.17 treat MAP_FIXED_NOREPLACE as a plain hint.
+        unsafe {
+            munmap(mapped, len as usize);
+        }
+        return Err(runtime_error(object_name, LoaderErrorReason::AddressInUse { start, len }));
     }
+    Ok(())
+}
 
+// Lets the kernel pick room for `hi - lo` bytes whose base is a multiple of
+// `align`, trims the slack, and returns that base.
+fn reserve_anywhere(object_name: &[u8], lo: u64, hi: u64, align: u64) -> Result<u64, LoaderError> {
+    let len = hi - lo + (align - PAGE_SIZE);
+    let mapped = unsafe {
+        mmap(
+            ptr::null_mut(),
+            len as usize,
+            PROT_NONE,
+            MAP_PRIVATE | MAP_ANONYMOUS | MAP_NORESERVE,
+            -1,
+            0,
+        )
+    };
+    if mapped as isize == -1 {
+        let errno = last_errno();
+        return Err(runtime_error(object_name, LoaderErrorReason::Mmap { start: 0, errno }));
+    }
+    let addr = mapped as u64;
+    let Some(floor) = addr.checked_sub(lo) else {
+        return Err(runtime_error(object_name, LoaderErrorReason::AddressInUse { start: addr, len }));
+    };
+    let base = floor.div_ceil(align) * align;
+    let (start, end) = (base + lo, base + hi);
     unsafe {
-        ptr::copy_nonoverlapping(plan.bytes.as_ptr(), mapped as *mut u8, len);
+        if start > addr {
+            munmap(mapped, (start - addr) as usize);
+        }
+        if end < addr + len {
+            munmap(end as usize as *mut c_void, (addr + len - end) as usize);
+        }
+    }
+    Ok(base)
+}
+
+// Reserves a PROT_NONE region over the load span of every object in the plan
+// and returns, per input object, the base the planner should be re-run with.
+// `ET_EXEC` images and `ET_DYN` objects with a requested base are reserved in
+// place with MAP_FIXED_NOREPLACE, so a clash with an existing mapping is
+// reported rather than clobbered; other `ET_DYN` objects get kernel-chosen
+// room. Segments are later mapped with MAP_FIXED inside these reservations.
+pub fn reserve_objects(plan: &LoaderOutput) -> Result<Vec<Option<u64>>, LoaderError> {
+    let mut bases = vec![None; plan.parsed.len()];
+    for &idx in &plan.discovered.order {
+        let Some(obj) = plan.parsed.get(idx) else {
+            continue;
+        };
+        let Some((lo, hi, align)) = load_extent(obj) else {
+            continue;
+        };
+        if obj.elf_type == ET_EXEC {
+            reserve_fixed(&obj.input_name, lo, hi - lo)?;
+        } else if let Some(base) = obj.load_base {
+            reserve_fixed(&obj.input_name, base.saturating_add(lo), hi - lo)?;
+            bases[idx] = Some(base);
+        } else {
+            bases[idx] = Some(reserve_anywhere(&obj.input_name, lo, hi, align)?);
+        }
+    }
+    Ok(bases)
+}
+
+fn map_fixed(plan: &MmapPlan, addr: usize, len: usize, flags: i32, fd: i32, offset: u64) -> Result<(), LoaderError> {
+    let mapped = unsafe {
+        mmap(
+            addr as *mut c_void,
+            len,
+            PROT_READ | PROT_WRITE,
+            flags | MAP_PRIVATE | MAP_FIXED,
+            fd,
+            offset as i64,
+        )
+    };
+    if mapped as isize == -1 {
+        let errno = last_errno();
+        return Err(runtime_error(&plan.object_name, LoaderErrorReason::Mmap { start: addr as u64, errno }));
+    }
+    if mapped as usize != addr {
+        return Err(runtime_error(&plan.object_name, LoaderErrorReason::Mmap { start: addr as u64, errno: 0 }));
+    }
+    Ok(())
+}
+
+// Maps one planned region inside the reservation made by `reserve_objects`,
+// which is why MAP_FIXED cannot replace anything the loader does not own.
+// A page-aligned file range is mapped MAP_PRIVATE from the object's file, so
+// pages that are never written stay shared with the page cache; the rest of
+// the region is anonymous. Otherwise the file bytes are copied in. Patches
+// go last and only dirty the pages they land on.
+fn map_segment(plan: &MmapPlan, parsed: &[ParsedObject], files: &[File]) -> Result<(), LoaderError> {
+    if plan.len == 0 {
+        return Ok(());
+    }
+
+    let start = plan.start as usize;
+    let file = files.get(plan.object_index).filter(|_| plan.file_len > 0 && plan.file_offset & (PAGE_SIZE - 1) == 0);
+    let file_pages = match file {
+        Some(_) => plan.file_len.next_multiple_of(PAGE_SIZE as usize).min(plan.len),
+        None => 0,
+    };
+    if let Some(f) = file {
+        map_fixed(plan, start, file_pages, 0, f.as_raw_fd(), plan.file_offset)?;
+        // The last page also holds whatever follows the range in the file.
+        unsafe {
+            ptr::write_bytes((start + plan.file_len) as *mut u8, 0, file_pages - plan.file_len);
+        }
+    }
+    if file_pages < plan.len {
+        map_fixed(plan, start + file_pages, plan.len - file_pages, MAP_ANONYMOUS, -1, 0)?;
+    }
+    if file.is_none() && plan.file_len > 0 {
+        let src = parsed.get(plan.object_index).and_then(|obj| {
+            let off = usize::try_from(plan.file_offset).ok()?;
+            obj.file_bytes.get(off..off.checked_add(plan.file_len)?)
+        });
+        let Some(src) = src else {
+            return Err(runtime_error(&plan.object_name, LoaderErrorReason::Truncated));
+        };
+        unsafe {
+            ptr::copy_nonoverlapping(src.as_ptr(), start as *mut u8, src.len());
+        }
+    }
+
+    // The part of a shared page that belongs to the next segment.
+    if let Some(fill) = plan.overlay {
+        let src = parsed.get(plan.object_index).and_then(|obj| {
+            let off = usize::try_from(fill.file_offset).ok()?;
+            obj.file_bytes.get(off..off.checked_add(fill.file_len)?)
+        });
+        let Some(src) = src.filter(|_| fill.file_len <= fill.len && fill.offset + fill.len <= plan.len) else {
+            return Err(runtime_error(&plan.object_name, LoaderErrorReason::Truncated));
+        };
+        unsafe {
+            let dst = (start + fill.offset) as *mut u8;
+            ptr::copy_nonoverlapping(src.as_ptr(), dst, src.len());
+            ptr::write_bytes(dst.add(src.len()), 0, fill.len - src.len());
+        }
+    }
+
+    for patch in &plan.patches {
+        unsafe {
+            ptr::write_unaligned((start + patch.offset) as *mut u64, patch.value);
+        }
     }
     Ok(())
 }
 
 fn protect_segment(plan: &MmapPlan) -> Result<(), LoaderError> {
-    if plan.bytes.is_empty() {
+    if plan.len == 0 {
         return Ok(());
     }
 
     let addr = plan.start as usize as *mut c_void;
-    let len = plan.bytes.len();
+    let len = plan.len;
     let rc = unsafe { mprotect(addr, len, prot_bits(plan.prot)) };
     if rc != 0 {
-        return Err(LoaderError {});
+        let errno = last_errno();
+        return Err(runtime_error(&plan.object_name, LoaderErrorReason::Mprotect { start: plan.start, errno }));
     }
     Ok(())
 }
 
-fn main_base(plan: &LoaderOutput) -> u64 {
-    let Some(main_obj) = plan.parsed.first() else {
+fn plan_contains(plan: &MmapPlan, addr: u64) -> bool {
+    addr >= plan.start && addr - plan.start < plan.len as u64
+}
+
+// Runs the planned IFUNC resolvers and stores their results. The target
+// regions are temporarily made writable and then restored to their planned
+// protection, so RELRO pieces end up read-only again.
+fn apply_resolver_writes(plan: &LoaderOutput) -> Result<(), LoaderError> {
+    let values: Vec<u64> = plan
+        .resolver_writes
+        .iter()
+        .map(|w| {
+            let resolver: extern "C" fn() -> u64 = unsafe { std::mem::transmute(w.resolver_pc as usize) };
+            resolver().wrapping_add(w.addend as u64)
+        })
+        .collect();
+
+    let targets: Vec<&MmapPlan> = plan
+        .mmap_plans
+        .iter()
+        .filter(|m| plan.resolver_writes.iter().any(|w| plan_contains(m, w.write_addr)))
+        .collect();
+
+    // Every resolver has run, so a target can drop PROT_EXEC while it is
+    // writable and never be W+X.
+    for m in &targets {
+        let addr = m.start as usize as *mut c_void;
+        let rc = unsafe { mprotect(addr, m.len, (prot_bits(m.prot) & !PROT_EXEC) | PROT_WRITE) };
+        if rc != 0 {
+            let errno = last_errno();
+            return Err(runtime_error(&m.object_name, LoaderErrorReason::Mprotect { start: m.start, errno }));
+        }
+    }
+
+    for (w, value) in plan.resolver_writes.iter().zip(values) {
+        unsafe {
+            ptr::write_unaligned(w.write_addr as usize as *mut u64, value);
+        }
+    }
+
+    for m in &targets {
+        protect_segment(m)?;
+    }
+    Ok(())
+}
+
+// Load bias of input object `idx`: 0 for ET_EXEC, otherwise the distance
+// between its lowest mapping and the lowest PT_LOAD page it came from. This
+// holds for packed and randomized bases alike.
+fn object_bias(plan: &LoaderOutput, idx: usize) -> u64 {
+    let Some(obj) = plan.parsed.get(idx) else {
         return 0;
     };
-    if main_obj.elf_type == ET_EXEC {
+    if obj.elf_type == ET_EXEC {
         return 0;
     }
 
-    let mut base = u64::MAX;
-    for m in &plan.mmap_plans {
-        if m.object_name == main_obj.input_name && m.start < base {
-            base = m.start;
-        }
+    let Some((lowest_vaddr, _, _)) = load_extent(obj) else {
+        return 0;
+    };
+    let lowest_start = plan
+        .mmap_plans
+        .iter()
+        .filter(|m| m.object_index == idx)
+        .map(|m| m.start)
+        .min();
+    lowest_start.map_or(0, |start| start.wrapping_sub(lowest_vaddr))
+}
+
+// Load bias of the loader's own image, found through the PT_PHDR the kernel
+// reported for it. The loader stands in for the interpreter, so this is what
+// the program sees as AT_BASE.
+fn loader_base() -> u64 {
+    let phdr = unsafe { getauxval(AT_PHDR) };
+    let phnum = unsafe { getauxval(AT_PHNUM) };
+    if phdr == 0 {
+        return 0;
     }
-    if base == u64::MAX {
-        0
-    } else {
-        base
+    for i in 0..phnum {
+        let ph = phdr + i * ELF64_PHDR_SIZE;
+        let p_type = unsafe { ptr::read_unaligned(ph as *const u32) };
+        if p_type == PT_PHDR {
+            let p_vaddr = unsafe { ptr::read_unaligned((ph + 16) as *const u64) };
+            return (phdr as u64).wrapping_sub(p_vaddr);
+        }
     }
+    0
+}
+
+// `st_value` of a function `obj` exports under `name`.
+fn exported_func(obj: &ParsedObject, name: &[u8]) -> Option<u64> {
+    obj.dynsyms
+        .iter()
+        .find(|sym| {
+            let bind = sym.st_info >> 4;
+            let vis = sym.st_other & 0x3;
+            let start = sym.name_offset as usize;
+            let sym_name = obj.dynstr.get(start..).map(|rest| rest.split(|&b| b == 0).next().unwrap_or(rest));
+            sym.is_defined()
+                && (bind == STB_GLOBAL || bind == STB_WEAK)
+                && sym.st_info & 0xf == STT_FUNC
+                && (vis == 0 || vis == STV_PROTECTED)
+                && sym_name == Some(name)
+        })
+        .map(|sym| sym.st_value)
 }
 
+// Run-time address of the main executable's program headers: PT_PHDR when
+// present, otherwise e_phoff translated through the PT_LOAD that covers it.
 fn main_phdr_addr(plan: &LoaderOutput, base: u64) -> u64 {
     let Some(main_obj) = plan.parsed.first() else {
         return 0;
@@ -145,21 +431,58 @@ fn main_phdr_addr(plan: &LoaderOutput, base: u64) -> u64 {
             return base.saturating_add(ph.p_vaddr);
         }
     }
+    let Some(raw) = main_obj.file_bytes.get(32..40) else {
+        return 0;
+    };
+    let mut phoff = [0u8; 8];
+    phoff.copy_from_slice(raw);
+    let phoff = u64::from_le_bytes(phoff);
+    for ph in &main_obj.phdrs {
+        if ph.p_type == PT_LOAD && phoff >= ph.p_offset && phoff - ph.p_offset < ph.p_filesz {
+            return base.saturating_add(ph.p_vaddr.saturating_add(phoff - ph.p_offset));
+        }
+    }
     0
 }
 
-fn alloc_initial_stack(plan: &LoaderOutput) -> Result<*mut usize, LoaderError> {
-    let mut argv0 = if let Some(main_obj) = plan.parsed.first() {
-        main_obj.input_name.clone()
-    } else {
-        b"program".to_vec()
-    };
-    if argv0.is_empty() || argv0[0] == 0 {
-        argv0 = b"program".to_vec();
+// AT_PHDR points at the file's own table, so AT_PHNUM has to count every
+// entry in it, not just the headers the parser keeps. A static program finds
+// its PT_TLS and PT_GNU_RELRO there.
+fn main_phnum(plan: &LoaderOutput) -> usize {
+    plan.parsed
+        .first()
+        .and_then(|o| o.file_bytes.get(56..58))
+        .map_or(0, |raw| u16::from_le_bytes([raw[0], raw[1]]) as usize)
+}
+
+pub struct ProcessArgs {
+    pub execfn: Vec<u8>,
+    pub argv: Vec<Vec<u8>>,
+    pub envp: Vec<Vec<u8>>,
+}
+
+fn with_nul(s: &[u8]) -> Vec<u8> {
+    let mut out = s.to_vec();
+    if out.last().copied() != Some(0) {
+        out.push(0);
     }
-    if argv0.last().copied() != Some(0) {
-        argv0.push(0);
+    out
+}
+
+// Lays out the initial process stack as described by the x86-64 psABI:
+//   sp -> argc, argv[0..argc], NULL, envp[..], NULL, auxv pairs, AT_NULL
+// with the strings and AT_RANDOM bytes placed above the table.
+fn alloc_initial_stack(plan: &LoaderOutput, args: &ProcessArgs) -> Result<*mut usize, LoaderError> {
+    let mut argv: Vec<Vec<u8>> = args.argv.iter().map(|a| with_nul(a)).collect();
+    if argv.is_empty() {
+        let argv0 = match plan.parsed.first() {
+            Some(main_obj) if !main_obj.input_name.is_empty() => main_obj.input_name.clone(),
+            _ => b"program".to_vec(),
+        };
+        argv.push(with_nul(&argv0));
     }
+    let envp: Vec<Vec<u8>> = args.envp.iter().map(|e| with_nul(e)).collect();
+    let execfn = if args.execfn.is_empty() { argv[0].clone() } else { with_nul(&args.execfn) };
 
     let mapped = unsafe {
         mmap(
@@ -172,17 +495,38 @@ fn alloc_initial_stack(plan: &LoaderOutput) -> Result<*mut usize, LoaderError> {
         )
     };
     if mapped as isize == -1 {
-        return Err(LoaderError {});
+        let errno = last_errno();
+        return Err(runtime_error(b"", LoaderErrorReason::Mmap { start: 0, errno }));
+    }
+
+    let strings_len = execfn.len()
+        + argv.iter().map(|a| a.len()).sum::<usize>()
+        + envp.iter().map(|e| e.len()).sum::<usize>();
+    if strings_len + RANDOM_LEN > STACK_SIZE / 2 {
+        return Err(runtime_error(b"", LoaderErrorReason::StackSetup));
     }
 
     let top = (mapped as usize + STACK_SIZE) & !0xfusize;
-    let argv0_addr = top - argv0.len();
-    let random_addr = argv0_addr - RANDOM_LEN;
-    let table_top = random_addr & !0xfusize;
+    let execfn_addr = top - execfn.len();
+    let mut cursor = execfn_addr;
+    let mut env_addrs: Vec<usize> = Vec::new();
+    for e in envp.iter().rev() {
+        cursor -= e.len();
+        env_addrs.push(cursor);
+    }
+    env_addrs.reverse();
+    let mut arg_addrs: Vec<usize> = Vec::new();
+    for a in argv.iter().rev() {
+        cursor -= a.len();
+        arg_addrs.push(cursor);
+    }
+    arg_addrs.reverse();
+    let random_addr = (cursor - RANDOM_LEN) & !0xfusize;
+    let table_top = random_addr;
 
-    let base = main_base(plan);
+    let base = object_bias(plan, 0);
     let phdr_addr = main_phdr_addr(plan, base) as usize;
-    let phnum = plan.parsed.first().map_or(0, |o| o.phdrs.len());
+    let phnum = main_phnum(plan);
 
     let mut random_bytes = [0u8; RANDOM_LEN];
     let host_random = unsafe { getauxval(AT_RANDOM) as *const u8 };
@@ -199,7 +543,7 @@ fn alloc_initial_stack(plan: &LoaderOutput) -> Result<*mut usize, LoaderError> {
         auxv.push((AT_PHNUM, phnum));
     }
     auxv.push((AT_PAGESZ, 4096));
-    auxv.push((AT_BASE, 0));
+    auxv.push((AT_BASE, loader_base() as usize));
     auxv.push((AT_FLAGS, 0));
     auxv.push((AT_ENTRY, plan.entry_pc as usize));
     auxv.push((AT_UID, unsafe { getuid() as usize }));
@@ -208,7 +552,7 @@ fn alloc_initial_stack(plan: &LoaderOutput) -> Result<*mut usize, LoaderError> {
     auxv.push((AT_EGID, unsafe { getegid() as usize }));
     auxv.push((AT_SECURE, 0));
     auxv.push((AT_RANDOM, random_addr));
-    auxv.push((AT_EXECFN, argv0_addr));
+    auxv.push((AT_EXECFN, execfn_addr));
 
     let hwcap = unsafe { getauxval(AT_HWCAP) };
     if hwcap != 0 {
@@ -231,16 +575,16 @@ fn alloc_initial_stack(plan: &LoaderOutput) -> Result<*mut usize, LoaderError> {
         auxv.push((AT_SYSINFO_EHDR, sysinfo_ehdr));
     }
 
-    let fixed_words = 4usize;
+    let fixed_words = 1 + arg_addrs.len() + 1 + env_addrs.len() + 1;
     let aux_words = auxv.len() * 2 + 2;
     let mut stack_words = fixed_words + aux_words;
-    if stack_words % 2 != 0 {
+    if !stack_words.is_multiple_of(2) {
         stack_words += 1;
     }
 
     let stack_table_bytes = stack_words * std::mem::size_of::<usize>();
-    if argv0.len() + RANDOM_LEN + stack_table_bytes > STACK_SIZE {
-        return Err(LoaderError {});
+    if top - table_top + stack_table_bytes > STACK_SIZE {
+        return Err(runtime_error(b"", LoaderErrorReason::StackSetup));
     }
     let sp = unsafe { (table_top as *mut usize).sub(stack_words) };
 
@@ -250,15 +594,27 @@ fn alloc_initial_stack(plan: &LoaderOutput) -> Result<*mut usize, LoaderError> {
             random_addr as *mut u8,
             RANDOM_LEN,
         );
-        ptr::copy_nonoverlapping(argv0.as_ptr(), argv0_addr as *mut u8, argv0.len());
+        ptr::copy_nonoverlapping(execfn.as_ptr(), execfn_addr as *mut u8, execfn.len());
+        for (a, addr) in argv.iter().zip(&arg_addrs) {
+            ptr::copy_nonoverlapping(a.as_ptr(), *addr as *mut u8, a.len());
+        }
+        for (e, addr) in envp.iter().zip(&env_addrs) {
+            ptr::copy_nonoverlapping(e.as_ptr(), *addr as *mut u8, e.len());
+        }
 
         let mut w = 0usize;
-        ptr::write(sp.add(w), 1);
-        w += 1;
-        ptr::write(sp.add(w), argv0_addr);
+        ptr::write(sp.add(w), arg_addrs.len());
         w += 1;
+        for addr in &arg_addrs {
+            ptr::write(sp.add(w), *addr);
+            w += 1;
+        }
         ptr::write(sp.add(w), 0);
         w += 1;
+        for addr in &env_addrs {
+            ptr::write(sp.add(w), *addr);
+            w += 1;
+        }
         ptr::write(sp.add(w), 0);
         w += 1;
         for (k, v) in &auxv {
@@ -280,16 +636,132 @@ fn alloc_initial_stack(plan: &LoaderOutput) -> Result<*mut usize, LoaderError> {
     Ok(sp)
 }
 
-pub fn run_runtime(plan: &LoaderOutput) -> Result<(), LoaderError> {
+fn set_fs_base(tp: usize) -> isize {
+    let ret: isize;
+    unsafe {
+        asm!(
+            "syscall",
+            inlateout("rax") SYS_ARCH_PRCTL as isize => ret,
+            in("rdi") ARCH_SET_FS,
+            in("rsi") tp,
+            lateout("rcx") _,
+            lateout("r11") _,
+            options(nostack)
+        );
+    }
+    ret
+}
+
+// Builds the static TLS area (x86-64 variant II): module blocks sit below the
+// thread pointer at `tp - tp_offset`, and the TCB at `tp` starts with the
+// self pointer followed by the DTV pointer. The DTV holds the module count
+// and then each module's block, indexed by the module id the planner wrote
+// into `DTPMOD64` slots. Once %fs is switched the loader itself must not
+// touch thread-local state, so this runs last.
+fn install_tls(tls: &TlsLayout) -> Result<(), LoaderError> {
+    if tls.modules.is_empty() {
+        return Ok(());
+    }
+
+    let align = tls.align.max(16) as usize;
+    let static_size = tls.static_size as usize;
+    let area_len = static_size + align + TCB_SIZE;
+    let area = unsafe {
+        mmap(
+            ptr::null_mut(),
+            area_len,
+            PROT_READ | PROT_WRITE,
+            MAP_PRIVATE | MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    if area as isize == -1 {
+        let errno = last_errno();
+        return Err(runtime_error(b"", LoaderErrorReason::Mmap { start: 0, errno }));
+    }
+
+    let tp = (area as usize + static_size + align - 1) & !(align - 1);
+    let dtv: &mut [usize] = Vec::leak(vec![0usize; tls.modules.len() + 1]);
+    dtv[0] = tls.modules.len();
+    for m in &tls.modules {
+        let block = tp - m.tp_offset as usize;
+        unsafe {
+            ptr::copy_nonoverlapping(m.image.as_ptr(), block as *mut u8, m.image.len());
+        }
+        dtv[m.module_id as usize] = block;
+    }
+
+    unsafe {
+        let tcb = tp as *mut usize;
+        ptr::write(tcb, tp);
+        ptr::write(tcb.add(1), dtv.as_ptr() as usize);
+        ptr::write(tcb.add(2), tp);
+    }
+
+    let rc = set_fs_base(tp);
+    if rc != 0 {
+        return Err(runtime_error(b"", LoaderErrorReason::SetThreadPointer { errno: (-rc) as i32 }));
+    }
+    Ok(())
+}
+
+// Registered with the program's `atexit` and also handed over as the psABI
+// `rtld_fini` in %rdx, so libc's `exit` runs the planned destructors once
+// whichever of the two it honours. It only touches process-global state,
+// which keeps it safe to call after %fs belongs to the program.
+extern "C" fn run_destructors() {
+    if DESTRUCTORS_RAN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if let Some(pcs) = DESTRUCTOR_PCS.get() {
+        for pc in pcs {
+            let dtor: extern "C" fn() = unsafe { std::mem::transmute(*pc) };
+            dtor();
+        }
+    }
+}
+
+// musl's start code ignores %rdx, so the finalizer is also registered with
+// the first `atexit` exported in load order. This runs before the program's
+// libc is initialized, as the constructors do: musl's `atexit` only fills a
+// static handler block under a lock word, both valid from load time.
+// Registering before any constructor makes it the last exit handler. glibc
+// exports no `atexit` (it lives in `libc_nonshared.a`), honours %rdx, and
+// mangles `__cxa_atexit` pointers with a guard it has not set up yet, so it
+// is left to %rdx alone.
+fn register_destructors(plan: &LoaderOutput) {
+    if plan.destructors.is_empty() {
+        return;
+    }
+    for &idx in &plan.discovered.order {
+        let Some(obj) = plan.parsed.get(idx) else {
+            continue;
+        };
+        if let Some(value) = exported_func(obj, b"atexit") {
+            let pc = object_bias(plan, idx).wrapping_add(value) as usize;
+            let atexit: extern "C" fn(extern "C" fn()) -> i32 = unsafe { std::mem::transmute(pc) };
+            atexit(run_destructors);
+            return;
+        }
+    }
+}
+
+// `files` holds the open input objects, indexed like `plan.parsed`.
+pub fn run_runtime(plan: &LoaderOutput, files: &[File], args: &ProcessArgs) -> Result<(), LoaderError> {
     for m in &plan.mmap_plans {
-        map_segment(m)?;
+        map_segment(m, &plan.parsed, files)?;
     }
 
     for m in &plan.mmap_plans {
         protect_segment(m)?;
     }
+    apply_resolver_writes(plan)?;
 
-    let stack_ptr = alloc_initial_stack(plan)?;
+    let stack_ptr = alloc_initial_stack(plan, args)?;
+    let _ = DESTRUCTOR_PCS.set(plan.destructors.iter().map(|d| d.pc as usize).collect());
+    install_tls(&plan.tls)?;
+    register_destructors(plan);
     for c in &plan.constructors {
         let ctor: extern "C" fn() =
             unsafe { std::mem::transmute(c.pc as usize) };
@@ -303,6 +775,7 @@ pub fn run_runtime(plan: &LoaderOutput) -> Result<(), LoaderError> {
             "jmp {entry}",
             stack = in(reg) stack_ptr,
             entry = in(reg) (plan.entry_pc as usize),
+            in("rdx") run_destructors as extern "C" fn() as usize,
             options(noreturn)
         );
     }
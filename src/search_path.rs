@@ -0,0 +1,250 @@
+use crate::consts::*;
+use crate::mmap_plan_impl::{load_align_exec, load_span_hi_exec, load_span_lo_exec};
+use crate::types::ProgramHeader;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::Read;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+// `$ORIGIN` here is the directory of the loader binary, so the musl built
+// next to it (`build/lib`) is found without being listed.
+pub const DEFAULT_LIBRARY_PATH: &str = "/lib:/usr/lib:$ORIGIN/lib";
+
+#[derive(Clone, Debug)]
+pub struct SearchConfig {
+    pub ld_library_path: Vec<PathBuf>,
+    pub default_paths: Vec<PathBuf>,
+}
+
+impl SearchConfig {
+    pub fn new(ld_library_path: Option<&OsStr>, default_paths: &str, loader_path: &Path) -> SearchConfig {
+        SearchConfig {
+            ld_library_path: ld_library_path.map_or_else(Vec::new, split_path_list),
+            default_paths: expand_search_list(default_paths.as_bytes(), loader_path),
+        }
+    }
+}
+
+fn split_path_list(list: &OsStr) -> Vec<PathBuf> {
+    list.as_bytes()
+        .split(|&b| b == b':')
+        .filter(|s| !s.is_empty())
+        .map(|s| PathBuf::from(OsStr::from_bytes(s)))
+        .collect()
+}
+
+// Dynamic-section fields the front-end needs to locate dependencies. This is
+// a best-effort, unverified read: malformed objects yield `None` here and are
+// rejected later by the verified parse stage.
+#[derive(Clone, Debug, Default)]
+pub struct DynamicPeek {
+    pub needed: Vec<Vec<u8>>,
+    pub soname: Option<Vec<u8>>,
+    pub rpath: Option<Vec<u8>>,
+    pub runpath: Option<Vec<u8>>,
+    // Page-rounded extent of the PT_LOAD segments and their largest alignment,
+    // used by the front-end to pick randomized load bases.
+    pub elf_type: u16,
+    pub load_lo: u64,
+    pub load_hi: u64,
+    pub load_align: u64,
+}
+
+fn read_u16(bytes: &[u8], off: usize) -> Option<u16> {
+    let b = bytes.get(off..off.checked_add(2)?)?;
+    Some(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(bytes: &[u8], off: usize) -> Option<u32> {
+    let b = bytes.get(off..off.checked_add(4)?)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u64(bytes: &[u8], off: usize) -> Option<u64> {
+    let b = bytes.get(off..off.checked_add(8)?)?;
+    let mut out = [0u8; 8];
+    out.copy_from_slice(b);
+    Some(u64::from_le_bytes(out))
+}
+
+fn vaddr_to_offset(loads: &[(u64, u64, u64)], vaddr: u64) -> Option<usize> {
+    for (p_vaddr, p_offset, p_filesz) in loads {
+        if vaddr >= *p_vaddr && vaddr - *p_vaddr < *p_filesz {
+            return usize::try_from(p_offset.checked_add(vaddr - *p_vaddr)?).ok();
+        }
+    }
+    None
+}
+
+fn cstr_at(bytes: &[u8], off: usize) -> Option<Vec<u8>> {
+    let tail = bytes.get(off..)?;
+    let len = tail.iter().position(|&b| b == 0)?;
+    Some(tail[..len].to_vec())
+}
+
+pub fn peek_dynamic(bytes: &[u8]) -> Option<DynamicPeek> {
+    if bytes.len() < ELF64_EHDR_SIZE || bytes[EI_MAG0] != ELFMAG0 || bytes[EI_MAG1] != ELFMAG1
+        || bytes[EI_MAG2] != ELFMAG2 || bytes[EI_MAG3] != ELFMAG3
+        || bytes[EI_CLASS] != ELFCLASS64 || bytes[EI_DATA] != ELFDATA2LSB
+    {
+        return None;
+    }
+
+    let elf_type = read_u16(bytes, 16)?;
+    let phoff = usize::try_from(read_u64(bytes, 32)?).ok()?;
+    let phnum = read_u16(bytes, 56)? as usize;
+
+    let mut loads: Vec<(u64, u64, u64)> = Vec::new();
+    let mut load_phdrs: Vec<ProgramHeader> = Vec::new();
+    let mut dynamic: Option<(usize, usize)> = None;
+    for i in 0..phnum {
+        let ph = phoff.checked_add(i.checked_mul(ELF64_PHDR_SIZE)?)?;
+        let p_type = read_u32(bytes, ph)?;
+        let p_flags = read_u32(bytes, ph + 4)?;
+        let p_offset = read_u64(bytes, ph + 8)?;
+        let p_vaddr = read_u64(bytes, ph + 16)?;
+        let p_filesz = read_u64(bytes, ph + 32)?;
+        let p_memsz = read_u64(bytes, ph + 40)?;
+        let p_align = read_u64(bytes, ph + 48)?;
+        if p_type == PT_LOAD {
+            loads.push((p_vaddr, p_offset, p_filesz));
+            load_phdrs.push(ProgramHeader { p_type, p_flags, p_offset, p_vaddr, p_filesz, p_memsz, p_align });
+        } else if p_type == PT_DYNAMIC {
+            dynamic = Some((usize::try_from(p_offset).ok()?, usize::try_from(p_filesz).ok()?));
+        }
+    }
+
+    let (dyn_off, dyn_size) = dynamic?;
+    let mut strtab: Option<u64> = None;
+    let mut needed: Vec<u64> = Vec::new();
+    let mut soname: Option<u64> = None;
+    let mut rpath: Option<u64> = None;
+    let mut runpath: Option<u64> = None;
+    for i in 0..dyn_size / ELF64_DYN_SIZE {
+        let ent = dyn_off.checked_add(i * ELF64_DYN_SIZE)?;
+        let tag = read_u64(bytes, ent)? as i64;
+        let val = read_u64(bytes, ent + 8)?;
+        match tag {
+            DT_NULL => break,
+            DT_NEEDED => needed.push(val),
+            DT_SONAME => soname = Some(val),
+            DT_RPATH => rpath = Some(val),
+            DT_RUNPATH => runpath = Some(val),
+            DT_STRTAB => strtab = Some(val),
+            _ => {}
+        }
+    }
+
+    let str_off = vaddr_to_offset(&loads, strtab?)?;
+    let string = |off: u64| -> Option<Vec<u8>> {
+        cstr_at(bytes, str_off.checked_add(usize::try_from(off).ok()?)?)
+    };
+
+    let mut out = DynamicPeek {
+        elf_type,
+        load_lo: load_span_lo_exec(&load_phdrs),
+        load_hi: load_span_hi_exec(&load_phdrs),
+        load_align: load_align_exec(&load_phdrs),
+        ..DynamicPeek::default()
+    };
+    for off in needed {
+        out.needed.push(string(off)?);
+    }
+    out.soname = match soname {
+        Some(off) => Some(string(off)?),
+        None => None,
+    };
+    out.rpath = match rpath {
+        Some(off) => Some(string(off)?),
+        None => None,
+    };
+    out.runpath = match runpath {
+        Some(off) => Some(string(off)?),
+        None => None,
+    };
+    Some(out)
+}
+
+fn origin_of(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+// Splits a DT_RPATH/DT_RUNPATH value and expands `$ORIGIN`/`${ORIGIN}` to the
+// directory of the object that carries the entry.
+fn expand_search_list(list: &[u8], object_path: &Path) -> Vec<PathBuf> {
+    let origin = origin_of(object_path).to_string_lossy().into_owned();
+    String::from_utf8_lossy(list)
+        .split(':')
+        .filter(|s| !s.is_empty())
+        .map(|s| PathBuf::from(s.replace("${ORIGIN}", &origin).replace("$ORIGIN", &origin)))
+        .collect()
+}
+
+// Like ld.so, a candidate of another class or machine (a 32-bit library in a
+// shared directory, say) is passed over and the search goes on.
+fn is_elf64_x86_64(path: &Path) -> bool {
+    let mut head = [0u8; 20];
+    if File::open(path).and_then(|mut f| f.read_exact(&mut head)).is_err() {
+        return false;
+    }
+    head[EI_MAG0] == ELFMAG0
+        && head[EI_MAG1] == ELFMAG1
+        && head[EI_MAG2] == ELFMAG2
+        && head[EI_MAG3] == ELFMAG3
+        && head[EI_CLASS] == ELFCLASS64
+        && head[EI_DATA] == ELFDATA2LSB
+        && read_u16(&head, 18) == Some(EM_X86_64)
+}
+
+fn search_dirs(dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+    dirs.iter().map(|dir| dir.join(name)).find(|cand| cand.is_file() && is_elf64_x86_64(cand))
+}
+
+// Locates the file for one DT_NEEDED entry, following the ld.so search order:
+// DT_RPATH (requester, then main executable; only when the requester has no
+// DT_RUNPATH), LD_LIBRARY_PATH, DT_RUNPATH of the requester, default paths.
+pub fn find_library(
+    name: &[u8],
+    requester_path: &Path,
+    requester: &DynamicPeek,
+    main_path: &Path,
+    main: &DynamicPeek,
+    config: &SearchConfig,
+) -> Option<PathBuf> {
+    let name = String::from_utf8_lossy(name).into_owned();
+    if name.contains('/') {
+        let direct = PathBuf::from(&name);
+        return if direct.is_file() { Some(direct) } else { None };
+    }
+
+    if requester.runpath.is_none() {
+        if let Some(rpath) = &requester.rpath {
+            if let Some(found) = search_dirs(&expand_search_list(rpath, requester_path), &name) {
+                return Some(found);
+            }
+        }
+        if main.runpath.is_none() {
+            if let Some(rpath) = &main.rpath {
+                if let Some(found) = search_dirs(&expand_search_list(rpath, main_path), &name) {
+                    return Some(found);
+                }
+            }
+        }
+    }
+
+    if let Some(found) = search_dirs(&config.ld_library_path, &name) {
+        return Some(found);
+    }
+
+    if let Some(runpath) = &requester.runpath {
+        if let Some(found) = search_dirs(&expand_search_list(runpath, requester_path), &name) {
+            return Some(found);
+        }
+    }
+
+    search_dirs(&config.default_paths, &name)
+}
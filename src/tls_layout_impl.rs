@@ -0,0 +1,266 @@
+use crate::relocate_plan_spec::*;
+use crate::tls_layout_spec::*;
+use crate::types::*;
+use vstd::arithmetic::div_mod::*;
+use vstd::prelude::*;
+
+verus! {
+
+proof fn lemma_mod_trans(a: int, b: int, c: int)
+    requires
+        b > 0,
+        c > 0,
+        a % b == 0,
+        b % c == 0,
+    ensures
+        a % c == 0,
+{
+    lemma_fundamental_div_mod(a, b);
+    lemma_fundamental_div_mod(b, c);
+    let k = a / b;
+    let j = b / c;
+    assert(a == (j * k) * c) by (nonlinear_arith)
+        requires
+            a == b * k,
+            b == c * j,
+    ;
+    lemma_mod_multiples_basic(j * k, c);
+}
+
+fn align_up_exec(v: u64, align: u64) -> (out: Option<u64>)
+    requires
+        align > 0,
+    ensures
+        match out {
+            Some(r) => r == align_up_or_zero(v, align) && v <= r && r % align == 0,
+            None => true,
+        },
+{
+    let rem = v % align;
+    if rem == 0 {
+        return Some(v);
+    }
+    let pad = align - rem;
+    if v > u64::MAX - pad {
+        return None;
+    }
+    let r = v + pad;
+    proof {
+        let q = v as int / align as int;
+        lemma_fundamental_div_mod(v as int, align as int);
+        assert(r as int == (q + 1) * align as int) by (nonlinear_arith)
+            requires
+                v as int == align as int * q + rem as int,
+                r as int == v as int + align as int - rem as int,
+        ;
+        lemma_mod_multiples_basic(q + 1, align as int);
+    }
+    Some(r)
+}
+
+fn tls_image_exec(obj: &ParsedObject, ph: &ProgramHeader) -> (out: Option<Vec<u8>>)
+    ensures
+        match out {
+            Some(image) => image@ == tls_image(*obj, *ph),
+            None => true,
+        },
+{
+    if ph.p_offset > usize::MAX as u64 || ph.p_filesz > usize::MAX as u64 {
+        return None;
+    }
+    let start = ph.p_offset as usize;
+    let len = ph.p_filesz as usize;
+    if start > obj.file_bytes.len() || len > obj.file_bytes.len() - start {
+        return None;
+    }
+    let mut image: Vec<u8> = Vec::new();
+    let mut i: usize = 0;
+    while i < len
+        invariant
+            i <= len,
+            start + len <= obj.file_bytes@.len(),
+            image@ == obj.file_bytes@.subrange(start as int, start + i),
+        decreases len - i,
+    {
+        image.push(obj.file_bytes[start + i]);
+        proof {
+            assert(image@ =~= obj.file_bytes@.subrange(start as int, start + i + 1));
+        }
+        i = i + 1;
+    }
+    Some(image)
+}
+
+fn tls_error(parsed: &Vec<ParsedObject>, obj_idx: usize, reason: LoaderErrorReason) -> LoaderError {
+    if obj_idx < parsed.len() {
+        loader_error(LoaderStage::TlsLayout, &parsed[obj_idx].input_name, reason)
+    } else {
+        loader_error(LoaderStage::TlsLayout, &Vec::new(), reason)
+    }
+}
+
+pub fn tls_layout_stage(
+    parsed: &Vec<ParsedObject>,
+    discovered: &DiscoveryResult,
+) -> (out: Result<TlsLayout, LoaderError>)
+    ensures
+        out.is_ok() ==> tls_layout_stage_spec(parsed@, *discovered, out.unwrap()),
+{
+    let ghost order = discovered.order@;
+    let mut modules: Vec<TlsModule> = Vec::new();
+    let mut offset: u64 = 0;
+    let mut layout_align: u64 = 1;
+    let mut pos: usize = 0;
+    while pos < discovered.order.len()
+        invariant
+            order == discovered.order@,
+            pos <= order.len(),
+            offset == tls_offset_before(parsed@, order, pos as nat),
+            modules@.len() == tls_modules_before(parsed@, order, pos as nat),
+            modules@.len() <= pos,
+            layout_align > 0,
+            layout_align == tls_area_align_before(parsed@, order, pos as nat),
+            forall|i: int|
+                0 <= i < modules@.len() ==> exists|p: int|
+                    tls_module_matches(parsed@, order, p, modules@[i]) && tls_modules_before(
+                        parsed@,
+                        order,
+                        p as nat,
+                    ) == i,
+            forall|i: int|
+                0 <= i < modules@.len() ==> {
+                    &&& modules@[i].module_id == i + 1
+                    &&& modules@[i].memsz <= modules@[i].tp_offset
+                    &&& modules@[i].tp_offset <= offset
+                    &&& modules@[i].align > 0
+                    &&& modules@[i].tp_offset % modules@[i].align == 0
+                    &&& layout_align % modules@[i].align == 0
+                },
+            tls_blocks_disjoint(modules@),
+        decreases order.len() - pos,
+    {
+        let obj_idx = discovered.order[pos];
+        if obj_idx >= parsed.len() {
+            return Err(tls_error(parsed, obj_idx, LoaderErrorReason::InvalidObjectIndex { index: obj_idx }));
+        }
+        proof {
+            assert(object_tls_at(parsed@, order, pos as int) == parsed@[obj_idx as int].tls);
+        }
+        match parsed[obj_idx].tls {
+            None => {
+                proof {
+                    assert(tls_offset_before(parsed@, order, (pos + 1) as nat) == offset);
+                    assert(tls_modules_before(parsed@, order, (pos + 1) as nat) == modules@.len());
+                    assert(tls_area_align_before(parsed@, order, (pos + 1) as nat) == layout_align);
+                }
+            }
+            Some(ph) => {
+                let align = if ph.p_align == 0 {
+                    1
+                } else {
+                    ph.p_align
+                };
+                if offset > u64::MAX - ph.p_memsz {
+                    return Err(tls_error(parsed, obj_idx, LoaderErrorReason::BadTlsLayout));
+                }
+                let end = offset + ph.p_memsz;
+                let tp_offset = match align_up_exec(end, align) {
+                    Some(v) => v,
+                    None => {
+                        return Err(tls_error(parsed, obj_idx, LoaderErrorReason::BadTlsLayout));
+                    }
+                };
+                let image = match tls_image_exec(&parsed[obj_idx], &ph) {
+                    Some(v) => v,
+                    None => {
+                        return Err(tls_error(parsed, obj_idx, LoaderErrorReason::Truncated));
+                    }
+                };
+
+                let ghost old_align = layout_align;
+                proof {
+                    assert(tls_align_of(ph) == align);
+                    assert(tls_area_align_before(parsed@, order, (pos + 1) as nat) == if old_align % align == 0 {
+                        old_align
+                    } else {
+                        align
+                    });
+                }
+                if layout_align % align != 0 {
+                    if align % layout_align != 0 {
+                        return Err(tls_error(parsed, obj_idx, LoaderErrorReason::BadTlsLayout));
+                    }
+                    proof {
+                        assert forall|i: int| 0 <= i < modules@.len() implies align % modules@[i].align
+                            == 0 by {
+                            lemma_mod_trans(align as int, old_align as int, modules@[i].align as int);
+                        };
+                    }
+                    layout_align = align;
+                }
+                proof {
+                    lemma_mod_self_0(align as int);
+                }
+
+                let module_id = modules.len() as u64 + 1;
+                let m = TlsModule {
+                    object_index: obj_idx,
+                    module_id,
+                    tp_offset,
+                    image,
+                    memsz: ph.p_memsz,
+                    align,
+                };
+                let ghost old_modules = modules@;
+                modules.push(m);
+                proof {
+                    let p = pos as int;
+                    assert(tls_align_of(ph) == align);
+                    assert(add_u64_or_zero(offset, ph.p_memsz) == end);
+                    assert(tls_offset_before(parsed@, order, (pos + 1) as nat) == tp_offset);
+                    assert(tls_modules_before(parsed@, order, (pos + 1) as nat) == modules@.len());
+                    assert(tls_module_matches(parsed@, order, p, m));
+                    assert forall|i: int|
+                        0 <= i < modules@.len() implies exists|q: int|
+                            tls_module_matches(parsed@, order, q, modules@[i]) && tls_modules_before(
+                                parsed@,
+                                order,
+                                q as nat,
+                            ) == i by {
+                        if i < old_modules.len() {
+                            assert(modules@[i] == old_modules[i]);
+                        } else {
+                            assert(modules@[i] == m);
+                            assert(tls_module_matches(parsed@, order, p, modules@[i]));
+                            assert(tls_modules_before(parsed@, order, p as nat) == i);
+                        }
+                    };
+                    assert forall|i: int, j: int| 0 <= i < j < modules@.len() implies (modules@[i].tp_offset
+                        as int) + (modules@[j].memsz as int) <= modules@[j].tp_offset as int by {
+                        if j < old_modules.len() {
+                            assert(tls_blocks_disjoint(old_modules));
+                        } else {
+                            assert(modules@[i].tp_offset <= offset);
+                        }
+                    };
+                }
+                offset = tp_offset;
+            }
+        }
+        pos = pos + 1;
+    }
+
+    let static_size = match align_up_exec(offset, layout_align) {
+        Some(v) => v,
+        None => {
+            return Err(loader_error(LoaderStage::TlsLayout, &Vec::new(), LoaderErrorReason::BadTlsLayout));
+        }
+    };
+    proof {
+        assert(pos == order.len());
+        assert(static_size == tls_static_size(parsed@, order));
+    }
+    Ok(TlsLayout { modules, static_size, align: layout_align })
+}
+
+} // verus!
@@ -0,0 +1,179 @@
+use crate::relocate_plan_spec::*;
+use crate::types::*;
+use vstd::prelude::*;
+
+verus! {
+
+pub open spec fn tls_align_of(ph: ProgramHeader) -> u64 {
+    if ph.p_align == 0 {
+        1
+    } else {
+        ph.p_align
+    }
+}
+
+pub open spec fn align_up_or_zero(v: u64, align: u64) -> u64 {
+    if align == 0 {
+        0
+    } else {
+        let rem = v % align;
+        if rem == 0 {
+            v
+        } else {
+            let raw = (v as int) + (align as int) - (rem as int);
+            if raw <= u64::MAX as int {
+                raw as u64
+            } else {
+                0
+            }
+        }
+    }
+}
+
+// x86-64 uses TLS variant II: every static block sits below the thread
+// pointer, and a module's block starts `tp_offset` bytes before it.
+pub open spec fn tls_block_offset(prev: u64, ph: ProgramHeader) -> u64 {
+    align_up_or_zero(add_u64_or_zero(prev, ph.p_memsz), tls_align_of(ph))
+}
+
+pub open spec fn object_tls_at(parsed: Seq<ParsedObject>, order: Seq<usize>, pos: int) -> Option<ProgramHeader> {
+    if 0 <= pos < order.len() && (order[pos] as int) < parsed.len() {
+        parsed[order[pos] as int].tls
+    } else {
+        None
+    }
+}
+
+pub open spec fn tls_offset_before(parsed: Seq<ParsedObject>, order: Seq<usize>, pos: nat) -> u64
+    decreases pos,
+{
+    if pos == 0 {
+        0
+    } else {
+        let prev = tls_offset_before(parsed, order, (pos - 1) as nat);
+        match object_tls_at(parsed, order, pos - 1) {
+            Some(ph) => tls_block_offset(prev, ph),
+            None => prev,
+        }
+    }
+}
+
+pub open spec fn tls_modules_before(parsed: Seq<ParsedObject>, order: Seq<usize>, pos: nat) -> nat
+    decreases pos,
+{
+    if pos == 0 {
+        0
+    } else {
+        let prev = tls_modules_before(parsed, order, (pos - 1) as nat);
+        match object_tls_at(parsed, order, pos - 1) {
+            Some(_) => prev + 1,
+            None => prev,
+        }
+    }
+}
+
+// Alignment of the whole static area: each module's alignment must divide
+// or be divided by the ones before it, and the larger one wins.
+pub open spec fn tls_area_align_before(parsed: Seq<ParsedObject>, order: Seq<usize>, pos: nat) -> u64
+    decreases pos,
+{
+    if pos == 0 {
+        1
+    } else {
+        let prev = tls_area_align_before(parsed, order, (pos - 1) as nat);
+        match object_tls_at(parsed, order, pos - 1) {
+            Some(ph) => {
+                if prev % tls_align_of(ph) == 0 {
+                    prev
+                } else {
+                    tls_align_of(ph)
+                }
+            },
+            None => prev,
+        }
+    }
+}
+
+pub open spec fn tls_static_size(parsed: Seq<ParsedObject>, order: Seq<usize>) -> u64 {
+    align_up_or_zero(
+        tls_offset_before(parsed, order, order.len()),
+        tls_area_align_before(parsed, order, order.len()),
+    )
+}
+
+pub open spec fn tls_image(obj: ParsedObject, ph: ProgramHeader) -> Seq<u8> {
+    obj.file_bytes@.subrange(ph.p_offset as int, (ph.p_offset as int) + (ph.p_filesz as int))
+}
+
+pub open spec fn tls_module_matches(
+    parsed: Seq<ParsedObject>,
+    order: Seq<usize>,
+    pos: int,
+    m: TlsModule,
+) -> bool {
+    &&& 0 <= pos < order.len()
+    &&& m.object_index == order[pos]
+    &&& match object_tls_at(parsed, order, pos) {
+        Some(ph) => {
+            &&& m.module_id == tls_modules_before(parsed, order, pos as nat) + 1
+            &&& m.tp_offset == tls_offset_before(parsed, order, (pos + 1) as nat)
+            &&& m.memsz == ph.p_memsz
+            &&& m.align == tls_align_of(ph)
+            &&& m.image@ == tls_image(parsed[order[pos] as int], ph)
+        }
+        None => false,
+    }
+}
+
+// The layout's module for input object `obj_idx`, if the object has a PT_TLS
+// block.
+pub open spec fn tls_module_from(modules: Seq<TlsModule>, obj_idx: int, i: nat) -> Option<TlsModule>
+    decreases modules.len() - i,
+{
+    if i >= modules.len() {
+        None
+    } else if modules[i as int].object_index as int == obj_idx {
+        Some(modules[i as int])
+    } else {
+        tls_module_from(modules, obj_idx, (i + 1) as nat)
+    }
+}
+
+pub open spec fn tls_module_of(layout: TlsLayout, obj_idx: int) -> Option<TlsModule> {
+    tls_module_from(layout.modules@, obj_idx, 0)
+}
+
+pub open spec fn tls_blocks_disjoint(modules: Seq<TlsModule>) -> bool {
+    forall|i: int, j: int|
+        0 <= i < j < modules.len() ==> (modules[i].tp_offset as int) + (modules[j].memsz as int)
+            <= modules[j].tp_offset as int
+}
+
+pub open spec fn tls_layout_stage_spec(
+    parsed: Seq<ParsedObject>,
+    discovered: DiscoveryResult,
+    layout: TlsLayout,
+) -> bool {
+    let order = discovered.order@;
+    let modules = layout.modules@;
+    &&& modules.len() == tls_modules_before(parsed, order, order.len())
+    &&& forall|i: int|
+        0 <= i < modules.len() ==> exists|p: int|
+            tls_module_matches(parsed, order, p, modules[i]) && tls_modules_before(parsed, order, p as nat) == i
+    &&& forall|i: int|
+        0 <= i < modules.len() ==> {
+            &&& modules[i].module_id == i + 1
+            &&& modules[i].memsz <= modules[i].tp_offset
+            &&& modules[i].tp_offset <= layout.static_size
+            &&& modules[i].align > 0
+            &&& modules[i].tp_offset % modules[i].align == 0
+            &&& layout.align % modules[i].align == 0
+        }
+    &&& tls_blocks_disjoint(modules)
+    &&& layout.align > 0
+    &&& layout.align == tls_area_align_before(parsed, order, order.len())
+    &&& layout.static_size == tls_static_size(parsed, order)
+    &&& layout.static_size % layout.align == 0
+}
+
+} // verus!
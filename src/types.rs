@@ -6,15 +6,68 @@ verus! {
 pub struct LoaderObject {
     pub name: Vec<u8>,
     pub bytes: Vec<u8>,
+    // Requested load bias for an `ET_DYN` object; `None` uses the packed layout.
+    pub load_base: Option<u64>,
 }
 
 #[derive(Clone, Debug)]
 pub struct LoaderInput {
     pub objects: Vec<LoaderObject>,
+    pub strong_before_weak: bool,
+    // Reject any region that would be both writable and executable.
+    pub w_xor_x: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoaderStage {
+    Input,
+    Parse,
+    Discover,
+    Resolve,
+    MmapPlan,
+    TlsLayout,
+    RelocatePlan,
+    RelocateApply,
+    Final,
+    Runtime,
+}
+
+#[derive(Clone, Debug)]
+pub enum LoaderErrorReason {
+    Io { errno: i32 },
+    Truncated,
+    BadElfHeader,
+    BadProgramHeaders,
+    BadDynamicSection,
+    BadSymbolTable,
+    BadRelocationTable,
+    UnmappedAddress { vaddr: u64 },
+    UnsupportedRelocType { reloc_type: u32, offset: u64 },
+    MissingNeeded { name: Vec<u8> },
+    MissingVersion { version: Vec<u8>, file: Vec<u8> },
+    BadSymbolIndex { sym_index: usize },
+    UndefinedSymbol { name: Vec<u8> },
+    MissingCopySource { name: Vec<u8> },
+    UnsupportedSymbol { name: Vec<u8>, shndx: u16, sym_type: u8 },
+    InvalidObjectIndex { index: usize },
+    NoInitOrder,
+    BadLoadBase { base: u64 },
+    BadTlsLayout,
+    SegmentOverlap { start: u64, len: u64 },
+    WritableExecutable { start: u64, len: u64 },
+    AddressInUse { start: u64, len: u64 },
+    Mmap { start: u64, errno: i32 },
+    Mprotect { start: u64, errno: i32 },
+    StackSetup,
+    SetThreadPointer { errno: i32 },
 }
 
 #[derive(Clone, Debug)]
-pub struct LoaderError {}
+pub struct LoaderError {
+    pub stage: LoaderStage,
+    pub object_name: Vec<u8>,
+    pub reason: LoaderErrorReason,
+}
 
 #[verifier::external_body]
 pub fn clone_u8_vec(v: &Vec<u8>) -> (out: Vec<u8>)
@@ -25,6 +78,38 @@ pub fn clone_u8_vec(v: &Vec<u8>) -> (out: Vec<u8>)
     v.clone()
 }
 
+pub fn loader_error(stage: LoaderStage, object_name: &Vec<u8>, reason: LoaderErrorReason) -> LoaderError {
+    LoaderError { stage, object_name: clone_u8_vec(object_name), reason }
+}
+
+pub fn cstr_at(bytes: &[u8], off: u32) -> (name: Vec<u8>)
+    ensures
+        name@.len() <= bytes@.len(),
+        forall|k: int| 0 <= k < name@.len() ==> name@[k] == bytes@[off as int + k] && name@[k] != 0,
+{
+    let mut name: Vec<u8> = Vec::new();
+    let start = off as usize;
+    if start >= bytes.len() {
+        return name;
+    }
+    let mut i = start;
+    while i < bytes.len()
+        invariant
+            start == off as int,
+            start <= i <= bytes@.len(),
+            name@.len() == i - start,
+            forall|k: int| 0 <= k < name@.len() ==> name@[k] == bytes@[start + k] && name@[k] != 0,
+        decreases bytes.len() - i,
+    {
+        if bytes[i] == 0 {
+            break;
+        }
+        name.push(bytes[i]);
+        i += 1;
+    }
+    name
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ProtFlags {
     pub read: bool,
@@ -43,12 +128,38 @@ impl ProtFlags {
     }
 }
 
+// An 8-byte little-endian word written over a region's initial contents.
+#[derive(Clone, Copy, Debug)]
+pub struct PlanPatch {
+    pub offset: usize,
+    pub value: u64,
+}
+
+// Bytes `[offset, offset + len)` of a region, taken from `file_len` file bytes
+// at `file_offset` followed by zeros. Used for the part of a shared page that
+// belongs to the later segment.
+#[derive(Clone, Copy, Debug)]
+pub struct PlanFill {
+    pub offset: usize,
+    pub len: usize,
+    pub file_offset: u64,
+    pub file_len: usize,
+}
+
+// A mapped region. Bytes `[0, file_len)` come from the file of input object
+// `object_index` starting at `file_offset`, the rest is zero-filled, the
+// `overlay` replaces its range, and the `patches` are then applied in order.
 #[derive(Clone, Debug)]
 pub struct MmapPlan {
     pub object_name: Vec<u8>,
+    pub object_index: usize,
     pub start: u64,
-    pub bytes: Vec<u8>,
+    pub len: usize,
     pub prot: ProtFlags,
+    pub file_offset: u64,
+    pub file_len: usize,
+    pub overlay: Option<PlanFill>,
+    pub patches: Vec<PlanPatch>,
 }
 
 #[derive(Clone, Debug)]
@@ -71,10 +182,21 @@ pub struct RelocWrite {
     pub reloc_type: u32,
 }
 
+#[derive(Clone, Debug)]
+pub struct ResolverWrite {
+    pub object_name: Vec<u8>,
+    pub write_addr: u64,
+    pub resolver_pc: u64,
+    pub addend: i64,
+    pub reloc_type: u32,
+}
+
 #[derive(Clone, Debug)]
 pub struct RelocatePlanOutput {
     pub mmap_plans: Vec<MmapPlan>,
+    pub tls: TlsLayout,
     pub reloc_plan: Vec<RelocWrite>,
+    pub resolver_plan: Vec<ResolverWrite>,
     pub parsed: Vec<ParsedObject>,
     pub discovered: DiscoveryResult,
     pub resolved: ResolutionResult,
@@ -83,18 +205,39 @@ pub struct RelocatePlanOutput {
 #[derive(Clone, Debug)]
 pub struct RelocateApplyOutput {
     pub mmap_plans: Vec<MmapPlan>,
+    pub tls: TlsLayout,
     pub reloc_writes: Vec<RelocWrite>,
+    pub resolver_writes: Vec<ResolverWrite>,
     pub parsed: Vec<ParsedObject>,
     pub discovered: DiscoveryResult,
     pub resolved: ResolutionResult,
 }
 
+#[derive(Clone, Debug)]
+pub struct TlsModule {
+    pub object_index: usize,
+    pub module_id: u64,
+    pub tp_offset: u64,
+    pub image: Vec<u8>,
+    pub memsz: u64,
+    pub align: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct TlsLayout {
+    pub modules: Vec<TlsModule>,
+    pub static_size: u64,
+    pub align: u64,
+}
+
 #[derive(Clone, Debug)]
 pub struct LoaderOutput {
     pub entry_pc: u64,
     pub constructors: Vec<InitCall>,
     pub destructors: Vec<TermCall>,
     pub mmap_plans: Vec<MmapPlan>,
+    pub tls: TlsLayout,
+    pub resolver_writes: Vec<ResolverWrite>,
     // Debug-only intermediate results, included for troubleshooting.
     pub reloc_writes: Vec<RelocWrite>,
     pub parsed: Vec<ParsedObject>,
@@ -102,7 +245,7 @@ pub struct LoaderOutput {
     pub resolved: ResolutionResult,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct ProgramHeader {
     pub p_type: u32,
     pub p_flags: u32,
@@ -110,6 +253,7 @@ pub struct ProgramHeader {
     pub p_vaddr: u64,
     pub p_filesz: u64,
     pub p_memsz: u64,
+    pub p_align: u64,
 }
 
 #[derive(Clone, Debug)]
@@ -124,9 +268,14 @@ pub struct DynamicInfo {
     pub strsz: u64,
     pub symtab_vaddr: u64,
     pub syment: u64,
+    pub hash_vaddr: u64,
+    pub gnu_hash_vaddr: u64,
     pub rela_vaddr: u64,
     pub relasz: u64,
     pub relaent: u64,
+    pub rel_vaddr: u64,
+    pub relsz: u64,
+    pub relent: u64,
     pub jmprel_vaddr: u64,
     pub pltrelsz: u64,
     pub pltrel: u64,
@@ -134,6 +283,51 @@ pub struct DynamicInfo {
     pub init_array_sz: u64,
     pub fini_array_vaddr: u64,
     pub fini_array_sz: u64,
+    pub preinit_array_vaddr: u64,
+    pub preinit_array_sz: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolCountSource {
+    Hash,
+    GnuHash,
+    SectionLayout,
+}
+
+#[derive(Clone, Debug)]
+pub struct GnuHashTable {
+    pub symoffset: u32,
+    pub bloom_shift: u32,
+    pub bloom: Vec<u64>,
+    pub buckets: Vec<u32>,
+    pub chains: Vec<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SysvHashTable {
+    pub buckets: Vec<u32>,
+    pub chains: Vec<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub enum SymbolHashTable {
+    Gnu(GnuHashTable),
+    Sysv(SysvHashTable),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct VersionDef {
+    pub index: u16,
+    pub flags: u16,
+    pub name_offset: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct VersionNeed {
+    pub file_offset: u32,
+    pub index: u16,
+    pub flags: u16,
+    pub name_offset: u32,
 }
 
 #[derive(Clone, Debug)]
@@ -152,11 +346,24 @@ impl DynSymbol {
     }
 }
 
+// The dynamic table a relocation entry was read from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelocTable {
+    Rela,
+    // 16-byte entries without an addend field: the word stored at `offset`
+    // in the file image is the addend.
+    Rel,
+    // Decoded from the DT_RELR bitmap: RELATIVE, with the word at `offset`
+    // read into `addend` while parsing.
+    Relr,
+}
+
 #[derive(Clone, Debug)]
 pub struct RelaEntry {
     pub offset: u64,
     pub info: u64,
     pub addend: i64,
+    pub table: RelocTable,
 }
 
 impl RelaEntry {
@@ -173,16 +380,30 @@ impl RelaEntry {
 pub struct ParsedObject {
     pub input_name: Vec<u8>,
     pub file_bytes: Vec<u8>,
+    pub load_base: Option<u64>,
     pub elf_type: u16,
     pub entry: u64,
+    // A static or static-pie executable (no PT_INTERP): its own startup code
+    // relocates itself, protects RELRO and runs its constructors.
+    pub static_exec: bool,
     pub phdrs: Vec<ProgramHeader>,
+    pub tls: Option<ProgramHeader>,
+    pub relro: Option<ProgramHeader>,
     pub dynamic: DynamicInfo,
     pub needed_offsets: Vec<u32>,
     pub soname_offset: Option<u32>,
     pub dynstr: Vec<u8>,
     pub dynsyms: Vec<DynSymbol>,
+    pub dynsym_count_source: SymbolCountSource,
+    pub hash_table: Option<SymbolHashTable>,
+    pub versym: Vec<u16>,
+    pub verdefs: Vec<VersionDef>,
+    pub verneeds: Vec<VersionNeed>,
     pub relas: Vec<RelaEntry>,
     pub jmprels: Vec<RelaEntry>,
+    pub init: Option<u64>,
+    pub fini: Option<u64>,
+    pub preinit_array: Vec<u64>,
     pub init_array: Vec<u64>,
     pub fini_array: Vec<u64>,
 }
@@ -190,6 +411,7 @@ pub struct ParsedObject {
 #[derive(Clone, Debug)]
 pub struct DiscoveryResult {
     pub order: Vec<usize>,
+    pub init_order: Vec<usize>,
 }
 
 #[derive(Clone, Debug)]
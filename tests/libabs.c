@@ -0,0 +1,5 @@
+#include "libabs.h"
+
+unsigned long libabs_marker_addr(void) {
+    return (unsigned long)libabs_marker;
+}
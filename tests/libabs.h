@@ -0,0 +1,10 @@
+#ifndef LIBABS_H
+#define LIBABS_H
+
+#define LIBABS_MARKER 0x5a5a0000UL
+
+extern char libabs_marker[];
+
+unsigned long libabs_marker_addr(void);
+
+#endif
@@ -0,0 +1,15 @@
+#include "libbig.h"
+
+static unsigned char libbig_pad[LIBBIG_PAD_SIZE];
+
+unsigned long libbig_fill(void) {
+    unsigned long sum = 0;
+    for (unsigned long i = 0; i < LIBBIG_PAD_SIZE; i += 4096) {
+        libbig_pad[i] = 1;
+    }
+    libbig_pad[LIBBIG_PAD_SIZE - 1] = 1;
+    for (unsigned long i = 0; i < LIBBIG_PAD_SIZE; i++) {
+        sum += libbig_pad[i];
+    }
+    return sum;
+}
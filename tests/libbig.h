@@ -0,0 +1,8 @@
+#ifndef LIBBIG_H
+#define LIBBIG_H
+
+#define LIBBIG_PAD_SIZE (3UL << 20)
+
+unsigned long libbig_fill(void);
+
+#endif
@@ -0,0 +1,5 @@
+#include "libdiamond.h"
+
+const char *diamond_name(void) {
+    return "libdiambottom";
+}
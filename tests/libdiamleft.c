@@ -0,0 +1,5 @@
+#include "libdiamond.h"
+
+const char *libdiamleft_name(void) {
+    return diamond_name();
+}
@@ -0,0 +1,7 @@
+#ifndef LIBDIAMOND_H
+#define LIBDIAMOND_H
+
+const char *diamond_name(void);
+const char *libdiamleft_name(void);
+
+#endif
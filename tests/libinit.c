@@ -0,0 +1,28 @@
+#include <stdio.h>
+#include "libfoo.h"
+#include "libinit.h"
+
+void libinit_print(void) {
+    printf("[libinit] function\n");
+}
+
+__attribute__((constructor))
+static void libinit_ctor(void) {
+    printf("[libinit] ctor\n");
+}
+
+__attribute__((destructor))
+static void libinit_dtor(void) {
+    printf("[libinit] dtor\n");
+}
+
+__attribute__((used, section(".init_array")))
+static void (*libinit_foreign_ctor)(void) = libfoo_print;
+
+void libinit_legacy_init(void) {
+    printf("[libinit] DT_INIT\n");
+}
+
+void libinit_legacy_fini(void) {
+    printf("[libinit] DT_FINI\n");
+}
@@ -0,0 +1,6 @@
+#ifndef LIBINIT_H
+#define LIBINIT_H
+
+void libinit_print(void);
+
+#endif
@@ -0,0 +1,11 @@
+#include "libtls.h"
+
+__thread int libtls_counter = LIBTLS_INITIAL;
+
+int libtls_get(void) {
+    return libtls_counter;
+}
+
+int *libtls_addr(void) {
+    return &libtls_counter;
+}
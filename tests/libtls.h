@@ -0,0 +1,11 @@
+#ifndef LIBTLS_H
+#define LIBTLS_H
+
+#define LIBTLS_INITIAL 7
+
+extern __thread int libtls_counter;
+
+int libtls_get(void);
+int *libtls_addr(void);
+
+#endif
@@ -0,0 +1,13 @@
+#include "libvis.h"
+
+const char *vis_name(void) {
+    return "libvis";
+}
+
+const char *vis_protected_name(void) {
+    return "libvis";
+}
+
+const char *vis_weak_name(void) {
+    return "libvis";
+}
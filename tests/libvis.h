@@ -0,0 +1,9 @@
+#ifndef LIBVIS_H
+#define LIBVIS_H
+
+const char *vis_name(void);
+const char *vis_protected_name(void);
+const char *vis_weak_name(void);
+void libvishide_print(void);
+
+#endif
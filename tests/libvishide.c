@@ -0,0 +1,20 @@
+#include <stdio.h>
+
+__attribute__((visibility("hidden")))
+const char *vis_name(void) {
+    return "libvishide";
+}
+
+__attribute__((visibility("protected")))
+const char *vis_protected_name(void) {
+    return "libvishide";
+}
+
+__attribute__((weak))
+const char *vis_weak_name(void) {
+    return "libvishide";
+}
+
+void libvishide_print(void) {
+    printf("[libvishide] hidden=%s protected=%s\n", vis_name(), vis_protected_name());
+}
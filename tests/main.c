@@ -4,6 +4,12 @@
 #include <stdlib.h>
 #include "libfoo.h"
 #include "libbar.h"
+#include "libvis.h"
+#include "libabs.h"
+#include "libinit.h"
+#include "libdiamond.h"
+#include "libbig.h"
+#include "libtls.h"
 
 #define panic(...)            \
     do {                      \
@@ -13,11 +19,41 @@
 
 static __thread int tls;
 
+// libtls_counter lives in libtls.so's static TLS block: main reaches it
+// through a TPOFF64 slot, libtls.so through DTPMOD64/DTPOFF64 and
+// __tls_get_addr. Constructors run on the thread pointer the loader set up;
+// musl's start code later installs its own from the program's PT_TLS alone,
+// so library TLS is checked before that.
+static void test_lib_tls(void) {
+    printf("[main] libtls_counter=%d libtls_get=%d\n", libtls_counter, libtls_get());
+    if (libtls_counter != LIBTLS_INITIAL || libtls_get() != LIBTLS_INITIAL || &libtls_counter != libtls_addr()) {
+        panic("[main] libtls.so TLS block is not where the library expects it\n");
+    }
+    libtls_counter = LIBTLS_INITIAL + 1;
+    printf("[main] libtls_counter=%d libtls_get=%d\n", libtls_counter, libtls_get());
+    if (libtls_get() != LIBTLS_INITIAL + 1) {
+        panic("[main] libtls.so sees a different TLS block than main\n");
+    }
+}
+
 __attribute__((constructor))
 static void main_ctor(void) {
     printf("[main] ctor\n");
+    test_lib_tls();
+}
+
+__attribute__((destructor))
+static void main_dtor(void) {
+    printf("[main] dtor\n");
 }
 
+static void main_preinit(void) {
+    printf("[main] preinit\n");
+}
+
+__attribute__((used, section(".preinit_array")))
+static void (*main_preinit_entry)(void) = main_preinit;
+
 static void *thread_entry(void *arg) {
     (void)arg;
     printf("[thread] tls=%d, &tls=%p\n", tls, &tls);
@@ -45,10 +81,55 @@ static void test_pthread(void) {
     printf("[main] tls=%d, &tls=%p\n", tls, &tls);
 }
 
-int main(void) {
+static void test_visibility(void) {
+    libvishide_print();
+    printf("[main] vis_name=%s vis_protected_name=%s vis_weak_name=%s\n",
+           vis_name(), vis_protected_name(), vis_weak_name());
+    if (strcmp(vis_name(), "libvis") != 0) {
+        panic("[main] vis_name bound to a hidden definition\n");
+    }
+    if (strcmp(vis_protected_name(), "libvishide") != 0) {
+        panic("[main] vis_protected_name skipped the first protected definition\n");
+    }
+}
+
+static void test_absolute_symbol(void) {
+    unsigned long addr = libabs_marker_addr();
+    printf("[main] libabs_marker=0x%lx\n", addr);
+    if (addr != LIBABS_MARKER) {
+        panic("[main] absolute symbol was rebased\n");
+    }
+}
+
+// main needs libdiamleft then libdiamright; only libdiamleft needs
+// libdiambottom. Breadth-first, libdiamright precedes libdiambottom.
+static void test_diamond(void) {
+    printf("[main] diamond_name=%s libdiamleft_name=%s\n", diamond_name(), libdiamleft_name());
+    if (strcmp(diamond_name(), "libdiamright") != 0 || strcmp(libdiamleft_name(), "libdiamright") != 0) {
+        panic("[main] global scope is not breadth-first\n");
+    }
+}
+
+static void test_big_library(void) {
+    unsigned long sum = libbig_fill();
+    printf("[main] libbig sum=%lu\n", sum);
+    if (sum != LIBBIG_PAD_SIZE / 4096 + 1) {
+        panic("[main] libbig.so is not fully mapped\n");
+    }
+}
+
+int main(int argc, char **argv) {
     printf("[main] entry\n");
+    for (int i = 0; i < argc; i++) {
+        printf("[main] argv[%d]=%s\n", i, argv[i]);
+    }
     libfoo_print();
+    libinit_print();
     libbar_step(3);
+    test_visibility();
+    test_absolute_symbol();
+    test_diamond();
+    test_big_library();
 
     printf("[main] pthread test start\n");
     test_pthread();
@@ -0,0 +1,41 @@
+#include <string.h>
+#include <unistd.h>
+
+// Entered without libc start code: `_start` hands the loader's `rtld_fini`
+// from %rdx to `rtldfini_main`, which calls it and leaves with `_exit`, so
+// the destructor only runs if the finalizer passed in %rdx works. Output
+// goes through `write` because stdio is never set up.
+__asm__(
+    ".text\n"
+    ".global _start\n"
+    "_start:\n"
+    "    xor %ebp, %ebp\n"
+    "    mov %rdx, %rdi\n"
+    "    and $-16, %rsp\n"
+    "    call rtldfini_main\n"
+    "    hlt\n");
+
+static void say(const char *s) {
+    write(1, s, strlen(s));
+}
+
+__attribute__((constructor))
+static void rtldfini_ctor(void) {
+    say("[rtldfini] ctor\n");
+}
+
+__attribute__((destructor))
+static void rtldfini_dtor(void) {
+    say("[rtldfini] dtor\n");
+}
+
+__attribute__((noreturn, used))
+void rtldfini_main(void (*fini)(void)) {
+    say("[rtldfini] entry\n");
+    if (fini) {
+        fini();
+        fini();
+    }
+    say("[rtldfini] exit\n");
+    _exit(0);
+}
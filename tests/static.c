@@ -0,0 +1,26 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+static __thread int tls = 41;
+
+// A pointer in writable data: a RELATIVE relocation in the static-pie build.
+static const char *greeting = "[static] relocated pointer";
+
+__attribute__((constructor))
+static void static_ctor(void) {
+    printf("[static] ctor\n");
+}
+
+__attribute__((destructor))
+static void static_dtor(void) {
+    printf("[static] dtor\n");
+}
+
+int main(int argc, char **argv) {
+    (void)argv;
+    tls++;
+    printf("%s\n", greeting);
+    printf("[static] tls=%d\n", tls);
+    printf("[static] argc=%d\n", argc);
+    return 0;
+}